@@ -0,0 +1,355 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use core::fmt;
+use core::str::FromStr;
+
+/// An AES state or block, in FIPS-197 byte order (column-major, byte `i` is
+/// at row `i % 4` of column `i / 4`).
+pub type Block = [u8; 16];
+
+/// AES substitution table.
+pub const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// Inverse of the AES substitution table.
+pub const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Operations of an AES round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesOp {
+    SubBytes,
+    ShiftRows,
+    MixColumns,
+    AddRoundKey,
+}
+
+impl FromStr for AesOp {
+    type Err = AesError;
+
+    /// Parse an operation from its snake case name (e.g., `"sub_bytes"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sub_bytes" => Ok(AesOp::SubBytes),
+            "shift_rows" => Ok(AesOp::ShiftRows),
+            "mix_columns" => Ok(AesOp::MixColumns),
+            "add_round_key" => Ok(AesOp::AddRoundKey),
+            _ => Err(AesError::UnknownOperation(s.to_string())),
+        }
+    }
+}
+
+/// Intermediate value of an AES encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intermediate {
+    /// Output of an operation in a given round.
+    ///
+    /// Round 0 only contains the initial `AddRoundKey`, and the last round
+    /// has no `MixColumns`. The output of `AddRoundKey` is the round state.
+    Op { round: usize, op: AesOp },
+    /// Difference (XOR) between the input state of the last round and the
+    /// ciphertext.
+    ///
+    /// The Hamming weight of each byte is the Hamming distance of an
+    /// implementation that overwrites its state register with the ciphertext.
+    LastRoundHd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AesError {
+    InvalidKeyLength(usize),
+    InvalidIntermediate { round: usize, op: AesOp },
+    UnknownOperation(String),
+}
+
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AesError::InvalidKeyLength(n) => {
+                write!(
+                    f,
+                    "invalid AES key length: {n} bytes (expected 16, 24 or 32)"
+                )
+            }
+            AesError::InvalidIntermediate { round, op } => {
+                write!(f, "no {op:?} operation in AES round {round}")
+            }
+            AesError::UnknownOperation(s) => write!(f, "unknown AES operation: {s:?}"),
+        }
+    }
+}
+
+impl std::error::Error for AesError {}
+
+/// AES-128/192/256 block cipher, with access to intermediate states.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::aes::{Aes, AesOp, Intermediate};
+///
+/// // Test vector from FIPS-197, appendix C.1.
+/// let key: Vec<u8> = (0..16).collect();
+/// let pt = [
+///     0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+///     0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+/// ];
+/// let aes = Aes::new(&key).unwrap();
+/// let ct = aes.encrypt(&pt);
+/// assert_eq!(ct[..4], [0x69, 0xc4, 0xe0, 0xd8]);
+/// assert_eq!(aes.decrypt(&ct), pt);
+///
+/// let s1 = aes
+///     .intermediate(&pt, Intermediate::Op { round: 1, op: AesOp::SubBytes })
+///     .unwrap();
+/// assert_eq!(s1[..4], [0x63, 0xca, 0xb7, 0x04]);
+///
+/// // AES-192 and AES-256 (FIPS-197, appendix C.2 and C.3).
+/// let key: Vec<u8> = (0..24).collect();
+/// assert_eq!(Aes::new(&key).unwrap().encrypt(&pt)[..4], [0xdd, 0xa9, 0x7c, 0xa4]);
+/// let key: Vec<u8> = (0..32).collect();
+/// assert_eq!(Aes::new(&key).unwrap().encrypt(&pt)[..4], [0x8e, 0xa2, 0xb7, 0xca]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aes {
+    round_keys: Vec<Block>,
+}
+
+impl Aes {
+    /// Create a cipher from a 16, 24 or 32 bytes key.
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            n => return Err(AesError::InvalidKeyLength(n)),
+        };
+        let nr = nk + 6;
+        let mut w: Vec<[u8; 4]> = key.chunks(4).map(|c| c.try_into().unwrap()).collect();
+        for i in nk..4 * (nr + 1) {
+            let mut t = w[i - 1];
+            if i % nk == 0 {
+                t.rotate_left(1);
+                t.iter_mut().for_each(|x| *x = SBOX[*x as usize]);
+                t[0] ^= RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                t.iter_mut().for_each(|x| *x = SBOX[*x as usize]);
+            }
+            let prev = w[i - nk];
+            w.push(core::array::from_fn(|j| prev[j] ^ t[j]));
+        }
+        let round_keys = w
+            .chunks(4)
+            .map(|c| core::array::from_fn(|i| c[i / 4][i % 4]))
+            .collect();
+        Ok(Aes { round_keys })
+    }
+
+    /// Number of rounds (10, 12 or 14).
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+
+    /// Expanded key, round key 0 is used for the initial whitening.
+    pub fn round_keys(&self) -> &[Block] {
+        &self.round_keys
+    }
+
+    pub fn encrypt(&self, block: &Block) -> Block {
+        let mut state = *block;
+        self.encrypt_until(&mut state, self.rounds(), AesOp::AddRoundKey);
+        state
+    }
+
+    pub fn decrypt(&self, block: &Block) -> Block {
+        let nr = self.rounds();
+        let mut state = *block;
+        add_round_key(&mut state, &self.round_keys[nr]);
+        for r in (0..nr).rev() {
+            inv_shift_rows(&mut state);
+            inv_sub_bytes(&mut state);
+            add_round_key(&mut state, &self.round_keys[r]);
+            if r != 0 {
+                inv_mix_columns(&mut state);
+            }
+        }
+        state
+    }
+
+    /// Compute an intermediate value of the encryption of `block`.
+    pub fn intermediate(&self, block: &Block, target: Intermediate) -> Result<Block, AesError> {
+        self.check_intermediate(target)?;
+        let nr = self.rounds();
+        let mut state = *block;
+        match target {
+            Intermediate::Op { round, op } => self.encrypt_until(&mut state, round, op),
+            Intermediate::LastRoundHd => {
+                self.encrypt_until(&mut state, nr - 1, AesOp::AddRoundKey);
+                let mut ct = state;
+                sub_bytes(&mut ct);
+                shift_rows(&mut ct);
+                add_round_key(&mut ct, &self.round_keys[nr]);
+                add_round_key(&mut state, &ct);
+            }
+        }
+        Ok(state)
+    }
+
+    /// Check that `target` exists for this key size.
+    pub fn check_intermediate(&self, target: Intermediate) -> Result<(), AesError> {
+        let Intermediate::Op { round, op } = target else {
+            return Ok(());
+        };
+        let valid = match round {
+            0 => op == AesOp::AddRoundKey,
+            r if r == self.rounds() => op != AesOp::MixColumns,
+            r => r < self.rounds(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(AesError::InvalidIntermediate { round, op })
+        }
+    }
+
+    /// Encrypt `state` in place, stopping after `op` of round `round`.
+    fn encrypt_until(&self, state: &mut Block, round: usize, op: AesOp) {
+        let nr = self.rounds();
+        add_round_key(state, &self.round_keys[0]);
+        for r in 1..=round {
+            let stop = r == round;
+            sub_bytes(state);
+            if stop && op == AesOp::SubBytes {
+                return;
+            }
+            shift_rows(state);
+            if stop && op == AesOp::ShiftRows {
+                return;
+            }
+            if r != nr {
+                mix_columns(state);
+                if stop && op == AesOp::MixColumns {
+                    return;
+                }
+            }
+            add_round_key(state, &self.round_keys[r]);
+        }
+    }
+}
+
+pub(crate) fn add_round_key(state: &mut Block, rk: &Block) {
+    state.iter_mut().zip(rk).for_each(|(s, k)| *s ^= k);
+}
+
+fn sub_bytes(state: &mut Block) {
+    state.iter_mut().for_each(|x| *x = SBOX[*x as usize]);
+}
+
+fn inv_sub_bytes(state: &mut Block) {
+    state.iter_mut().for_each(|x| *x = INV_SBOX[*x as usize]);
+}
+
+pub(crate) fn shift_rows(state: &mut Block) {
+    let s = *state;
+    for (i, x) in state.iter_mut().enumerate() {
+        let (c, r) = (i / 4, i % 4);
+        *x = s[4 * ((c + r) % 4) + r];
+    }
+}
+
+fn inv_shift_rows(state: &mut Block) {
+    let s = *state;
+    for (i, x) in state.iter_mut().enumerate() {
+        let (c, r) = (i / 4, i % 4);
+        *x = s[4 * ((c + 4 - r) % 4) + r];
+    }
+}
+
+/// Multiplication in GF(2^8) modulo the AES polynomial.
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            p ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    p
+}
+
+fn mix_columns_with(state: &mut Block, m: [u8; 4]) {
+    for col in state.chunks_mut(4) {
+        let a: [u8; 4] = col.try_into().unwrap();
+        for (r, x) in col.iter_mut().enumerate() {
+            *x = (0..4).fold(0, |acc, j| acc ^ gmul(m[(j + 4 - r) % 4], a[j]));
+        }
+    }
+}
+
+pub(crate) fn mix_columns(state: &mut Block) {
+    mix_columns_with(state, [2, 3, 1, 1]);
+}
+
+fn inv_mix_columns(state: &mut Block) {
+    mix_columns_with(state, [14, 11, 13, 9]);
+}
@@ -0,0 +1,225 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use rand_core::{impls, Error, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// ChaCha20 keystream generator (RFC 8439), used as a DRBG.
+///
+/// Outputs are the keystream words (little-endian), block after block. The
+/// block counter is 32 bits and the nonce 96 bits, as in RFC 8439. The
+/// keystream bytes are thus given by `fill_bytes`.
+///
+/// # Examples
+///
+/// ```
+/// use rand_core::RngCore;
+/// use secbench_crypto::ChaCha20Rng;
+///
+/// // RFC 8439, section 2.3.2.
+/// let key: Vec<u8> = (0..32).collect();
+/// let nonce = [0, 0, 0, 9, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+/// let mut rng = ChaCha20Rng::new(key.try_into().unwrap(), nonce, 1);
+/// assert_eq!(rng.next_u32(), 0xe4e7f110);
+///
+/// // RFC 8439, appendix A.1, test vector #1.
+/// let mut rng = ChaCha20Rng::new([0; 32], [0; 12], 0);
+/// let mut keystream = [0u8; 8];
+/// rng.fill_bytes(&mut keystream);
+/// assert_eq!(keystream, [0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90]);
+///
+/// // The generator can be restored from its key, nonce and position.
+/// let mut restored = ChaCha20Rng::new(rng.key(), rng.nonce(), 0);
+/// restored.set_word_pos(rng.word_pos());
+/// assert_eq!(restored.next_u32(), rng.next_u32());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChaCha20Rng {
+    input: [u32; 16],
+    block: [u32; 16],
+    index: usize,
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+/// ChaCha20 block function.
+fn chacha20_block(input: &[u32; 16]) -> [u32; 16] {
+    let mut x = *input;
+    for _ in 0..10 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 1, 5, 9, 13);
+        quarter_round(&mut x, 2, 6, 10, 14);
+        quarter_round(&mut x, 3, 7, 11, 15);
+        quarter_round(&mut x, 0, 5, 10, 15);
+        quarter_round(&mut x, 1, 6, 11, 12);
+        quarter_round(&mut x, 2, 7, 8, 13);
+        quarter_round(&mut x, 3, 4, 9, 14);
+    }
+    for (x, i) in x.iter_mut().zip(input) {
+        *x = x.wrapping_add(*i);
+    }
+    x
+}
+
+impl ChaCha20Rng {
+    pub fn new(key: [u8; 32], nonce: [u8; 12], counter: u32) -> Self {
+        let mut input = [0u32; 16];
+        input[..4].copy_from_slice(&CONSTANTS);
+        for (w, b) in input[4..12].iter_mut().zip(key.chunks_exact(4)) {
+            *w = u32::from_le_bytes(b.try_into().unwrap());
+        }
+        input[12] = counter;
+        for (w, b) in input[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+            *w = u32::from_le_bytes(b.try_into().unwrap());
+        }
+        ChaCha20Rng {
+            input,
+            block: [0; 16],
+            index: 16,
+        }
+    }
+
+    pub fn key(&self) -> [u8; 32] {
+        let mut key = [0; 32];
+        for (b, w) in key.chunks_exact_mut(4).zip(&self.input[4..12]) {
+            b.copy_from_slice(&w.to_le_bytes());
+        }
+        key
+    }
+
+    pub fn nonce(&self) -> [u8; 12] {
+        let mut nonce = [0; 12];
+        for (b, w) in nonce.chunks_exact_mut(4).zip(&self.input[13..]) {
+            b.copy_from_slice(&w.to_le_bytes());
+        }
+        nonce
+    }
+
+    /// Counter of the next block to be generated.
+    pub fn counter(&self) -> u32 {
+        self.input[12]
+    }
+
+    /// Position in the keystream, in 32-bit words.
+    pub fn word_pos(&self) -> u64 {
+        // The counter is incremented when a block is generated, and the
+        // keystream has 2^32 blocks of 16 words.
+        (self.counter() as u64 * 16 + self.index as u64).wrapping_sub(16) & 0xf_ffff_ffff
+    }
+
+    /// Seek to a position in the keystream, in 32-bit words.
+    pub fn set_word_pos(&mut self, pos: u64) {
+        self.input[12] = (pos / 16) as u32;
+        self.index = 16;
+        let skip = (pos % 16) as usize;
+        if skip != 0 {
+            self.refill();
+            self.index = skip;
+        }
+    }
+
+    fn refill(&mut self) {
+        self.block = chacha20_block(&self.input);
+        self.input[12] = self.input[12].wrapping_add(1);
+        self.index = 0;
+    }
+}
+
+impl RngCore for ChaCha20Rng {
+    fn next_u32(&mut self) -> u32 {
+        if self.index == 16 {
+            self.refill();
+        }
+        let x = self.block[self.index];
+        self.index += 1;
+        x
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for ChaCha20Rng {
+    /// The key, with a zero nonce and counter.
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> Self {
+        ChaCha20Rng::new(seed, [0; 12], 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_word_pos() {
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let mut rng = ChaCha20Rng::new(key, [7; 12], 0);
+        for pos in [0, 5, 16, 1000, (1 << 36) - 16, (1 << 36) - 5, (1 << 36) - 1] {
+            rng.set_word_pos(pos);
+            assert_eq!(rng.word_pos(), pos);
+        }
+
+        // Last block of the keystream, then wrap around to the first one.
+        let pos = (1 << 36) - 5;
+        rng.set_word_pos(pos);
+        let mut last_block = ChaCha20Rng::new(key, [7; 12], u32::MAX);
+        for _ in 0..11 {
+            last_block.next_u32();
+        }
+        for i in 0..5 {
+            assert_eq!(rng.word_pos(), pos + i);
+            assert_eq!(rng.next_u32(), last_block.next_u32());
+        }
+        assert_eq!(rng.word_pos(), 0);
+        assert_eq!(rng.next_u32(), ChaCha20Rng::new(key, [7; 12], 0).next_u32());
+    }
+}
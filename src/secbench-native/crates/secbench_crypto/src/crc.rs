@@ -0,0 +1,281 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use core::fmt;
+
+/// Parameters of a CRC, following the Rocksoft model (see the
+/// [CRC catalogue](https://reveng.sourceforge.io/crc-catalogue/)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrcParams {
+    /// Width in bits (1 to 64).
+    pub width: u32,
+    /// Polynomial, in normal (non-reflected) form, without the top bit.
+    pub poly: u64,
+    pub init: u64,
+    /// Process input bytes LSB first.
+    pub refin: bool,
+    /// Reflect the final register.
+    pub refout: bool,
+    pub xorout: u64,
+}
+
+impl CrcParams {
+    pub const CRC8_KOOP: CrcParams = CrcParams {
+        width: 8,
+        poly: 0x4d,
+        init: 0xff,
+        refin: true,
+        refout: true,
+        xorout: 0xff,
+    };
+    pub const CRC16_ARC: CrcParams = CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0,
+        refin: true,
+        refout: true,
+        xorout: 0,
+    };
+    /// Also known as CRC-16/CCITT-FALSE.
+    pub const CRC16_IBM_3740: CrcParams = CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0xffff,
+        refin: false,
+        refout: false,
+        xorout: 0,
+    };
+    pub const CRC16_XMODEM: CrcParams = CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0,
+        refin: false,
+        refout: false,
+        xorout: 0,
+    };
+    /// The CRC32 of zlib, Ethernet, PNG...
+    pub const CRC32_ISO_HDLC: CrcParams = CrcParams {
+        width: 32,
+        poly: 0x04c11db7,
+        init: 0xffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffff,
+    };
+    /// Also known as CRC-32C.
+    pub const CRC32_ISCSI: CrcParams = CrcParams {
+        width: 32,
+        poly: 0x1edc6f41,
+        init: 0xffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffff,
+    };
+
+    /// Look up parameters of a common CRC by name (e.g., `"crc32"`).
+    pub fn from_name(name: &str) -> Option<CrcParams> {
+        match name.to_ascii_lowercase().as_str() {
+            "crc8" | "crc8_koop" => Some(Self::CRC8_KOOP),
+            "crc16" | "crc16_arc" => Some(Self::CRC16_ARC),
+            "crc16_ibm_3740" | "crc16_ccitt_false" => Some(Self::CRC16_IBM_3740),
+            "crc16_xmodem" => Some(Self::CRC16_XMODEM),
+            "crc32" | "crc32_iso_hdlc" => Some(Self::CRC32_ISO_HDLC),
+            "crc32c" | "crc32_iscsi" => Some(Self::CRC32_ISCSI),
+            _ => None,
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrcError {
+    InvalidWidth(u32),
+    /// A parameter (`poly`, `init` or `xorout`) does not fit in the CRC width.
+    ParameterTooWide {
+        name: &'static str,
+        value: u64,
+        width: u32,
+    },
+}
+
+impl fmt::Display for CrcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrcError::InvalidWidth(w) => write!(f, "invalid CRC width: {w} (expected 1 to 64)"),
+            CrcError::ParameterTooWide { name, value, width } => {
+                write!(
+                    f,
+                    "CRC parameter {name}={value:#x} does not fit in {width} bits"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CrcError {}
+
+fn reflect(x: u64, width: u32) -> u64 {
+    x.reverse_bits() >> (64 - width)
+}
+
+/// Table-driven CRC engine.
+///
+/// The register of a reflected CRC (`refin`) is stored reflected and shifts
+/// right. Otherwise, it is stored left-aligned on 64 bits and shifts left, which
+/// supports any width with the same byte-wise table lookup.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::crc::{Crc, CrcParams};
+///
+/// let check = b"123456789";
+/// let crc = Crc::new(CrcParams::CRC32_ISO_HDLC).unwrap();
+/// assert_eq!(crc.checksum(check), 0xcbf43926);
+/// assert_eq!(Crc::new(CrcParams::CRC16_IBM_3740).unwrap().checksum(check), 0x29b1);
+/// assert_eq!(Crc::new(CrcParams::CRC8_KOOP).unwrap().checksum(check), 0xd8);
+///
+/// // Register after each byte.
+/// let mut registers = [0; 9];
+/// crc.registers(check, &mut registers);
+/// assert_eq!(registers[8] ^ 0xffffffff, 0xcbf43926);
+///
+/// // Parameters must fit in the register.
+/// let init = 0x1ffff;
+/// assert!(Crc::new(CrcParams { init, ..CrcParams::CRC16_XMODEM }).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Crc {
+    params: CrcParams,
+    table: [u64; 256],
+}
+
+impl Crc {
+    pub fn new(params: CrcParams) -> Result<Self, CrcError> {
+        if !(1..=64).contains(&params.width) {
+            return Err(CrcError::InvalidWidth(params.width));
+        }
+        for (name, value) in [
+            ("poly", params.poly),
+            ("init", params.init),
+            ("xorout", params.xorout),
+        ] {
+            if value & !params.mask() != 0 {
+                return Err(CrcError::ParameterTooWide {
+                    name,
+                    value,
+                    width: params.width,
+                });
+            }
+        }
+        let mut table = [0u64; 256];
+        if params.refin {
+            let poly = reflect(params.poly, params.width);
+            for (b, t) in table.iter_mut().enumerate() {
+                let mut r = b as u64;
+                for _ in 0..8 {
+                    r = if r & 1 != 0 { (r >> 1) ^ poly } else { r >> 1 };
+                }
+                *t = r;
+            }
+        } else {
+            let poly = params.poly << (64 - params.width);
+            for (b, t) in table.iter_mut().enumerate() {
+                let mut r = (b as u64) << 56;
+                for _ in 0..8 {
+                    r = if r >> 63 != 0 {
+                        (r << 1) ^ poly
+                    } else {
+                        r << 1
+                    };
+                }
+                *t = r;
+            }
+        }
+        Ok(Crc { params, table })
+    }
+
+    pub fn params(&self) -> &CrcParams {
+        &self.params
+    }
+
+    fn start(&self) -> u64 {
+        let p = &self.params;
+        if p.refin {
+            reflect(p.init, p.width)
+        } else {
+            p.init << (64 - p.width)
+        }
+    }
+
+    fn step(&self, r: u64, b: u8) -> u64 {
+        if self.params.refin {
+            (r >> 8) ^ self.table[((r ^ b as u64) & 0xff) as usize]
+        } else {
+            (r << 8) ^ self.table[((r >> 56) ^ b as u64) as usize]
+        }
+    }
+
+    /// Register value, right-aligned (and reflected when `refin` is set).
+    fn register(&self, r: u64) -> u64 {
+        if self.params.refin {
+            r
+        } else {
+            r >> (64 - self.params.width)
+        }
+    }
+
+    fn finalize(&self, r: u64) -> u64 {
+        let p = &self.params;
+        let mut r = self.register(r);
+        if p.refin != p.refout {
+            r = reflect(r, p.width);
+        }
+        r ^ p.xorout
+    }
+
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        self.finalize(data.iter().fold(self.start(), |r, &b| self.step(r, b)))
+    }
+
+    /// Register after processing each byte of `data`.
+    ///
+    /// Values are stored as a table-driven implementation would: reflected
+    /// when `refin` is set, and before the final reflection and `xorout`.
+    pub fn registers(&self, data: &[u8], out: &mut [u64]) {
+        let mut r = self.start();
+        for (o, &b) in out.iter_mut().zip(data) {
+            r = self.step(r, b);
+            *o = self.register(r);
+        }
+    }
+}
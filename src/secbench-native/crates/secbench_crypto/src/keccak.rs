@@ -0,0 +1,343 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use core::fmt;
+use core::str::FromStr;
+
+/// Keccak-f[1600] state, lane `(x, y)` is at index `x + 5 * y`.
+///
+/// This is the lane order of FIPS-202: the state serialized as bytes is the
+/// little-endian encoding of the lanes.
+pub type KeccakState = [u64; 25];
+
+/// Number of rounds of Keccak-f[1600].
+pub const ROUNDS: usize = 24;
+
+const RC: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+const RHO_OFFSETS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Source lane of each destination lane in the π step.
+const PI_LANES: [usize; 25] = [
+    0, 6, 12, 18, 24, 3, 9, 10, 16, 22, 1, 7, 13, 19, 20, 4, 5, 11, 17, 23, 2, 8, 14, 15, 21,
+];
+
+/// Steps of a Keccak-f round, in execution order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeccakStep {
+    Theta = 0,
+    Rho,
+    Pi,
+    Chi,
+    Iota,
+}
+
+impl KeccakStep {
+    pub const COUNT: usize = 5;
+    pub const ALL: [KeccakStep; Self::COUNT] = [
+        KeccakStep::Theta,
+        KeccakStep::Rho,
+        KeccakStep::Pi,
+        KeccakStep::Chi,
+        KeccakStep::Iota,
+    ];
+}
+
+impl FromStr for KeccakStep {
+    type Err = KeccakError;
+
+    /// Parse a step from its name (e.g., `"theta"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "theta" => Ok(KeccakStep::Theta),
+            "rho" => Ok(KeccakStep::Rho),
+            "pi" => Ok(KeccakStep::Pi),
+            "chi" => Ok(KeccakStep::Chi),
+            "iota" => Ok(KeccakStep::Iota),
+            _ => Err(KeccakError::UnknownStep(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeccakError {
+    /// SHA-3 digest size (in bits) not in 224, 256, 384, 512.
+    InvalidDigestSize(usize),
+    /// SHAKE security level (in bits) not in 128, 256.
+    InvalidSecurityLevel(usize),
+    UnknownStep(String),
+}
+
+impl fmt::Display for KeccakError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeccakError::InvalidDigestSize(n) => {
+                write!(
+                    f,
+                    "invalid SHA-3 digest size: {n} bits (expected 224, 256, 384 or 512)"
+                )
+            }
+            KeccakError::InvalidSecurityLevel(n) => {
+                write!(
+                    f,
+                    "invalid SHAKE security level: {n} bits (expected 128 or 256)"
+                )
+            }
+            KeccakError::UnknownStep(s) => write!(f, "unknown Keccak step: {s:?}"),
+        }
+    }
+}
+
+impl std::error::Error for KeccakError {}
+
+pub fn theta(a: &mut KeccakState) {
+    let c: [u64; 5] = core::array::from_fn(|x| a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20]);
+    for x in 0..5 {
+        let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        for y in 0..5 {
+            a[x + 5 * y] ^= d;
+        }
+    }
+}
+
+pub fn rho(a: &mut KeccakState) {
+    a.iter_mut()
+        .zip(RHO_OFFSETS)
+        .for_each(|(lane, r)| *lane = lane.rotate_left(r));
+}
+
+pub fn pi(a: &mut KeccakState) {
+    let b = *a;
+    a.iter_mut()
+        .zip(PI_LANES)
+        .for_each(|(lane, src)| *lane = b[src]);
+}
+
+pub fn chi(a: &mut KeccakState) {
+    for row in a.chunks_mut(5) {
+        let b: [u64; 5] = row.try_into().unwrap();
+        for x in 0..5 {
+            row[x] = b[x] ^ (!b[(x + 1) % 5] & b[(x + 2) % 5]);
+        }
+    }
+}
+
+pub fn iota(a: &mut KeccakState, round: usize) {
+    a[0] ^= RC[round];
+}
+
+/// Apply one step of a given round.
+pub fn step(a: &mut KeccakState, round: usize, step: KeccakStep) {
+    match step {
+        KeccakStep::Theta => theta(a),
+        KeccakStep::Rho => rho(a),
+        KeccakStep::Pi => pi(a),
+        KeccakStep::Chi => chi(a),
+        KeccakStep::Iota => iota(a, round),
+    }
+}
+
+/// Keccak-f[1600] permutation.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::keccak::keccak_f1600;
+///
+/// let mut state = [0u64; 25];
+/// keccak_f1600(&mut state);
+/// assert_eq!(state[0], 0xf1258f7940e1dde7);
+/// ```
+pub fn keccak_f1600(a: &mut KeccakState) {
+    keccak_f1600_traced(a, |_, _, _| {});
+}
+
+/// Keccak-f[1600] permutation, calling `f(round, step, state)` after each step.
+pub fn keccak_f1600_traced<F>(a: &mut KeccakState, mut f: F)
+where
+    F: FnMut(usize, KeccakStep, &KeccakState),
+{
+    for round in 0..ROUNDS {
+        for s in KeccakStep::ALL {
+            step(a, round, s);
+            f(round, s, a);
+        }
+    }
+}
+
+/// Keccak sponge with the Keccak-f[1600] permutation.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::keccak::Sponge;
+///
+/// let mut h = Sponge::sha3(256).unwrap();
+/// h.absorb(b"abc");
+/// let mut digest = [0u8; 32];
+/// h.squeeze(&mut digest);
+/// assert_eq!(digest[..4], [0x3a, 0x98, 0x5d, 0xa7]);
+///
+/// let mut xof = Sponge::shake(128).unwrap();
+/// let mut out = [0u8; 4];
+/// xof.squeeze(&mut out);
+/// assert_eq!(out, [0x7f, 0x9c, 0x2b, 0xa4]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sponge {
+    state: KeccakState,
+    /// Rate in bytes.
+    rate: usize,
+    /// Domain separation bits, including the first bit of the padding.
+    suffix: u8,
+    pos: usize,
+    squeezing: bool,
+}
+
+impl Sponge {
+    /// Create a sponge from a rate (in bytes, a multiple of 8 below 200)
+    /// and a domain separation suffix.
+    pub fn new(rate: usize, suffix: u8) -> Self {
+        assert!(
+            rate > 0 && rate < 200 && rate.is_multiple_of(8),
+            "invalid rate"
+        );
+        Sponge {
+            state: [0; 25],
+            rate,
+            suffix,
+            pos: 0,
+            squeezing: false,
+        }
+    }
+
+    /// SHA3-224/256/384/512 sponge.
+    pub fn sha3(bits: usize) -> Result<Self, KeccakError> {
+        match bits {
+            224 | 256 | 384 | 512 => Ok(Self::new(200 - bits / 4, 0x06)),
+            _ => Err(KeccakError::InvalidDigestSize(bits)),
+        }
+    }
+
+    /// SHAKE128/256 sponge.
+    pub fn shake(security: usize) -> Result<Self, KeccakError> {
+        match security {
+            128 | 256 => Ok(Self::new(200 - security / 4, 0x1f)),
+            _ => Err(KeccakError::InvalidSecurityLevel(security)),
+        }
+    }
+
+    /// Current state of the permutation.
+    pub fn state(&self) -> &KeccakState {
+        &self.state
+    }
+
+    /// Absorb data. Panics if called after [`Sponge::squeeze`].
+    pub fn absorb(&mut self, data: &[u8]) {
+        assert!(!self.squeezing, "absorb called after squeeze");
+        for &b in data {
+            self.xor_byte(self.pos, b);
+            self.pos += 1;
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pad the absorbed data and squeeze output bytes.
+    ///
+    /// Can be called several times to produce a longer output.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        if !self.squeezing {
+            self.xor_byte(self.pos, self.suffix);
+            self.xor_byte(self.rate - 1, 0x80);
+            keccak_f1600(&mut self.state);
+            self.pos = 0;
+            self.squeezing = true;
+        }
+        for b in out {
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+            *b = (self.state[self.pos / 8] >> (8 * (self.pos % 8))) as u8;
+            self.pos += 1;
+        }
+    }
+
+    fn xor_byte(&mut self, i: usize, b: u8) {
+        self.state[i / 8] ^= (b as u64) << (8 * (i % 8));
+    }
+}
+
+/// SHA3 digest of `data`, `bits` is the digest size.
+pub fn sha3(data: &[u8], bits: usize) -> Result<Vec<u8>, KeccakError> {
+    let mut h = Sponge::sha3(bits)?;
+    h.absorb(data);
+    let mut digest = vec![0; bits / 8];
+    h.squeeze(&mut digest);
+    Ok(digest)
+}
+
+/// SHAKE output of `data`, with a given security level (128 or 256).
+pub fn shake(data: &[u8], security: usize, output_len: usize) -> Result<Vec<u8>, KeccakError> {
+    let mut h = Sponge::shake(security)?;
+    h.absorb(data);
+    let mut out = vec![0; output_len];
+    h.squeeze(&mut out);
+    Ok(out)
+}
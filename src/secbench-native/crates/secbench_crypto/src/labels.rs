@@ -0,0 +1,233 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::aes::{INV_SBOX, SBOX};
+
+/// Selection function, mapping a data byte `x` and a key guess `k` to a label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// First round SubBytes output: `SBOX[x ^ k]`.
+    SboxOut,
+    /// Hamming weight of `SBOX[x ^ k]`.
+    SboxOutHw,
+    /// Hamming distance between the SubBytes input and output:
+    /// `HW((x ^ k) ^ SBOX[x ^ k])`.
+    SboxOutHdIn,
+    /// Last round inverse SubBytes output, from a ciphertext byte: `INV_SBOX[x ^ k]`.
+    InvSboxOut,
+    /// Hamming distance between the last round SubBytes input and the
+    /// ciphertext byte overwriting it in the state register:
+    /// `HW(INV_SBOX[x ^ k] ^ r)`, where `r` is the ciphertext byte at
+    /// [`Selection::register_byte`]. This is one byte of
+    /// [`crate::aes::Intermediate::LastRoundHd`].
+    InvSboxOutHd,
+}
+
+impl Selection {
+    /// Label of the data byte `x` with the key guess `k`.
+    ///
+    /// `register` is the data byte at [`Selection::register_byte`], it is
+    /// ignored by selections that only depend on `x`.
+    pub fn apply(self, x: u8, k: u8, register: u8) -> u8 {
+        match self {
+            Selection::SboxOut => SBOX[(x ^ k) as usize],
+            Selection::SboxOutHw => SBOX[(x ^ k) as usize].count_ones() as u8,
+            Selection::SboxOutHdIn => ((x ^ k) ^ SBOX[(x ^ k) as usize]).count_ones() as u8,
+            Selection::InvSboxOut => INV_SBOX[(x ^ k) as usize],
+            Selection::InvSboxOutHd => (INV_SBOX[(x ^ k) as usize] ^ register).count_ones() as u8,
+        }
+    }
+
+    /// Position of the other data byte used by the label of byte `i`, if any.
+    ///
+    /// For [`Selection::InvSboxOutHd`], data are 16-byte ciphertexts and this
+    /// is the state byte that ShiftRows moves to byte `i` in the last round
+    /// (i.e., the register holding the SubBytes input of ciphertext byte `i`).
+    pub fn register_byte(self, i: usize) -> Option<usize> {
+        match self {
+            Selection::InvSboxOutHd => {
+                debug_assert!(i < 16);
+                let (c, r) = (i / 4, i % 4);
+                Some(4 * ((c + r) % 4) + r)
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of distinct labels (i.e., classes for a `CondMeanVar`).
+    pub fn classes(self) -> usize {
+        match self {
+            Selection::SboxOut | Selection::InvSboxOut => 256,
+            Selection::SboxOutHw | Selection::SboxOutHdIn | Selection::InvSboxOutHd => 9,
+        }
+    }
+
+    /// Lookup table of the selection function, indexed by `x * 256 + k`.
+    ///
+    /// For [`Selection::InvSboxOutHd`], the table contains `INV_SBOX[x ^ k]`,
+    /// the distance with the register is computed in [`LabelGenerator::fill`].
+    fn table(self) -> Vec<u16> {
+        let entry = move |x: u8, k: u8| match self {
+            Selection::InvSboxOutHd => INV_SBOX[(x ^ k) as usize],
+            _ => self.apply(x, k, 0),
+        };
+        (0..=255u8)
+            .flat_map(|x| (0..=255u8).map(move |k| entry(x, k) as u16))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSelection(pub String);
+
+impl fmt::Display for UnknownSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown selection function: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownSelection {}
+
+impl FromStr for Selection {
+    type Err = UnknownSelection;
+
+    /// Parse a selection function from its snake case name (e.g., `"sbox_out_hw"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sbox_out" => Ok(Selection::SboxOut),
+            "sbox_out_hw" => Ok(Selection::SboxOutHw),
+            "sbox_out_hd_in" => Ok(Selection::SboxOutHdIn),
+            "inv_sbox_out" => Ok(Selection::InvSboxOut),
+            "inv_sbox_out_hd" => Ok(Selection::InvSboxOutHd),
+            _ => Err(UnknownSelection(s.to_string())),
+        }
+    }
+}
+
+/// Labels of one trace for a set of byte positions and key guesses.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::labels::{LabelGenerator, Selection};
+///
+/// let gen = LabelGenerator::new(Selection::SboxOut, vec![0, 1], (0..=255).collect());
+/// let mut labels = vec![0; gen.n_targets()];
+/// gen.fill(&[0x00, 0x01], &mut labels);
+/// // Byte 0, guess 0 and byte 1, guess 1 both give SBOX[0].
+/// assert_eq!(labels[0], 0x63);
+/// assert_eq!(labels[256 + 1], 0x63);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LabelGenerator {
+    selection: Selection,
+    bytes: Vec<usize>,
+    guesses: Vec<u8>,
+    table: Vec<u16>,
+}
+
+impl LabelGenerator {
+    /// Labels are ordered by byte position, then by key guess: the label of
+    /// `bytes[i]` with `guesses[j]` is at index `i * guesses.len() + j`.
+    pub fn new(selection: Selection, bytes: Vec<usize>, guesses: Vec<u8>) -> Self {
+        LabelGenerator {
+            selection,
+            bytes,
+            guesses,
+            table: selection.table(),
+        }
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn n_targets(&self) -> usize {
+        self.bytes.len() * self.guesses.len()
+    }
+
+    /// Compute the labels of one trace, whose data (plaintext or ciphertext) is `data`.
+    pub fn fill(&self, data: &[u8], out: &mut [u16]) {
+        let targets = out.chunks_mut(self.guesses.len().max(1));
+        for (&b, out) in self.bytes.iter().zip(targets) {
+            let row = &self.table[data[b] as usize * 256..][..256];
+            match self.selection.register_byte(b) {
+                Some(r) => {
+                    let register = data[r] as u16;
+                    for (o, &k) in out.iter_mut().zip(&self.guesses) {
+                        *o = (row[k as usize] ^ register).count_ones() as u16;
+                    }
+                }
+                None => {
+                    for (o, &k) in out.iter_mut().zip(&self.guesses) {
+                        *o = row[k as usize];
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aes::{Aes, Intermediate};
+
+    #[test]
+    fn test_inv_sbox_out_hd_matches_last_round_hd() {
+        let aes = Aes::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+        let k10 = aes.round_keys()[aes.rounds()];
+        let bytes: Vec<usize> = (0..16).collect();
+        for seed in 0..8u8 {
+            let pt: [u8; 16] = core::array::from_fn(|i| (i as u8).wrapping_mul(37) ^ seed);
+            let ct = aes.encrypt(&pt);
+            let hd = aes.intermediate(&pt, Intermediate::LastRoundHd).unwrap();
+            for &i in &bytes {
+                let gen = LabelGenerator::new(Selection::InvSboxOutHd, vec![i], vec![k10[i]]);
+                let mut label = [0];
+                gen.fill(&ct, &mut label);
+                // The label of ciphertext byte `i` is the distance of the register
+                // ShiftRows moves to byte `i`.
+                let r = Selection::InvSboxOutHd.register_byte(i).unwrap();
+                assert_eq!(label[0], hd[r].count_ones() as u16, "byte {i}");
+                assert_eq!(
+                    Selection::InvSboxOutHd.apply(ct[i], k10[i], ct[r]) as u16,
+                    label[0]
+                );
+            }
+        }
+        // Bytes of the first row are not moved by ShiftRows.
+        assert_eq!(Selection::InvSboxOutHd.register_byte(4), Some(4));
+        assert_eq!(Selection::InvSboxOutHd.register_byte(1), Some(5));
+        assert_eq!(Selection::SboxOut.register_byte(1), None);
+    }
+}
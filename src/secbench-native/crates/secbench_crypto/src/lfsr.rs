@@ -0,0 +1,118 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use rand_core::{impls, Error, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Galois (right-shifting) Linear Feedback Shift Register (LFSR).
+///
+/// At each step, the output bit is the least significant bit of the
+/// register, the register is shifted right and `taps` is XORed into it when
+/// the output bit is set:
+///
+/// ```c
+/// bit = lfsr & 1; lfsr >>= 1; lfsr ^= (-bit) & taps;
+/// ```
+///
+/// `taps` includes the top bit of the register, so the register width is
+/// implied (e.g., `0xB400` for a 16-bit register). `next_u32` packs 32 output
+/// bits, the first output in the least significant bit.
+///
+/// # Examples
+///
+/// ```
+/// use rand_core::RngCore;
+/// use secbench_crypto::GaloisLfsr;
+///
+/// let mut lfsr = GaloisLfsr::new(0xB400, 0xACE1);
+/// assert_eq!(lfsr.next_u32(), 0x3b62c4e1);
+/// assert_eq!(lfsr.state(), 0x330d);
+///
+/// let mut lfsr = GaloisLfsr::new(0xB400, 0xACE1);
+/// assert_eq!(lfsr.next_bit(), 1);
+/// assert_eq!(lfsr.state(), 0xACE1 >> 1 ^ 0xB400);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GaloisLfsr {
+    taps: u64,
+    state: u64,
+}
+
+impl GaloisLfsr {
+    pub fn new(taps: u64, state: u64) -> Self {
+        GaloisLfsr { taps, state }
+    }
+
+    pub fn taps(&self) -> u64 {
+        self.taps
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Clock the register once and return the output bit.
+    pub fn next_bit(&mut self) -> u8 {
+        let bit = self.state & 1;
+        self.state = (self.state >> 1) ^ (bit.wrapping_neg() & self.taps);
+        bit as u8
+    }
+}
+
+impl RngCore for GaloisLfsr {
+    fn next_u32(&mut self) -> u32 {
+        (0..32).fold(0, |acc, i| acc | (self.next_bit() as u32) << i)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for GaloisLfsr {
+    /// Little-endian encodings of `taps` (bytes 0..8) and the initial state
+    /// (bytes 8..16).
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: [u8; 16]) -> Self {
+        GaloisLfsr::new(
+            u64::from_le_bytes(seed[0..8].try_into().unwrap()),
+            u64::from_le_bytes(seed[8..16].try_into().unwrap()),
+        )
+    }
+}
@@ -30,13 +30,30 @@
 //! Reproducible and stable models of cryptographic primitives.
 //!
 //! This module includes:
-//! - Pseudo Random Number Generators (PRNG)
-//! - Ciphers
+//! - Pseudo Random Number Generators (PRNG): PCG32, xorshift32, xoshiro128**,
+//!   Galois LFSR and ChaCha20
+//! - Ciphers, and a leakage simulator of a masked AES
+//! - Cyclic Redundancy Checks (CRC)
+//! - Hash functions (SHA-3 and SHAKE)
+//! - Labels for side-channel attacks
 //!
 //! **Caution note**: These implementations are not indented for production use,
 //! only for simulation of RNG outputs. Please, use real-world proof
 //! cryptographic libraries (e.g., OpenSSL) for production systems.
 
+pub mod aes;
+mod chacha;
+pub mod crc;
+pub mod keccak;
+pub mod labels;
+mod lfsr;
+pub mod masked_aes;
 mod pcg32;
+mod values;
+mod xorshift;
 
-pub use pcg32::{Pcg32, Pcg32Seed};
\ No newline at end of file
+pub use chacha::ChaCha20Rng;
+pub use lfsr::GaloisLfsr;
+pub use pcg32::{Pcg32, Pcg32Seed};
+pub use values::{fill_values, fill_values_packed, RngValue};
+pub use xorshift::{Xorshift32, Xoshiro128StarStar};
@@ -0,0 +1,329 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Simulation of side-channel leakage of a Boolean-masked AES.
+//!
+//! The simulated implementation splits the state in `order + 1` shares whose
+//! XOR is the unmasked state. Linear operations are applied share-wise, and
+//! the round key is added to the first share only. The S-box layer is ideal:
+//! its output is a fresh random sharing of the unmasked S-box output, so that
+//! no intermediate value of the S-box computation leaks.
+//!
+//! Each operation leaks one sample per share and per byte, in the order
+//! (operation, share, byte). The operations are the initial `AddRoundKey`,
+//! followed by `SubBytes`, `ShiftRows`, `MixColumns` (except in the last AES
+//! round) and `AddRoundKey` for each simulated round.
+
+use core::fmt;
+use core::str::FromStr;
+
+use rand_core::RngCore;
+
+use crate::aes::{add_round_key, mix_columns, shift_rows, Aes, AesError, Block, SBOX};
+
+/// Leakage of a byte written by the simulated implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeakageModel {
+    /// The value itself.
+    Identity,
+    /// Hamming weight of the value.
+    HammingWeight,
+    /// Hamming distance between the value and the previous value of the same
+    /// share byte.
+    HammingDistance,
+}
+
+impl LeakageModel {
+    fn leak(self, prev: u8, value: u8) -> f64 {
+        match self {
+            LeakageModel::Identity => value as f64,
+            LeakageModel::HammingWeight => value.count_ones() as f64,
+            LeakageModel::HammingDistance => (prev ^ value).count_ones() as f64,
+        }
+    }
+}
+
+impl FromStr for LeakageModel {
+    type Err = SimulatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "identity" => Ok(LeakageModel::Identity),
+            "hw" => Ok(LeakageModel::HammingWeight),
+            "hd" => Ok(LeakageModel::HammingDistance),
+            _ => Err(SimulatorError::UnknownLeakageModel(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulatorError {
+    Aes(AesError),
+    InvalidRounds { rounds: usize, max: usize },
+    InvalidNoise,
+    UnknownLeakageModel(String),
+}
+
+impl fmt::Display for SimulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulatorError::Aes(e) => e.fmt(f),
+            SimulatorError::InvalidRounds { rounds, max } => {
+                write!(f, "cannot simulate {rounds} rounds (expected 1 to {max})")
+            }
+            SimulatorError::InvalidNoise => {
+                write!(
+                    f,
+                    "noise must be a finite and non-negative standard deviation"
+                )
+            }
+            SimulatorError::UnknownLeakageModel(s) => {
+                write!(
+                    f,
+                    "unknown leakage model: {s:?} (expected \"identity\", \"hw\" or \"hd\")"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulatorError {}
+
+impl From<AesError> for SimulatorError {
+    fn from(e: AesError) -> Self {
+        SimulatorError::Aes(e)
+    }
+}
+
+/// Parameters of the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatorConfig {
+    /// Masking order (0 for an unprotected implementation).
+    pub order: usize,
+    pub model: LeakageModel,
+    /// Standard deviation of the Gaussian noise added to each sample.
+    pub noise: f64,
+    /// Number of AES rounds simulated.
+    pub rounds: usize,
+    /// Maximum number of dummy samples inserted before each operation.
+    ///
+    /// The number of dummy samples is uniform in `0..=max_delay`, and traces
+    /// are padded with dummy samples to a fixed length.
+    pub max_delay: usize,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        SimulatorConfig {
+            order: 1,
+            model: LeakageModel::HammingWeight,
+            noise: 1.0,
+            rounds: 1,
+            max_delay: 0,
+        }
+    }
+}
+
+/// Leakage simulator of a masked AES encryption.
+///
+/// All the randomness (masks, delays, noise) is drawn from the generator
+/// given to [`MaskedAesSimulator::simulate`] (e.g., a [`crate::Pcg32`]), so
+/// traces are reproducible.
+///
+/// # Examples
+///
+/// ```
+/// use secbench_crypto::aes::SBOX;
+/// use secbench_crypto::masked_aes::{LeakageModel, MaskedAesSimulator, SimulatorConfig};
+/// use secbench_crypto::{Pcg32, Pcg32Seed};
+///
+/// let config = SimulatorConfig {
+///     order: 1,
+///     model: LeakageModel::Identity,
+///     noise: 0.0,
+///     ..Default::default()
+/// };
+/// let key = [0x2b; 16];
+/// let sim = MaskedAesSimulator::new(&key, config).unwrap();
+/// let mut trace = vec![0.0; sim.n_samples()];
+/// let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(0, 0));
+/// sim.simulate(&[0x10; 16], &mut rng, &mut trace);
+///
+/// // The shares of the first SubBytes output (operation 1) recombine to the
+/// // unmasked value.
+/// let (s0, s1) = (trace[32] as u8, trace[48] as u8);
+/// assert_eq!(s0 ^ s1, SBOX[0x10 ^ 0x2b]);
+///
+/// // The noise is a standard deviation.
+/// let config = SimulatorConfig { noise: f64::NAN, ..Default::default() };
+/// assert!(MaskedAesSimulator::new(&key, config).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaskedAesSimulator {
+    aes: Aes,
+    config: SimulatorConfig,
+}
+
+impl MaskedAesSimulator {
+    pub fn new(key: &[u8], config: SimulatorConfig) -> Result<Self, SimulatorError> {
+        let aes = Aes::new(key)?;
+        if config.rounds == 0 || config.rounds > aes.rounds() {
+            return Err(SimulatorError::InvalidRounds {
+                rounds: config.rounds,
+                max: aes.rounds(),
+            });
+        }
+        if !(config.noise.is_finite() && config.noise >= 0.0) {
+            return Err(SimulatorError::InvalidNoise);
+        }
+        Ok(MaskedAesSimulator { aes, config })
+    }
+
+    pub fn config(&self) -> &SimulatorConfig {
+        &self.config
+    }
+
+    /// Number of leaking operations.
+    pub fn n_ops(&self) -> usize {
+        let rounds = self.config.rounds;
+        let mix_columns = rounds.min(self.aes.rounds() - 1);
+        1 + 3 * rounds + mix_columns
+    }
+
+    /// Number of samples of a trace.
+    pub fn n_samples(&self) -> usize {
+        self.n_ops() * (16 * (self.config.order + 1) + self.config.max_delay)
+    }
+
+    /// Simulate the encryption of `plaintext`, writing the leakage in `trace`
+    /// (of length [`MaskedAesSimulator::n_samples`]).
+    pub fn simulate<R: RngCore>(&self, plaintext: &Block, rng: &mut R, trace: &mut [f32]) {
+        assert_eq!(trace.len(), self.n_samples(), "invalid trace length");
+        let n_shares = self.config.order + 1;
+        let round_keys = self.aes.round_keys();
+        let mut leaker = Leaker {
+            config: &self.config,
+            trace,
+            pos: 0,
+            rng,
+        };
+
+        let mut shares = vec![[0u8; 16]; n_shares];
+        shares[0] = *plaintext;
+        for i in 1..n_shares {
+            let mut mask = [0u8; 16];
+            leaker.rng.fill_bytes(&mut mask);
+            add_round_key(&mut shares[0], &mask);
+            shares[i] = mask;
+        }
+
+        leaker.apply(&mut shares, |s, i| {
+            if i == 0 {
+                add_round_key(s, &round_keys[0])
+            }
+        });
+        for (r, round_key) in round_keys
+            .iter()
+            .enumerate()
+            .take(self.config.rounds + 1)
+            .skip(1)
+        {
+            let mut unmasked = [0u8; 16];
+            shares.iter().for_each(|s| add_round_key(&mut unmasked, s));
+            let mut fresh = vec![[0u8; 16]; n_shares];
+            fresh[0] = unmasked.map(|x| SBOX[x as usize]);
+            for i in 1..n_shares {
+                leaker.rng.fill_bytes(&mut fresh[i]);
+                let mask = fresh[i];
+                add_round_key(&mut fresh[0], &mask);
+            }
+            leaker.apply(&mut shares, |s, i| *s = fresh[i]);
+            leaker.apply(&mut shares, |s, _| shift_rows(s));
+            if r != self.aes.rounds() {
+                leaker.apply(&mut shares, |s, _| mix_columns(s));
+            }
+            leaker.apply(&mut shares, |s, i| {
+                if i == 0 {
+                    add_round_key(s, round_key)
+                }
+            });
+        }
+        while leaker.pos < leaker.trace.len() {
+            leaker.dummy();
+        }
+    }
+}
+
+/// Writes the samples of a trace.
+struct Leaker<'a, R> {
+    config: &'a SimulatorConfig,
+    trace: &'a mut [f32],
+    pos: usize,
+    rng: &'a mut R,
+}
+
+impl<R: RngCore> Leaker<'_, R> {
+    fn noise(&mut self) -> f64 {
+        if self.config.noise == 0.0 {
+            return 0.0;
+        }
+        // Box-Muller transform, with u1 in (0, 1].
+        let u1 = (self.rng.next_u32() as f64 + 1.0) / 4294967296.0;
+        let u2 = self.rng.next_u32() as f64 / 4294967296.0;
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * core::f64::consts::PI * u2).cos();
+        self.config.noise * z
+    }
+
+    fn push(&mut self, prev: u8, value: u8) {
+        let x = self.config.model.leak(prev, value) + self.noise();
+        self.trace[self.pos] = x as f32;
+        self.pos += 1;
+    }
+
+    /// Leakage of a dummy operation on random data.
+    fn dummy(&mut self) {
+        let [prev, value, ..] = self.rng.next_u32().to_le_bytes();
+        self.push(prev, value);
+    }
+
+    /// Apply `f(share, share_index)` to each share and leak the result.
+    fn apply(&mut self, shares: &mut [Block], f: impl Fn(&mut Block, usize)) {
+        if self.config.max_delay > 0 {
+            let delay = self.rng.next_u32() as usize % (self.config.max_delay + 1);
+            (0..delay).for_each(|_| self.dummy());
+        }
+        for (i, share) in shares.iter_mut().enumerate() {
+            let prev = *share;
+            f(share, i);
+            for (p, v) in prev.iter().zip(share.iter()) {
+                self.push(*p, *v);
+            }
+        }
+    }
+}
@@ -27,14 +27,17 @@
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
 
-use core::convert::TryInto;
 use core::num::Wrapping;
 
 use rand_core::{impls, Error, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
 
+use crate::values::{fill_values, fill_values_packed, RngValue};
+
 type W64 = Wrapping<u64>;
 
+const DEFAULT_MULT: W64 = Wrapping(0x5851_f42d_4c95_7f2d);
+
 /// Seed for the `Pcg32` PRNG.
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Pcg32Seed([u8; 16]);
@@ -43,6 +46,9 @@ impl Pcg32Seed {
     /// Create a seed from an initial state (`state`) and a sequence
     /// index (`inc`).
     ///
+    /// The seed bytes are the little-endian encodings of `state` (bytes 0..8)
+    /// and `inc` (bytes 8..16).
+    ///
     /// This corresponds to the seeding approach used
     /// in [PCG github](https://github.com/imneme/pcg-c-basic/blob/master/pcg32-demo.c) repository.
     ///
@@ -84,6 +90,10 @@ impl AsMut<[u8]> for Pcg32Seed {
 /// let mut rng : Pcg32 = Pcg32::new(Pcg32Seed::from_state_inc(0x42, 1));
 /// let w1 = rng.generate();
 /// let w2 = rng.generate();
+///
+/// // Same outputs as `pcg32_srandom_r(&rng, 42, 54)` in the C version.
+/// let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(42, 54));
+/// assert_eq!(rng.generate(), 0xa15c02b7);
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Pcg32 {
@@ -99,27 +109,73 @@ impl Pcg32 {
         rng
     }
 
+    /// Restore a PRNG from a raw LCG state, as returned by [`Pcg32::state`].
+    ///
+    /// Returns `None` if `inc` is even (it is always odd in a valid state).
+    pub fn from_state(state: u64, inc: u64) -> Option<Self> {
+        (inc & 1 == 1).then_some(Pcg32 { state, inc })
+    }
+
+    /// Raw LCG state `(state, inc)`.
+    ///
+    /// Unlike the arguments of [`Pcg32Seed::from_state_inc`], this is the
+    /// internal state of the generator, which allows to resume a stream.
+    pub fn state(&self) -> (u64, u64) {
+        (self.state, self.inc)
+    }
+
     /// Reset the PRNG instance using a given seed.
+    ///
+    /// This is equivalent to `pcg32_srandom_r(rng, state, inc)` in the
+    /// reference C implementation.
     pub fn reset(&mut self, seed: Pcg32Seed) {
-        let inc = u64::from_le_bytes((&seed.0[0..8]).try_into().unwrap());
-        let state = u64::from_le_bytes((&seed.0[8..16]).try_into().unwrap());
+        let state = u64::from_le_bytes(seed.0[0..8].try_into().unwrap());
+        let inc = u64::from_le_bytes(seed.0[8..16].try_into().unwrap());
         self.state = 0;
-        self.inc = (Wrapping(inc) << 1).0 | 1;
+        self.inc = (inc << 1) | 1;
         self.generate();
         self.state = self.state.wrapping_add(state);
         self.generate();
     }
 
     /// Generate a random output.
+    ///
+    /// The output is a 32-bit value (`pcg32_random_r`), returned as a `u64`.
     pub fn generate(&mut self) -> u64 {
-        const DEFAULT_MULT: W64 = Wrapping(0x5851_f42d_4c95_7f2d);
         let old_state = Wrapping(self.state);
         self.state = (old_state * DEFAULT_MULT + Wrapping(self.inc)).0;
-        let xor_shifted = ((old_state >> 18) ^ old_state) >> 27;
-        let rot = old_state >> 59;
-        let shift = (!rot + Wrapping(1)) & Wrapping(31);
-        let result = (xor_shifted >> (rot.0 as usize)) | (xor_shifted << (shift.0 as usize));
-        result.0
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27).0 as u32;
+        let rot = (old_state >> 59).0 as u32;
+        xor_shifted.rotate_right(rot) as u64
+    }
+
+    /// Skip `delta` outputs in O(log(delta)) (`pcg32_advance_r`).
+    ///
+    /// Since the LCG has period 2^64, going backward by `d` steps is
+    /// `advance(d.wrapping_neg())`.
+    pub fn advance(&mut self, mut delta: u64) {
+        let (mut cur_mult, mut cur_plus) = (DEFAULT_MULT, Wrapping(self.inc));
+        let (mut acc_mult, mut acc_plus) = (Wrapping(1u64), Wrapping(0u64));
+        while delta > 0 {
+            if delta & 1 != 0 {
+                acc_mult *= cur_mult;
+                acc_plus = acc_plus * cur_mult + cur_plus;
+            }
+            cur_plus *= cur_mult + Wrapping(1);
+            cur_mult *= cur_mult;
+            delta >>= 1;
+        }
+        self.state = (acc_mult * Wrapping(self.state) + acc_plus).0;
+    }
+
+    /// Fill `dst` with one output per value (see [`fill_values`]).
+    pub fn fill<T: RngValue>(&mut self, dst: &mut [T]) {
+        fill_values(self, dst)
+    }
+
+    /// Fill `dst` with several values per output (see [`fill_values_packed`]).
+    pub fn fill_packed<T: RngValue>(&mut self, dst: &mut [T]) {
+        fill_values_packed(self, dst)
     }
 }
 
@@ -135,7 +191,7 @@ impl RngCore for Pcg32 {
     }
 
     fn next_u64(&mut self) -> u64 {
-        self.generate()
+        impls::next_u64_via_u32(self)
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
@@ -154,4 +210,85 @@ impl SeedableRng for Pcg32 {
     fn from_seed(seed: Pcg32Seed) -> Self {
         Pcg32::new(seed)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Outputs of `pcg32_random_r` after `pcg32_srandom_r(&rng, state, inc)`,
+    /// from the reference C implementation (pcg-c-basic).
+    const KAT: [(u64, u64, [u32; 6]); 3] = [
+        (
+            42,
+            54,
+            [
+                0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e,
+            ],
+        ),
+        (
+            0,
+            0,
+            [
+                0xe4c14788, 0x379c6516, 0x5c4ab3bb, 0x601d23e0, 0x1c382b8c, 0xd1faab16,
+            ],
+        ),
+        (
+            0xdeadbeefcafebabe,
+            0x0123456789abcdef,
+            [
+                0x1a70ef54, 0x59a840fe, 0xb799c69e, 0xc645d5c4, 0x19d3d499, 0xead6ee16,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_reference_outputs() {
+        for (state, inc, expected) in KAT {
+            let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(state, inc));
+            for x in expected {
+                assert_eq!(rng.generate(), x as u64);
+            }
+            let mut rng = Pcg32::from_seed(Pcg32Seed::from_state_inc(state, inc));
+            assert_eq!(rng.next_u32(), expected[0]);
+            assert_eq!(
+                rng.next_u64(),
+                (expected[2] as u64) << 32 | expected[1] as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_advance() {
+        let (state, inc, expected) = KAT[0];
+        let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(state, inc));
+        rng.advance(4);
+        assert_eq!(rng.generate(), expected[4] as u64);
+        rng.advance(5u64.wrapping_neg());
+        assert_eq!(rng.generate(), expected[0] as u64);
+    }
+
+    #[test]
+    fn test_state() {
+        let (state, inc, expected) = KAT[1];
+        let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(state, inc));
+        rng.generate();
+        let (state, inc) = rng.state();
+        let mut restored = Pcg32::from_state(state, inc).unwrap();
+        assert_eq!(restored.generate(), expected[1] as u64);
+        assert!(Pcg32::from_state(state, inc ^ 1).is_none());
+    }
+
+    #[test]
+    fn test_fill_packed() {
+        let (state, inc, expected) = KAT[0];
+        let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(state, inc));
+        let mut bytes = [0u8; 6];
+        rng.fill_packed(&mut bytes);
+        assert_eq!(bytes[..4], expected[0].to_le_bytes());
+        assert_eq!(bytes[4..], expected[1].to_le_bytes()[..2]);
+        assert_eq!(rng.generate(), expected[2] as u64);
+        let mut words = [0u16; 1];
+        rng.fill(&mut words);
+        assert_eq!(words[0], expected[3] as u16);
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use rand_core::RngCore;
+
+/// A value that can be drawn from the 32-bit outputs of a PRNG.
+pub trait RngValue: Copy {
+    /// Number of values packed in one 32-bit output.
+    const PACKED: usize;
+
+    /// Convert one output into a value (truncated for integers, uniform in
+    /// `[0, 1)` for floats).
+    fn from_output(x: u32) -> Self;
+
+    /// Value `i` (`i < PACKED`) of an output, in little-endian order.
+    fn unpack(x: u32, i: usize) -> Self;
+}
+
+macro_rules! impl_rng_int {
+    ($($t:ty => $packed:expr),*) => { $(
+        impl RngValue for $t {
+            const PACKED: usize = $packed;
+
+            fn from_output(x: u32) -> Self {
+                x as $t
+            }
+
+            fn unpack(x: u32, i: usize) -> Self {
+                (x >> (i * 32 / $packed)) as $t
+            }
+        }
+    )* };
+}
+
+impl_rng_int!(u8 => 4, u16 => 2, u32 => 1, u64 => 1);
+
+impl RngValue for f32 {
+    const PACKED: usize = 1;
+
+    fn from_output(x: u32) -> Self {
+        (x >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    fn unpack(x: u32, _i: usize) -> Self {
+        Self::from_output(x)
+    }
+}
+
+impl RngValue for f64 {
+    const PACKED: usize = 1;
+
+    fn from_output(x: u32) -> Self {
+        x as f64 * (1.0 / (1u64 << 32) as f64)
+    }
+
+    fn unpack(x: u32, _i: usize) -> Self {
+        Self::from_output(x)
+    }
+}
+
+/// Fill `dst` with one output of `rng` (`next_u32`) per value.
+pub fn fill_values<R: RngCore + ?Sized, T: RngValue>(rng: &mut R, dst: &mut [T]) {
+    dst.iter_mut()
+        .for_each(|x| *x = T::from_output(rng.next_u32()));
+}
+
+/// Fill `dst` with [`RngValue::PACKED`] values per output of `rng`.
+///
+/// For example, each output gives 4 bytes (in little-endian order). A
+/// partially used last output is still consumed.
+pub fn fill_values_packed<R: RngCore + ?Sized, T: RngValue>(rng: &mut R, dst: &mut [T]) {
+    for chunk in dst.chunks_mut(T::PACKED) {
+        let x = rng.next_u32();
+        for (i, v) in chunk.iter_mut().enumerate() {
+            *v = T::unpack(x, i);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use rand_core::{impls, Error, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Marsaglia's xorshift32 PRNG (shifts 13, 17, 5).
+///
+/// Each call to `next_u32` returns the new state, as in the usual C version:
+///
+/// ```c
+/// x ^= x << 13; x ^= x >> 17; x ^= x << 5; return state = x;
+/// ```
+///
+/// The state must be non-zero, otherwise the generator outputs only zeros.
+///
+/// # Examples
+///
+/// ```
+/// use rand_core::RngCore;
+/// use secbench_crypto::Xorshift32;
+///
+/// let mut rng = Xorshift32::new(1);
+/// assert_eq!(rng.next_u32(), 0x00042021);
+/// assert_eq!(rng.next_u32(), 0x04080601);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Xorshift32 {
+    state: u32,
+}
+
+impl Xorshift32 {
+    pub fn new(state: u32) -> Self {
+        Xorshift32 { state }
+    }
+
+    /// Current state (i.e., the last output).
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+}
+
+impl RngCore for Xorshift32 {
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Xorshift32 {
+    /// Little-endian encoding of the initial state.
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: [u8; 4]) -> Self {
+        Xorshift32::new(u32::from_le_bytes(seed))
+    }
+}
+
+/// xoshiro128** PRNG from Blackman and Vigna.
+///
+/// This follows the [reference C implementation](https://prng.di.unimi.it/xoshiro128starstar.c).
+/// The state must not be all zeros.
+///
+/// # Examples
+///
+/// ```
+/// use rand_core::RngCore;
+/// use secbench_crypto::Xoshiro128StarStar;
+///
+/// let mut rng = Xoshiro128StarStar::new([1, 2, 3, 4]);
+/// let outputs: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+/// assert_eq!(outputs, [11520, 0, 5927040, 70819200, 2031721883, 1637235492]);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Xoshiro128StarStar {
+    s: [u32; 4],
+}
+
+impl Xoshiro128StarStar {
+    pub fn new(state: [u32; 4]) -> Self {
+        Xoshiro128StarStar { s: state }
+    }
+
+    pub fn state(&self) -> [u32; 4] {
+        self.s
+    }
+}
+
+impl RngCore for Xoshiro128StarStar {
+    fn next_u32(&mut self) -> u32 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 9;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(11);
+        result
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Xoshiro128StarStar {
+    /// Little-endian encoding of the four state words.
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: [u8; 16]) -> Self {
+        let mut s = [0u32; 4];
+        for (w, b) in s.iter_mut().zip(seed.chunks_exact(4)) {
+            *w = u32::from_le_bytes(b.try_into().unwrap());
+        }
+        Xoshiro128StarStar::new(s)
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Compare the SIMD kernels with their scalar reference.
+//!
+//! Run with `cargo bench -p secbench_dsp --bench vectorized`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ndarray::Array2;
+use rand::{Rng, SeedableRng};
+use realfft::num_complex::Complex;
+use secbench_dsp::sliding::{MatchCorrelation, SlidingExecutor, SlidingType};
+use secbench_dsp::vectorized::{level, scalar, SimdFloat};
+use secbench_dsp::Transform2D;
+
+const SIZES: [usize; 2] = [1024, 65536];
+
+fn random_vec(rng: &mut impl Rng, n: usize) -> Vec<f32> {
+    (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect()
+}
+
+fn bench_kernels(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let simd = format!("{:?}", level());
+    let kernels = f32::kernels();
+    for n in SIZES {
+        let a = random_vec(&mut rng, n);
+        let b = random_vec(&mut rng, n);
+        let s = random_vec(&mut rng, n);
+        let mut dst = vec![0.0f32; n];
+
+        let mut group = c.benchmark_group(format!("sum_sq_dev/{n}"));
+        group.bench_function("scalar", |bench| {
+            bench.iter(|| scalar::sum_sq_dev(black_box(&a), 0.5))
+        });
+        group.bench_function(&simd, |bench| {
+            bench.iter(|| (kernels.sum_sq_dev)(black_box(&a), 0.5))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group(format!("euclidean_combine/{n}"));
+        group.bench_function("scalar", |bench| {
+            bench.iter(|| scalar::euclidean_combine(&mut dst, black_box(&a), &b, 2.0))
+        });
+        group.bench_function(&simd, |bench| {
+            bench.iter(|| (kernels.euclidean_combine)(&mut dst, black_box(&a), &b, 2.0))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group(format!("correlation_combine/{n}"));
+        group.bench_function("scalar", |bench| {
+            bench.iter(|| scalar::correlation_combine(&mut dst, black_box(&a), &b, &s, 0.1, 2.0))
+        });
+        group.bench_function(&simd, |bench| {
+            bench.iter(|| (kernels.correlation_combine)(&mut dst, black_box(&a), &b, &s, 0.1, 2.0))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group(format!("sub_scaled/{n}"));
+        group.bench_function("scalar", |bench| {
+            bench.iter(|| scalar::sub_scaled(&mut dst, black_box(&a), &b, 0.5))
+        });
+        group.bench_function(&simd, |bench| {
+            bench.iter(|| (kernels.sub_scaled)(&mut dst, black_box(&a), &b, 0.5))
+        });
+        group.finish();
+
+        let xs: Vec<Complex<f32>> = a
+            .iter()
+            .zip(&b)
+            .map(|(&re, &im)| Complex::new(re, im))
+            .collect();
+        let ks: Vec<Complex<f32>> = s
+            .iter()
+            .zip(&a)
+            .map(|(&re, &im)| Complex::new(re, im))
+            .collect();
+        let mut group = c.benchmark_group(format!("complex_mul/{n}"));
+        group.bench_function("scalar", |bench| {
+            bench.iter_batched_ref(
+                || xs.clone(),
+                |xs| scalar::complex_mul(xs, black_box(&ks)),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(&simd, |bench| {
+            bench.iter_batched_ref(
+                || xs.clone(),
+                |xs| (kernels.complex_mul)(xs, black_box(&ks)),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+fn bench_transforms(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let (n_traces, n_samples) = (64, 8192);
+    let data = Array2::from_shape_fn((n_traces, n_samples), |_| rng.gen_range(-1.0f32..1.0));
+    let mut output = Array2::zeros((n_traces, n_samples));
+
+    let mut group = c.benchmark_group("transforms");
+    for window in [16, 256] {
+        let mut var: SlidingExecutor<f32, f32> =
+            SlidingExecutor::new(SlidingType::Var, window, None);
+        group.bench_with_input(
+            BenchmarkId::new("sliding_var", window),
+            &window,
+            |bench, _| bench.iter(|| var.apply_2d_inplace(output.view_mut(), data.view())),
+        );
+
+        let pattern = random_vec(&mut rng, window);
+        let mut matcher: MatchCorrelation<f32, f32> = MatchCorrelation::new(&pattern, n_samples);
+        let mut output = Array2::zeros((n_traces, n_samples - window + 1));
+        group.bench_with_input(
+            BenchmarkId::new("match_correlation", window),
+            &window,
+            |bench, _| bench.iter(|| matcher.apply_2d_inplace(output.view_mut(), data.view())),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_kernels, bench_transforms);
+criterion_main!(benches);
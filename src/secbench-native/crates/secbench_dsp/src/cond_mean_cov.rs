@@ -0,0 +1,632 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Class means and pooled covariance, for Gaussian templates.
+//!
+//! [`CondMeanCov`] accumulates the mean of each class and the pooled
+//! within-class scatter matrix over a selection of samples (points of
+//! interest). Accumulators can be merged, which allows to process traces in
+//! parallel. Frozen [`GaussianTemplates`] compute log-likelihoods of attack
+//! traces.
+//!
+//! The between-class scatter is derived from the class means, which gives
+//! LDA and class PCA projections (see [`CondMeanCov::lda`] and
+//! [`CondMeanCov::class_pca`]).
+
+use crate::eigen::symmetric_eigen;
+use crate::multi_condmean::Label;
+use crate::{DspFloat, IntoFloat};
+use ndarray::{s, Array1, Array2, ArrayView1, ArrayView2, Axis, Zip};
+use num_traits::AsPrimitive;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+#[derive(Clone)]
+pub struct CondMeanCov<I> {
+    pois: Vec<usize>,
+    // means[class][poi]
+    means: Array2<I>,
+    // Pooled within-class scatter matrix (sum of the scatter matrices of classes).
+    scatter: Array2<I>,
+    samples_per_class: Array1<u32>,
+}
+
+impl<I> CondMeanCov<I>
+where
+    I: DspFloat + Send + Sync + 'static,
+    u32: AsPrimitive<I>,
+{
+    /// Accumulator over samples `pois` of the traces.
+    pub fn new(pois: Vec<usize>, classes: usize) -> Self {
+        debug_assert_ne!(classes, 0);
+        let d = pois.len();
+        CondMeanCov {
+            pois,
+            means: Array2::zeros([classes, d]),
+            scatter: Array2::zeros([d, d]),
+            samples_per_class: Array1::zeros(classes),
+        }
+    }
+
+    pub fn pois(&self) -> &[usize] {
+        &self.pois
+    }
+
+    pub fn num_classes(&self) -> usize {
+        self.means.nrows()
+    }
+
+    pub fn samples_per_class(&self) -> &Array1<u32> {
+        &self.samples_per_class
+    }
+
+    /// Mean of each class, shape `(classes, pois)`.
+    pub fn means(&self) -> &Array2<I> {
+        &self.means
+    }
+
+    /// Pooled within-class scatter matrix, shape `(pois, pois)`.
+    pub fn scatter(&self) -> &Array2<I> {
+        &self.scatter
+    }
+
+    /// Pooled covariance matrix (unbiased).
+    pub fn covariance(&self) -> Array2<I> {
+        let n: u32 = self.samples_per_class.sum();
+        let classes = self.samples_per_class.iter().filter(|&&c| c != 0).count() as u32;
+        if n <= classes {
+            return Array2::zeros(self.scatter.raw_dim());
+        }
+        let denum: I = (n - classes).as_();
+        self.scatter.mapv(|x| x / denum)
+    }
+
+    /// Mean of all the traces accumulated.
+    pub fn total_mean(&self) -> Array1<I> {
+        let n: u32 = self.samples_per_class.sum();
+        let mut mean = Array1::zeros(self.pois.len());
+        if n == 0 {
+            return mean;
+        }
+        Zip::from(self.means.rows())
+            .and(&self.samples_per_class)
+            .for_each(|m, &n_c| mean.scaled_add(n_c.as_() / n.as_(), &m));
+        mean
+    }
+
+    /// Between-class scatter matrix, `sum_c n_c (m_c - m) (m_c - m)^T`.
+    pub fn between_scatter(&self) -> Array2<I> {
+        let d = self.pois.len();
+        let mean = self.total_mean();
+        let mut scatter = Array2::zeros([d, d]);
+        Zip::from(self.means.rows())
+            .and(&self.samples_per_class)
+            .for_each(|m, &n_c| {
+                if n_c == 0 {
+                    return;
+                }
+                let delta = &m - &mean;
+                Zip::from(scatter.rows_mut())
+                    .and(&delta)
+                    .for_each(|mut row, &x| row.scaled_add(x * n_c.as_(), &delta));
+            });
+        scatter
+    }
+
+    /// Fisher LDA projection: the `components` directions maximizing the
+    /// ratio of between-class to within-class scatter.
+    ///
+    /// Returns `None` if the within-class scatter is not positive definite.
+    /// At most `classes - 1` eigenvalues are non-zero.
+    pub fn lda(&self, components: usize) -> Option<Projection<I>> {
+        // With Sw = L L^T, solve the symmetric problem
+        // (L^-1 Sb L^-T) u = l u, then w = L^-T u.
+        let chol = cholesky(self.scatter.view())?;
+        let mut m = self.between_scatter();
+        for _ in 0..2 {
+            // Rows of `m` become L^-1 applied to the columns of `m`, since
+            // the result is symmetric, two passes give L^-1 Sb L^-T.
+            m.rows_mut()
+                .into_iter()
+                .for_each(|mut r| forward_substitution(chol.view(), r.as_slice_mut().unwrap()));
+            m = m.t().as_standard_layout().into_owned();
+        }
+        let m = (&m + &m.t()).mapv(|x| x * I::from_f64(0.5).unwrap());
+        let (values, vectors) = symmetric_eigen(m.view());
+        let k = components.min(values.len());
+        let mut basis = vectors
+            .slice(s![.., ..k])
+            .t()
+            .as_standard_layout()
+            .into_owned();
+        basis.rows_mut().into_iter().for_each(|mut r| {
+            backward_substitution(chol.view(), r.as_slice_mut().unwrap());
+            normalize_direction(r.as_slice_mut().unwrap());
+        });
+        Some(Projection {
+            values: values.slice(s![..k]).to_owned(),
+            components: basis,
+        })
+    }
+
+    /// Class PCA projection: principal components of the class means (each
+    /// class with at least one trace weighting equally).
+    ///
+    /// Eigenvalues are the (unbiased) variances of the class means along
+    /// each component.
+    pub fn class_pca(&self, components: usize) -> Projection<I> {
+        let rows: Vec<usize> = (0..self.num_classes())
+            .filter(|&c| self.samples_per_class[c] != 0)
+            .collect();
+        let means = self.means.select(Axis(0), &rows);
+        let d = self.pois.len();
+        let cov = match means.mean_axis(Axis(0)) {
+            Some(center) if rows.len() > 1 => {
+                let centered = &means - &center;
+                let denum: I = ((rows.len() - 1) as u32).as_();
+                centered.t().dot(&centered).mapv(|x| x / denum)
+            }
+            _ => Array2::zeros([d, d]),
+        };
+        let (values, vectors) = symmetric_eigen(cov.view());
+        let k = components.min(d);
+        Projection {
+            values: values.slice(s![..k]).to_owned(),
+            components: vectors
+                .slice(s![.., ..k])
+                .t()
+                .as_standard_layout()
+                .into_owned(),
+        }
+    }
+
+    /// Accumulate one trace (of any length, only `pois` are used).
+    pub fn process<S>(&mut self, trace: ArrayView1<S>, label: Label)
+    where
+        S: IntoFloat<I> + Copy,
+    {
+        let label = label as usize;
+        let samples = self.samples_per_class[label] + 1;
+        self.samples_per_class[label] = samples;
+        let mut mean = self.means.row_mut(label);
+        // Welford update: S += (x - m_old) (x - m_new)^T.
+        let mut delta = Array1::zeros(self.pois.len());
+        let mut delta_new = Array1::zeros(self.pois.len());
+        Zip::from(&mut delta)
+            .and(&mut delta_new)
+            .and(&mut mean)
+            .and(&self.pois)
+            .for_each(|d, dn, m, &p| {
+                let x: I = trace[p].into_float();
+                *d = x - *m;
+                *m += *d / samples.as_();
+                *dn = x - *m;
+            });
+        Zip::from(self.scatter.rows_mut())
+            .and(&delta)
+            .for_each(|mut row, &d| row.scaled_add(d, &delta_new));
+    }
+
+    pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView1<Label>)
+    where
+        S: IntoFloat<I> + Copy,
+    {
+        Zip::from(data.outer_iter())
+            .and(labels)
+            .for_each(|d, &l| self.process(d, l));
+    }
+
+    /// Same as [`CondMeanCov::process_block`], processing chunks of traces in
+    /// parallel and merging the results.
+    pub fn process_block_parallel<S>(
+        &mut self,
+        data: ArrayView2<S>,
+        labels: ArrayView1<Label>,
+        chunk_size: usize,
+    ) where
+        S: IntoFloat<I> + Copy + Sync,
+    {
+        let empty = CondMeanCov::new(self.pois.clone(), self.num_classes());
+        let partial = (0..data.nrows())
+            .step_by(chunk_size.max(1))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|start| {
+                let end = (start + chunk_size.max(1)).min(data.nrows());
+                let mut acc = empty.clone();
+                acc.process_block(data.slice(s![start..end, ..]), labels.slice(s![start..end]));
+                acc
+            })
+            .collect::<Vec<_>>();
+        partial.iter().for_each(|acc| self.merge(acc));
+    }
+
+    /// Merge another accumulator (over the same POIs and classes).
+    pub fn merge(&mut self, other: &Self) {
+        debug_assert_eq!(self.pois, other.pois);
+        self.scatter += &other.scatter;
+        Zip::from(self.means.rows_mut())
+            .and(other.means.rows())
+            .and(&mut self.samples_per_class)
+            .and(&other.samples_per_class)
+            .for_each(|mut m_1, m_2, n_1, &n_2| {
+                if n_2 == 0 {
+                    return;
+                }
+                let n = *n_1 + n_2;
+                let delta = &m_2 - &m_1;
+                let w_12: I = (*n_1).as_() * n_2.as_() / n.as_();
+                m_1.scaled_add(n_2.as_() / n.as_(), &delta);
+                Zip::from(self.scatter.rows_mut())
+                    .and(&delta)
+                    .for_each(|mut row, &d| row.scaled_add(d * w_12, &delta));
+                *n_1 = n;
+            });
+    }
+
+    /// Gaussian templates with the pooled covariance, or `None` if the
+    /// covariance is not positive definite (e.g., not enough traces).
+    pub fn freeze(&self) -> Option<GaussianTemplates<I>> {
+        let chol = cholesky(self.covariance().view())?;
+        let log_det = chol
+            .diag()
+            .iter()
+            .map(|x| x.ln())
+            .fold(I::zero(), |a, b| a + b)
+            * I::from_f64(2.0).unwrap();
+        let mut whitened_means = self.means.clone();
+        whitened_means
+            .rows_mut()
+            .into_iter()
+            .for_each(|mut m| forward_substitution(chol.view(), m.as_slice_mut().unwrap()));
+        Some(GaussianTemplates {
+            pois: self.pois.clone(),
+            chol,
+            whitened_means,
+            empty: self.samples_per_class.mapv(|n| n == 0),
+            log_det,
+        })
+    }
+}
+
+/// Cholesky factor `L` (lower triangular) of a symmetric positive definite
+/// matrix `a = L L^T`.
+pub(crate) fn cholesky<I: DspFloat>(a: ArrayView2<I>) -> Option<Array2<I>> {
+    let d = a.nrows();
+    let mut l = Array2::zeros([d, d]);
+    for j in 0..d {
+        let mut diag = a[(j, j)];
+        for k in 0..j {
+            diag -= l[(j, k)] * l[(j, k)];
+        }
+        if diag.is_nan() || diag <= I::zero() {
+            return None;
+        }
+        let diag = diag.sqrt();
+        l[(j, j)] = diag;
+        for i in j + 1..d {
+            let mut x = a[(i, j)];
+            for k in 0..j {
+                x -= l[(i, k)] * l[(j, k)];
+            }
+            l[(i, j)] = x / diag;
+        }
+    }
+    Some(l)
+}
+
+/// Solve `L y = x` in place.
+fn forward_substitution<I: DspFloat>(l: ArrayView2<I>, x: &mut [I]) {
+    for i in 0..x.len() {
+        let mut v = x[i];
+        for k in 0..i {
+            v -= l[(i, k)] * x[k];
+        }
+        x[i] = v / l[(i, i)];
+    }
+}
+
+/// Solve `L^T y = x` in place.
+fn backward_substitution<I: DspFloat>(l: ArrayView2<I>, x: &mut [I]) {
+    for i in (0..x.len()).rev() {
+        let mut v = x[i];
+        for k in i + 1..x.len() {
+            v -= l[(k, i)] * x[k];
+        }
+        x[i] = v / l[(i, i)];
+    }
+}
+
+/// Scale a direction to unit norm, with its largest component positive.
+fn normalize_direction<I: DspFloat>(x: &mut [I]) {
+    let norm = x.iter().fold(I::zero(), |acc, &v| acc + v * v).sqrt();
+    let largest = x
+        .iter()
+        .fold(I::zero(), |m, &v| if v.abs() > m.abs() { v } else { m });
+    if norm > I::zero() {
+        let scale = if largest < I::zero() { -norm } else { norm };
+        x.iter_mut().for_each(|v| *v /= scale);
+    }
+}
+
+/// Projection basis over the points of interest.
+#[derive(Clone, Debug)]
+pub struct Projection<I> {
+    /// Eigenvalues associated to each component, in decreasing order.
+    pub values: Array1<I>,
+    /// Components (one per row), shape `(components, pois)`.
+    pub components: Array2<I>,
+}
+
+/// Gaussian templates with a pooled covariance matrix.
+#[derive(Clone)]
+pub struct GaussianTemplates<I> {
+    pois: Vec<usize>,
+    // Cholesky factor of the covariance.
+    chol: Array2<I>,
+    // L^-1 m for each class mean m.
+    whitened_means: Array2<I>,
+    // Classes without profiling traces.
+    empty: Array1<bool>,
+    log_det: I,
+}
+
+impl<I> GaussianTemplates<I>
+where
+    I: DspFloat + Send + Sync,
+{
+    pub fn num_classes(&self) -> usize {
+        self.whitened_means.nrows()
+    }
+
+    /// Log-likelihood of each class for a trace (`-inf` for classes without
+    /// profiling traces).
+    pub fn log_likelihood<S>(&self, trace: ArrayView1<S>, out: &mut [I])
+    where
+        S: IntoFloat<I> + Copy,
+    {
+        let d = self.pois.len();
+        let mut z: Vec<I> = self.pois.iter().map(|&p| trace[p].into_float()).collect();
+        forward_substitution(self.chol.view(), &mut z);
+        let half = I::from_f64(0.5).unwrap();
+        let cst = -half
+            * (self.log_det
+                + I::from_usize(d).unwrap() * I::from_f64(core::f64::consts::TAU.ln()).unwrap());
+        Zip::from(out)
+            .and(self.whitened_means.rows())
+            .and(&self.empty)
+            .for_each(|o, m, &empty| {
+                *o = if empty {
+                    I::neg_infinity()
+                } else {
+                    let dist = z
+                        .iter()
+                        .zip(m)
+                        .fold(I::zero(), |acc, (&a, &b)| acc + (a - b) * (a - b));
+                    cst - half * dist
+                };
+            });
+    }
+
+    /// Log-likelihoods of a block of traces, shape `(traces, classes)`.
+    pub fn log_likelihood_block<S>(&self, data: ArrayView2<S>, parallel: bool) -> Array2<I>
+    where
+        S: IntoFloat<I> + Copy + Sync,
+    {
+        let mut out = Array2::zeros([data.nrows(), self.num_classes()]);
+        let zip = Zip::from(out.axis_iter_mut(Axis(0))).and(data.axis_iter(Axis(0)));
+        let f = |mut o: ndarray::ArrayViewMut1<I>, x: ArrayView1<S>| {
+            self.log_likelihood(x, o.as_slice_mut().unwrap())
+        };
+        if parallel {
+            zip.par_for_each(f);
+        } else {
+            zip.for_each(f);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cholesky, CondMeanCov};
+    use ndarray::{array, s, Array1, Array2, Axis};
+    use rand::distributions::Uniform;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_data(n: usize, classes: u16) -> (Array2<f64>, Array1<u16>) {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let labels = Array1::from_shape_fn(n, |_| rng.sample(Uniform::new(0, classes)));
+        let data = Array2::from_shape_fn((n, 6), |(i, j)| {
+            let x: f64 = rng.sample(Uniform::new(-1.0, 1.0));
+            x + (labels[i] as usize * (j + 1)) as f64 + if j == 3 { 0.0 } else { x }
+        });
+        (data, labels)
+    }
+
+    #[test]
+    fn test_mean_cov() {
+        let (data, labels) = random_data(1000, 3);
+        let pois = vec![0, 2, 3];
+        let mut acc = CondMeanCov::<f64>::new(pois.clone(), 3);
+        acc.process_block(data.view(), labels.view());
+        assert_eq!(acc.samples_per_class().sum(), 1000);
+
+        let x = data.select(Axis(1), &pois);
+        let mut scatter = Array2::<f64>::zeros((3, 3));
+        for c in 0..3 {
+            let rows: Vec<usize> = (0..1000).filter(|&i| labels[i] == c).collect();
+            let xc = x.select(Axis(0), &rows);
+            let mean = xc.mean_axis(Axis(0)).unwrap();
+            assert!(acc.means().row(c as usize).abs_diff_eq(&mean, 1e-9));
+            let centered = &xc - &mean;
+            scatter += &centered.t().dot(&centered);
+        }
+        assert!(acc.scatter().abs_diff_eq(&scatter, 1e-6));
+        assert!(acc.covariance().abs_diff_eq(&(scatter / 997.0), 1e-9));
+    }
+
+    #[test]
+    fn test_merge() {
+        let (data, labels) = random_data(1000, 4);
+        let pois = vec![1, 3, 4, 5];
+        let mut acc = CondMeanCov::<f64>::new(pois.clone(), 4);
+        acc.process_block(data.view(), labels.view());
+
+        let mut merged = CondMeanCov::<f64>::new(pois.clone(), 4);
+        merged.process_block(data.slice(s![..300, ..]), labels.slice(s![..300]));
+        let mut other = CondMeanCov::<f64>::new(pois.clone(), 4);
+        other.process_block(data.slice(s![300.., ..]), labels.slice(s![300..]));
+        merged.merge(&other);
+        assert_eq!(merged.samples_per_class(), acc.samples_per_class());
+        assert!(merged.means().abs_diff_eq(acc.means(), 1e-9));
+        assert!(merged.scatter().abs_diff_eq(acc.scatter(), 1e-6));
+
+        let mut par = CondMeanCov::<f64>::new(pois, 4);
+        par.process_block_parallel(data.view(), labels.view(), 128);
+        assert!(par.means().abs_diff_eq(acc.means(), 1e-9));
+        assert!(par.scatter().abs_diff_eq(acc.scatter(), 1e-6));
+    }
+
+    #[test]
+    fn test_scatter() {
+        let (data, labels) = random_data(1000, 4);
+        let pois = vec![0, 2, 3, 5];
+        let mut acc = CondMeanCov::<f64>::new(pois.clone(), 5);
+        acc.process_block(data.view(), labels.view());
+
+        // Within and between-class scatter sum to the total scatter.
+        let x = data.select(Axis(1), &pois);
+        let centered = &x - &x.mean_axis(Axis(0)).unwrap();
+        let total = centered.t().dot(&centered);
+        assert!(acc
+            .total_mean()
+            .abs_diff_eq(&x.mean_axis(Axis(0)).unwrap(), 1e-9));
+        assert!((acc.scatter() + &acc.between_scatter()).abs_diff_eq(&total, 1e-6));
+    }
+
+    #[test]
+    fn test_lda() {
+        let (data, labels) = random_data(2000, 4);
+        let mut acc = CondMeanCov::<f64>::new(vec![0, 1, 3, 4, 5], 4);
+        acc.process_block(data.view(), labels.view());
+        let lda = acc.lda(5).unwrap();
+        assert_eq!(lda.components.shape(), [5, 5]);
+        assert!(lda.values.windows(2).into_iter().all(|w| w[0] >= w[1]));
+        // Class means are collinear, a single direction discriminates
+        // them, and the rank of Sb is at most classes - 1.
+        assert!(lda.values[0] > 100.0 && lda.values[1] < 0.01);
+        assert!(lda.values[3].abs() < 1e-9 && lda.values[4].abs() < 1e-9);
+
+        // Generalized eigenproblem Sb w = l Sw w.
+        let (sw, sb) = (acc.scatter(), acc.between_scatter());
+        for (w, &l) in lda.components.outer_iter().zip(&lda.values) {
+            assert!((w.dot(&w) - 1.0).abs() < 1e-12);
+            assert!(sb.dot(&w).abs_diff_eq(&(sw.dot(&w) * l), 1e-6 * l.max(1.0)));
+        }
+        assert_eq!(
+            acc.lda(2).unwrap().components,
+            lda.components.slice(s![..2, ..])
+        );
+        let empty = CondMeanCov::<f64>::new(vec![0, 1], 2);
+        assert!(empty.lda(1).is_none());
+    }
+
+    #[test]
+    fn test_class_pca() {
+        let (data, labels) = random_data(1000, 3);
+        let mut acc = CondMeanCov::<f64>::new(vec![0, 1, 2], 4);
+        acc.process_block(data.view(), labels.view());
+        let pca = acc.class_pca(2);
+        assert_eq!(pca.components.shape(), [2, 3]);
+        // Empty class 3 is ignored.
+        let means = acc.means().slice(s![..3, ..]).to_owned();
+        let centered = &means - &means.mean_axis(Axis(0)).unwrap();
+        let cov = centered.t().dot(&centered) / 2.0;
+        for (v, &l) in pca.components.outer_iter().zip(&pca.values) {
+            assert!(cov.dot(&v).abs_diff_eq(&(&v * l), 1e-9));
+        }
+        assert!((pca.values.sum() - cov.diag().sum()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cholesky() {
+        let a = array![[4.0, 2.0, 0.4], [2.0, 5.0, 1.0], [0.4, 1.0, 3.0]];
+        let l = cholesky(a.view()).unwrap();
+        assert!(l.dot(&l.t()).abs_diff_eq(&a, 1e-12));
+        assert_eq!(l[(0, 1)], 0.0);
+        assert!(cholesky(array![[1.0, 2.0], [2.0, 1.0]].view()).is_none());
+    }
+
+    #[test]
+    fn test_templates() {
+        let (data, labels) = random_data(2000, 5);
+        let mut acc = CondMeanCov::<f64>::new(vec![0, 1, 3], 6);
+        acc.process_block(data.view(), labels.view());
+        let templates = acc.freeze().unwrap();
+        let ll = templates.log_likelihood_block(data.view(), true);
+        assert_eq!(ll.shape(), [2000, 6]);
+        // Class 5 has no profiling traces.
+        assert!(ll.column(5).iter().all(|&x| x == f64::NEG_INFINITY));
+
+        let correct = ll
+            .outer_iter()
+            .zip(&labels)
+            .filter(|(row, &l)| {
+                let best = (0..6).max_by(|&a, &b| row[a].total_cmp(&row[b])).unwrap();
+                best == l as usize
+            })
+            .count();
+        assert!(correct > 1900, "{correct} traces classified correctly");
+
+        // Direct evaluation of the Gaussian log-density.
+        let cov = acc.covariance();
+        let l = cholesky(cov.view()).unwrap();
+        let det = l.diag().product().powi(2);
+        let x = data.row(0).select(Axis(0), &[0, 1, 3]);
+        let diff = &x - &acc.means().row(2);
+        let inv = {
+            // Inverse of the 3x3 covariance with the cofactor formula.
+            let c = &cov;
+            let mut inv = Array2::<f64>::zeros((3, 3));
+            for i in 0..3 {
+                for j in 0..3 {
+                    let (r0, r1) = ((j + 1) % 3, (j + 2) % 3);
+                    let (c0, c1) = ((i + 1) % 3, (i + 2) % 3);
+                    inv[(i, j)] = (c[(r0, c0)] * c[(r1, c1)] - c[(r0, c1)] * c[(r1, c0)]) / det;
+                }
+            }
+            inv
+        };
+        let expected = -0.5 * diff.dot(&inv.dot(&diff))
+            - 0.5 * det.ln()
+            - 1.5 * (2.0 * std::f64::consts::PI).ln();
+        assert!((ll[(0, 2)] - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Distribution-level leakage detection tests.
+//!
+//! [`DistributionTest`] accumulates the [`JointHistogram`] of each target
+//! variable (e.g., fixed versus random, or a multi-class intermediate value)
+//! and each quantized sample. Unlike the Welch t-test, the chi-squared and
+//! Kolmogorov-Smirnov tests computed from the histograms detect any
+//! difference between the distributions of the classes, not only
+//! differences of means.
+
+use crate::histogram::{marginals, JointHistogram, Quantizer};
+use crate::multi_condmean::Label;
+use crate::stats::{chi2_sf, ks_2samp_pvalue};
+use crate::IntoFloat;
+use ndarray::{Array2, ArrayView2};
+
+#[derive(Clone)]
+pub struct DistributionTest {
+    inner: JointHistogram,
+}
+
+impl DistributionTest {
+    pub fn new(targets: usize, samples: usize, classes: usize, quantizer: Quantizer) -> Self {
+        DistributionTest {
+            inner: JointHistogram::new(targets, samples, classes, quantizer),
+        }
+    }
+
+    /// The accumulated histograms.
+    pub fn histogram(&self) -> &JointHistogram {
+        &self.inner
+    }
+
+    pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView2<Label>)
+    where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        self.inner.process_block(data, labels);
+    }
+
+    /// See [`JointHistogram::process_block_parallel`].
+    pub fn process_block_parallel<S>(
+        &mut self,
+        data: ArrayView2<S>,
+        labels: ArrayView2<Label>,
+        chunk_size: usize,
+    ) where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        self.inner.process_block_parallel(data, labels, chunk_size);
+    }
+
+    /// Merge another accumulator (with the same shape and quantizer).
+    pub fn merge(&mut self, other: &Self) {
+        self.inner.merge(&other.inner);
+    }
+
+    /// Chi-squared test of independence between each target and each
+    /// sample: returns the statistics and the p-values, of shape
+    /// `(targets, samples)`.
+    ///
+    /// Empty classes and bins are ignored; the p-value is 1 when fewer than
+    /// two classes or two bins are observed.
+    pub fn freeze_chi2_test(&self) -> (Array2<f64>, Array2<f64>) {
+        let results = self.inner.map_joint(|joint| {
+            let (chi2, dof) = chi2_statistic(joint);
+            let p = if dof == 0 {
+                1.0
+            } else {
+                chi2_sf(chi2, dof as f64)
+            };
+            (chi2, p)
+        });
+        (results.mapv(|r| r.0), results.mapv(|r| r.1))
+    }
+
+    /// Two-sample Kolmogorov-Smirnov tests between the classes of each target
+    /// at each sample: returns the statistics and asymptotic p-values, of
+    /// shape `(targets, samples)`.
+    ///
+    /// With two classes (e.g., fixed and random traces), this is the usual
+    /// two-sample test. With more classes, each class is compared with the
+    /// traces of all other classes: the statistic is the largest one, and the
+    /// p-value the smallest one with a Bonferroni correction. Empty classes
+    /// are ignored.
+    ///
+    /// The statistic is computed on the quantized samples, it is a lower
+    /// bound of the statistic of the raw samples (exact for integer samples
+    /// with one bin per value).
+    pub fn freeze_ks_test(&self) -> (Array2<f64>, Array2<f64>) {
+        let results = self.inner.map_joint(ks_test);
+        (results.mapv(|r| r.0), results.mapv(|r| r.1))
+    }
+}
+
+/// Pearson's chi-squared statistic of a contingency table, and its number of
+/// degrees of freedom (empty rows and columns are ignored).
+fn chi2_statistic(joint: ArrayView2<u32>) -> (f64, usize) {
+    let (n_classes, n_bins) = marginals(joint);
+    let n: u64 = n_classes.iter().sum();
+    let rows = n_classes.iter().filter(|&&n| n != 0).count();
+    let cols = n_bins.iter().filter(|&&n| n != 0).count();
+    if rows < 2 || cols < 2 {
+        return (0.0, 0);
+    }
+    // sum (O - E)^2 / E = N (sum O^2 / (n_c n_b) - 1).
+    let sum: f64 = joint
+        .indexed_iter()
+        .filter(|(_, &o)| o != 0)
+        .map(|((c, b), &o)| {
+            let o = o as f64;
+            o * o / (n_classes[c] as f64 * n_bins[b] as f64)
+        })
+        .sum();
+    ((n as f64 * (sum - 1.0)).max(0.0), (rows - 1) * (cols - 1))
+}
+
+/// Kolmogorov-Smirnov statistic between the traces of class `c` and all
+/// other traces of a joint histogram, with the sizes of both samples.
+fn ks_statistic(joint: ArrayView2<u32>, n_bins: &[u64], c: usize) -> (f64, u64, u64) {
+    let n_a: u64 = joint.row(c).iter().map(|&x| x as u64).sum();
+    let n_b = n_bins.iter().sum::<u64>() - n_a;
+    if n_a == 0 || n_b == 0 {
+        return (0.0, n_a, n_b);
+    }
+    let (mut c_a, mut c_all, mut d) = (0u64, 0u64, 0.0f64);
+    for (&x, &n) in joint.row(c).iter().zip(n_bins) {
+        c_a += x as u64;
+        c_all += n;
+        d = d.max((c_a as f64 / n_a as f64 - (c_all - c_a) as f64 / n_b as f64).abs());
+    }
+    (d, n_a, n_b)
+}
+
+/// Kolmogorov-Smirnov statistic and p-value of a joint histogram, see
+/// [`DistributionTest::freeze_ks_test`].
+fn ks_test(joint: ArrayView2<u32>) -> (f64, f64) {
+    let (n_classes, n_bins) = marginals(joint);
+    let classes: Vec<usize> = (0..n_classes.len())
+        .filter(|&c| n_classes[c] != 0)
+        .collect();
+    // With two classes, both comparisons are the same test.
+    let tested = match classes.len() {
+        0 | 1 => return (0.0, 1.0),
+        2 => &classes[..1],
+        _ => &classes[..],
+    };
+    let (mut d_max, mut p_min) = (0.0f64, 1.0f64);
+    for &c in tested {
+        let (d, n_a, n_b) = ks_statistic(joint, &n_bins, c);
+        d_max = d_max.max(d);
+        p_min = p_min.min(ks_2samp_pvalue(d, n_a, n_b));
+    }
+    (d_max, (p_min * tested.len() as f64).min(1.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chi2_statistic, ks_statistic, ks_test, DistributionTest};
+    use crate::histogram::Quantizer;
+    use crate::mia::test::random_data;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_statistics() {
+        let (chi2, dof) = chi2_statistic(array![[10, 20], [30, 40]].view());
+        assert_eq!(dof, 1);
+        assert!((chi2 - 0.7936507936507936).abs() < 1e-12);
+        // Empty rows and columns are ignored.
+        let (chi2, dof) = chi2_statistic(array![[10, 0, 20], [0, 0, 0], [30, 0, 40]].view());
+        assert_eq!(dof, 1);
+        assert!((chi2 - 0.7936507936507936).abs() < 1e-12);
+        assert_eq!(chi2_statistic(array![[3, 4]].view()), (0.0, 0));
+
+        let joint = array![[1, 0, 0, 1], [0, 1, 1, 0]];
+        assert_eq!(ks_statistic(joint.view(), &[1, 1, 1, 1], 0), (0.5, 2, 2));
+        assert_eq!(ks_statistic(joint.view(), &[1, 1, 1, 1], 1), (0.5, 2, 2));
+        assert_eq!(ks_test(array![[1, 2], [0, 0]].view()), (0.0, 1.0));
+        // Class 2 against classes 0 and 1.
+        let joint = array![[2, 0], [2, 0], [0, 4]];
+        assert_eq!(ks_statistic(joint.view(), &[4, 4], 2), (1.0, 4, 4));
+        let (d, p) = ks_test(joint.view());
+        assert_eq!(d, 1.0);
+        assert!(p > 0.0 && p <= 1.0);
+    }
+
+    #[test]
+    fn test_leakage_tests() {
+        let (data, labels) = random_data(5000);
+        let mut acc = DistributionTest::new(2, 5, 4, Quantizer::new(-4.0, 28.0, 32));
+        acc.process_block(data.view(), labels.view());
+        let (chi2, p) = acc.freeze_chi2_test();
+        assert!(chi2[(1, 2)] > 1000.0 && p[(1, 2)] < 1e-100);
+        assert!(p[(0, 0)] < 1e-100);
+        assert!(p[(1, 4)] > 1e-4 && p[(0, 3)] > 1e-4, "{p}");
+
+        // Multi-class KS test: the non-monotonic leakage of sample 2 is
+        // detected.
+        let (d, p) = acc.freeze_ks_test();
+        assert!(d[(1, 2)] > 0.5 && p[(1, 2)] < 1e-50, "{d} {p}");
+        assert!(p[(0, 0)] < 1e-50);
+        assert!(p[(1, 4)] > 1e-4 && p[(0, 3)] > 1e-4, "{p}");
+
+        // Fixed versus random: class 1 has a wider distribution.
+        let labels = labels.mapv(|l| (l >= 2) as u16);
+        let data = Array2::from_shape_fn((5000, 5), |(i, j)| match j {
+            1 if labels[(i, 0)] == 1 => data[(i, 3)] * 3,
+            _ => data[(i, 3)],
+        });
+        let mut acc = DistributionTest::new(2, 5, 2, Quantizer::new(-8.0, 8.0, 16));
+        acc.process_block(data.view(), labels.view());
+        let (d, p) = acc.freeze_ks_test();
+        assert!(d[(0, 1)] > 0.2 && p[(0, 1)] < 1e-50, "{d} {p}");
+        assert!(d[(1, 1)] < 0.05 && p[(1, 1)] > 1e-4, "{d} {p}");
+        assert!(p[(0, 0)] > 1e-4);
+    }
+}
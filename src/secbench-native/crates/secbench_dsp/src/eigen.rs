@@ -0,0 +1,170 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Eigendecomposition of small dense symmetric matrices.
+//!
+//! The cyclic Jacobi method is used: it is simple, accurate and fast enough
+//! for the matrix sizes met in side-channel analysis (a few hundreds of
+//! points of interest at most).
+
+use crate::DspFloat;
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+
+/// Maximum number of Jacobi sweeps, convergence is usually reached in less
+/// than 10 sweeps.
+const MAX_SWEEPS: usize = 64;
+
+/// Eigenvalues and eigenvectors of a symmetric matrix `a`.
+///
+/// Eigenvalues are sorted in decreasing order and the eigenvectors are
+/// the columns of the returned matrix (orthonormal). The sign of each
+/// eigenvector is fixed so that its largest component is positive.
+///
+/// The input must be finite: NaN entries do not panic, but give meaningless
+/// (usually NaN) results.
+pub fn symmetric_eigen<I: DspFloat>(a: ArrayView2<I>) -> (Array1<I>, Array2<I>) {
+    debug_assert_eq!(a.nrows(), a.ncols());
+    let d = a.nrows();
+    let mut a = a.to_owned();
+    let mut v = Array2::eye(d);
+    let half = I::from_f64(0.5).unwrap();
+    for _ in 0..MAX_SWEEPS {
+        let off = off_diagonal_norm(&a);
+        let scale = a.diag().fold(I::zero(), |acc, &x| acc + x * x) + off;
+        if off <= I::epsilon() * I::epsilon() * scale {
+            break;
+        }
+        for p in 0..d {
+            for q in p + 1..d {
+                let apq = a[(p, q)];
+                if apq == I::zero() {
+                    continue;
+                }
+                // Rotation zeroing a[p, q] (Golub & Van Loan, Algorithm 8.4.1).
+                let theta = (a[(q, q)] - a[(p, p)]) * half / apq;
+                let t = theta.signum() / (theta.abs() + (theta * theta + I::one()).sqrt());
+                let c = I::one() / (t * t + I::one()).sqrt();
+                let s = t * c;
+                rotate(&mut a, &mut v, p, q, c, s);
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..d).collect();
+    order.sort_by(|&i, &j| a[(j, j)].total_cmp(&a[(i, i)]));
+    let values = Array1::from_iter(order.iter().map(|&i| a[(i, i)]));
+    let mut vectors = v.select(Axis(1), &order);
+    vectors.columns_mut().into_iter().for_each(|mut col| {
+        let largest = col
+            .iter()
+            .fold(I::zero(), |m, &x| if x.abs() > m.abs() { x } else { m });
+        if largest < I::zero() {
+            col.mapv_inplace(|x| -x);
+        }
+    });
+    (values, vectors)
+}
+
+fn off_diagonal_norm<I: DspFloat>(a: &Array2<I>) -> I {
+    a.indexed_iter()
+        .filter(|((i, j), _)| i != j)
+        .fold(I::zero(), |acc, (_, &x)| acc + x * x)
+}
+
+/// Apply the Jacobi rotation `J(p, q)` to `a` (`J^T a J`) and accumulate it
+/// in `v`.
+fn rotate<I: DspFloat>(a: &mut Array2<I>, v: &mut Array2<I>, p: usize, q: usize, c: I, s: I) {
+    let d = a.nrows();
+    for k in 0..d {
+        let (akp, akq) = (a[(k, p)], a[(k, q)]);
+        a[(k, p)] = c * akp - s * akq;
+        a[(k, q)] = s * akp + c * akq;
+    }
+    for k in 0..d {
+        let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+        a[(p, k)] = c * apk - s * aqk;
+        a[(q, k)] = s * apk + c * aqk;
+    }
+    for k in 0..d {
+        let (vkp, vkq) = (v[(k, p)], v[(k, q)]);
+        v[(k, p)] = c * vkp - s * vkq;
+        v[(k, q)] = s * vkp + c * vkq;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::symmetric_eigen;
+    use ndarray::{array, Array2};
+    use rand::distributions::Uniform;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_diagonal() {
+        let a = array![[1.0, 0.0, 0.0], [0.0, 3.0, 0.0], [0.0, 0.0, 2.0]];
+        let (values, vectors) = symmetric_eigen(a.view());
+        assert_eq!(values, array![3.0, 2.0, 1.0]);
+        assert_eq!(
+            vectors,
+            array![[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_known_values() {
+        let a = array![[2.0, 1.0], [1.0, 2.0]];
+        let (values, vectors) = symmetric_eigen(a.view());
+        assert!(values.abs_diff_eq(&array![3.0, 1.0], 1e-12));
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(vectors.column(0).abs_diff_eq(&array![h, h], 1e-12));
+    }
+
+    #[test]
+    fn test_reconstruction() {
+        let mut rng = StdRng::seed_from_u64(0xe16e);
+        let b = Array2::from_shape_fn((20, 20), |_| rng.sample(Uniform::new(-1.0, 1.0)));
+        let a = &b + &b.t();
+        let (values, vectors) = symmetric_eigen(a.view());
+        assert!(values.windows(2).into_iter().all(|w| w[0] >= w[1]));
+        assert!(vectors
+            .t()
+            .dot(&vectors)
+            .abs_diff_eq(&Array2::eye(20), 1e-10));
+        let rebuilt = vectors.dot(&Array2::from_diag(&values)).dot(&vectors.t());
+        assert!(rebuilt.abs_diff_eq(&a, 1e-10));
+    }
+
+    #[test]
+    fn test_nan() {
+        let a = array![[1.0, f64::NAN], [f64::NAN, 2.0]];
+        let (values, vectors) = symmetric_eigen(a.view());
+        assert_eq!(values.len(), 2);
+        assert_eq!(vectors.dim(), (2, 2));
+    }
+}
@@ -132,9 +132,8 @@ where
             )
             .unwrap();
 
-        for (x, k) in self.fft_tr_output.iter_mut().zip(self.filter_kernel.iter()) {
-            *x *= k;
-        }
+        let kernels = Dst::kernels();
+        (kernels.complex_mul)(&mut self.fft_tr_output, &self.filter_kernel);
 
         // Move data back to time domain
         self.ctx
@@ -144,14 +143,12 @@ where
 
         // Normalize
         let norm_factor = Dst::from_usize(self.fft_len()).unwrap();
-        for x in output {
-            *x /= norm_factor;
-        }
+        (kernels.div_assign)(output, norm_factor);
     }
 
     pub fn filter_single_pass(&mut self, output: &mut [Dst], input: &[Src]) {
         let fft_len = self.fft_len();
-        debug_assert!(self.filter_kernel.len() > 0, "kernel must be initialized");
+        debug_assert!(!self.filter_kernel.is_empty(), "kernel must be initialized");
         debug_assert!(input.len() <= fft_len);
         debug_assert!(output.len() >= input.len());
         debug_assert!(output.len() >= fft_len);
@@ -185,7 +182,7 @@ where
     /// A good explanation is available on [Wikipedia](https://en.wikipedia.org/wiki/Phase_correlation).
     pub fn phase_correlation(&mut self, output: &mut [Dst], input: &[Src]) {
         let fft_len = self.fft_len();
-        debug_assert!(self.filter_kernel.len() > 0, "kernel must be initialized");
+        debug_assert!(!self.filter_kernel.is_empty(), "kernel must be initialized");
         debug_assert!(output.len() >= input.len());
         debug_assert!(output.len() >= fft_len);
 
@@ -220,9 +217,7 @@ where
 
         // Normalize
         let norm_factor = Dst::from_usize(fft_len).unwrap();
-        for x in output {
-            *x /= norm_factor;
-        }
+        (Dst::kernels().div_assign)(output, norm_factor);
     }
 }
 
@@ -420,4 +415,4 @@ mod test {
         let output_i32 = output.iter().map(|x| x.round() as i32).collect_vec();
         assert_eq!(output_i32, &[3, 8, 14, 20, 26, 14, 5]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,273 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Joint histograms of labels and quantized samples.
+//!
+//! [`JointHistogram`] accumulates, for each target variable and each sample,
+//! the joint histogram of the label and of the quantized sample. The layout
+//! of data and labels is the same as [`CondMeanVar`](crate::CondMeanVar):
+//! traces of shape `(n, samples)` and labels of shape `(n, targets)`.
+//!
+//! It is the storage of [`MiaHistogram`](crate::MiaHistogram) (mutual
+//! information) and [`DistributionTest`](crate::DistributionTest)
+//! (chi-squared and Kolmogorov-Smirnov leakage detection tests).
+
+use crate::multi_condmean::Label;
+use crate::IntoFloat;
+use ndarray::parallel::prelude::*;
+use ndarray::{s, Array2, Array4, ArrayView1, ArrayView2, ArrayViewMut3, Axis, Zip};
+
+/// Uniform quantization of samples in `bins` bins over `[low, high)`.
+///
+/// Values out of the range are put in the first or last bin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer {
+    low: f64,
+    scale: f64,
+    bins: usize,
+}
+
+impl Quantizer {
+    pub fn new(low: f64, high: f64, bins: usize) -> Self {
+        debug_assert!(high > low);
+        debug_assert!(bins > 0 && bins <= 1 << 16);
+        Quantizer {
+            low,
+            scale: bins as f64 / (high - low),
+            bins,
+        }
+    }
+
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    #[inline(always)]
+    pub fn bin(&self, x: f64) -> u16 {
+        let b = ((x - self.low) * self.scale).floor();
+        b.clamp(0.0, (self.bins - 1) as f64) as u16
+    }
+}
+
+#[derive(Clone)]
+pub struct JointHistogram {
+    quantizer: Quantizer,
+    // hist[target][class][sample][bin] -> number of traces.
+    hist: Array4<u32>,
+    // samples_per_class[target][class] -> number of traces in the class.
+    samples_per_class: Array2<u32>,
+}
+
+impl JointHistogram {
+    pub fn new(targets: usize, samples: usize, classes: usize, quantizer: Quantizer) -> Self {
+        debug_assert_ne!(samples, 0);
+        debug_assert_ne!(classes, 0);
+        debug_assert_ne!(targets, 0);
+        JointHistogram {
+            quantizer,
+            hist: Array4::zeros([targets, classes, samples, quantizer.bins()]),
+            samples_per_class: Array2::zeros([targets, classes]),
+        }
+    }
+
+    pub fn quantizer(&self) -> &Quantizer {
+        &self.quantizer
+    }
+
+    pub fn num_classes(&self) -> usize {
+        self.hist.shape()[1]
+    }
+
+    pub fn samples_per_class(&self) -> &Array2<u32> {
+        &self.samples_per_class
+    }
+
+    /// Joint histograms, of shape `(targets, classes, samples, bins)`.
+    pub fn histograms(&self) -> &Array4<u32> {
+        &self.hist
+    }
+
+    fn quantize<S>(&self, data: ArrayView2<S>, parallel: bool) -> Array2<u16>
+    where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        let mut quantized = Array2::zeros(data.raw_dim());
+        let zip = Zip::from(&mut quantized).and(data);
+        let f = |q: &mut u16, &x: &S| *q = self.quantizer.bin(x.into_float());
+        if parallel {
+            zip.par_for_each(f);
+        } else {
+            zip.for_each(f);
+        }
+        quantized
+    }
+
+    /// Add quantized traces to the histograms `hist[class][sample][bin]` of
+    /// a target.
+    fn accumulate(
+        mut hist: ArrayViewMut3<u32>,
+        quantized: ArrayView2<u16>,
+        labels: ArrayView1<Label>,
+    ) {
+        Zip::from(quantized.outer_iter())
+            .and(labels)
+            .for_each(|q, &label| {
+                let mut h = hist.index_axis_mut(Axis(0), label as usize);
+                Zip::from(h.outer_iter_mut())
+                    .and(q)
+                    .for_each(|mut row, &b| row[b as usize] += 1);
+            });
+    }
+
+    fn count_labels(&mut self, labels: ArrayView2<Label>) {
+        labels.outer_iter().for_each(|l| {
+            Zip::from(self.samples_per_class.outer_iter_mut())
+                .and(l)
+                .for_each(|mut n, &label| n[label as usize] += 1);
+        });
+    }
+
+    pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView2<Label>)
+    where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        debug_assert_eq!(data.ncols(), self.hist.shape()[2]);
+        debug_assert_eq!(labels.ncols(), self.hist.shape()[0]);
+        let quantized = self.quantize(data, false);
+        Zip::from(self.hist.outer_iter_mut())
+            .and(labels.columns())
+            .for_each(|h, l| Self::accumulate(h, quantized.view(), l));
+        self.count_labels(labels);
+    }
+
+    /// Same as [`JointHistogram::process_block`], targets and chunks of
+    /// `chunk_size` samples being processed in parallel.
+    pub fn process_block_parallel<S>(
+        &mut self,
+        data: ArrayView2<S>,
+        labels: ArrayView2<Label>,
+        chunk_size: usize,
+    ) where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        debug_assert_eq!(data.ncols(), self.hist.shape()[2]);
+        debug_assert_eq!(labels.ncols(), self.hist.shape()[0]);
+        let quantized = self.quantize(data, true);
+        let chunk_size = chunk_size.max(1);
+        self.hist
+            .outer_iter_mut()
+            .into_par_iter()
+            .zip(labels.axis_iter(Axis(1)).into_par_iter())
+            .for_each(|(mut h, l)| {
+                h.axis_chunks_iter_mut(Axis(1), chunk_size)
+                    .into_par_iter()
+                    .zip(
+                        quantized
+                            .axis_chunks_iter(Axis(1), chunk_size)
+                            .into_par_iter(),
+                    )
+                    .for_each(|(h, q)| Self::accumulate(h, q, l));
+            });
+        self.count_labels(labels);
+    }
+
+    /// Merge another accumulator (with the same shape and quantizer).
+    pub fn merge(&mut self, other: &Self) {
+        debug_assert_eq!(self.quantizer, other.quantizer);
+        self.hist += &other.hist;
+        self.samples_per_class += &other.samples_per_class;
+    }
+
+    /// Apply `f` on the joint histogram `(classes, bins)` of each target and
+    /// sample, in parallel. The output has shape `(targets, samples)`.
+    pub fn map_joint<T, F>(&self, f: F) -> Array2<T>
+    where
+        T: Default + Clone + Send,
+        F: Fn(ArrayView2<u32>) -> T + Sync,
+    {
+        let shape = self.hist.shape();
+        let mut out = Array2::default([shape[0], shape[2]]);
+        Zip::indexed(&mut out).par_for_each(|(t, j), o| *o = f(self.hist.slice(s![t, .., j, ..])));
+        out
+    }
+}
+
+/// Marginal counts of a joint histogram `joint[class][bin]`.
+pub(crate) fn marginals(joint: ArrayView2<u32>) -> (Vec<u64>, Vec<u64>) {
+    let mut n_classes = vec![0u64; joint.nrows()];
+    let mut n_bins = vec![0u64; joint.ncols()];
+    joint.indexed_iter().for_each(|((c, b), &n)| {
+        n_classes[c] += n as u64;
+        n_bins[b] += n as u64;
+    });
+    (n_classes, n_bins)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JointHistogram, Quantizer};
+    use ndarray::{s, Array1, Array2, Axis};
+    use rand::distributions::Uniform;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_quantizer() {
+        let q = Quantizer::new(-1.0, 1.0, 4);
+        assert_eq!(q.bin(-1.0), 0);
+        assert_eq!(q.bin(-0.4), 1);
+        assert_eq!(q.bin(0.0), 2);
+        assert_eq!(q.bin(0.99), 3);
+        assert_eq!(q.bin(-5.0), 0);
+        assert_eq!(q.bin(7.0), 3);
+    }
+
+    #[test]
+    fn test_merge_parallel() {
+        let mut rng = StdRng::seed_from_u64(0x3141);
+        let labels = Array2::from_shape_fn((3000, 2), |_| rng.sample(Uniform::new(0u16, 4)));
+        let data = Array2::from_shape_fn((3000, 5), |(i, j)| {
+            labels[(i, j % 2)] as i8 * 4 + rng.sample(Uniform::new(-2i8, 3))
+        });
+        let q = Quantizer::new(-4.0, 28.0, 16);
+        let mut acc = JointHistogram::new(2, 5, 4, q);
+        acc.process_block(data.view(), labels.view());
+
+        let mut merged = JointHistogram::new(2, 5, 4, q);
+        merged.process_block(data.slice(s![..1000, ..]), labels.slice(s![..1000, ..]));
+        let mut other = JointHistogram::new(2, 5, 4, q);
+        other.process_block_parallel(data.slice(s![1000.., ..]), labels.slice(s![1000.., ..]), 2);
+        merged.merge(&other);
+        assert_eq!(merged.histograms(), acc.histograms());
+        assert_eq!(merged.samples_per_class(), acc.samples_per_class());
+        let n: Array1<u32> = acc.samples_per_class().sum_axis(Axis(1));
+        assert_eq!(n, Array1::from_elem(2, 3000));
+        assert_eq!(acc.histograms().sum(), 2 * 3000 * 5);
+    }
+}
@@ -39,11 +39,24 @@
 //! - `batching`: Enable support for parallel processing of signals. Also enables `ndarray`.
 //!
 
+pub mod cond_mean_cov;
+pub mod distribution_test;
+pub mod eigen;
 pub mod fft;
+pub mod histogram;
+pub mod mia;
 pub mod multi_condmean;
+pub mod poi;
 pub mod sliding;
+pub mod stats;
+pub mod trace_stats;
 mod traits;
+pub mod vectorized;
 
-pub use traits::{DspFloat, IntoFloat, Transform1D, Transform2D};
+pub use traits::{Accumulate, DspFloat, IntoFloat, Transform1D, Transform2D};
 
-pub use multi_condmean::{CondMeanVar, CondMeanVarP};
\ No newline at end of file
+pub use cond_mean_cov::{CondMeanCov, GaussianTemplates, Projection};
+pub use distribution_test::DistributionTest;
+pub use histogram::JointHistogram;
+pub use mia::MiaHistogram;
+pub use multi_condmean::{CondMeanVar, CondMeanVarP};
@@ -0,0 +1,205 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Histogram-based mutual information analysis (MIA).
+//!
+//! [`MiaHistogram`] accumulates the [`JointHistogram`] of each target
+//! variable and each quantized sample. Accumulators can be merged, and mutual
+//! information estimates (in bits) are computed from the histograms, with an
+//! optional bias correction.
+
+use crate::histogram::{marginals, JointHistogram, Quantizer};
+use crate::multi_condmean::Label;
+use crate::IntoFloat;
+use ndarray::{Array2, ArrayView2};
+
+/// Bias correction of mutual information estimates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BiasCorrection {
+    /// Plug-in estimator (biased upwards).
+    None,
+    /// Miller-Madow correction of the entropies, `(K - 1) / 2N` for `K`
+    /// non-empty cells. Corrected estimates can be slightly negative.
+    #[default]
+    MillerMadow,
+}
+
+#[derive(Clone)]
+pub struct MiaHistogram {
+    inner: JointHistogram,
+}
+
+impl MiaHistogram {
+    pub fn new(targets: usize, samples: usize, classes: usize, quantizer: Quantizer) -> Self {
+        MiaHistogram {
+            inner: JointHistogram::new(targets, samples, classes, quantizer),
+        }
+    }
+
+    /// The accumulated histograms.
+    pub fn histogram(&self) -> &JointHistogram {
+        &self.inner
+    }
+
+    pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView2<Label>)
+    where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        self.inner.process_block(data, labels);
+    }
+
+    /// See [`JointHistogram::process_block_parallel`].
+    pub fn process_block_parallel<S>(
+        &mut self,
+        data: ArrayView2<S>,
+        labels: ArrayView2<Label>,
+        chunk_size: usize,
+    ) where
+        S: IntoFloat<f64> + Copy + Sync,
+    {
+        self.inner.process_block_parallel(data, labels, chunk_size);
+    }
+
+    /// Merge another accumulator (with the same shape and quantizer).
+    pub fn merge(&mut self, other: &Self) {
+        self.inner.merge(&other.inner);
+    }
+
+    /// Mutual information (in bits) between each target and each sample,
+    /// shape `(targets, samples)`.
+    pub fn freeze_mi(&self, correction: BiasCorrection) -> Array2<f64> {
+        self.inner.map_joint(|joint| {
+            let (n_classes, n_bins) = marginals(joint);
+            mutual_information(
+                joint.iter().map(|&n| n as u64),
+                &n_classes,
+                &n_bins,
+                correction,
+            )
+        })
+    }
+}
+
+/// Sum of `n ln n` over non-zero counts `n`, and the number of such counts.
+fn n_log_n(counts: impl Iterator<Item = u64>) -> (f64, usize) {
+    counts.filter(|&n| n != 0).fold((0.0, 0), |(acc, k), n| {
+        let n = n as f64;
+        (acc + n * n.ln(), k + 1)
+    })
+}
+
+/// Mutual information (in bits) from a joint histogram and its marginals.
+fn mutual_information(
+    joint: impl Iterator<Item = u64>,
+    marginal_a: &[u64],
+    marginal_b: &[u64],
+    correction: BiasCorrection,
+) -> f64 {
+    let (s_ab, k_ab) = n_log_n(joint);
+    let (s_a, k_a) = n_log_n(marginal_a.iter().copied());
+    let (s_b, k_b) = n_log_n(marginal_b.iter().copied());
+    let n: u64 = marginal_a.iter().sum();
+    if n == 0 {
+        return 0.0;
+    }
+    let n = n as f64;
+    // I = H(A) + H(B) - H(A, B), with H(X) = ln N - (1 / N) sum n_x ln n_x.
+    let mut mi = (s_ab - s_a - s_b) / n + n.ln();
+    if correction == BiasCorrection::MillerMadow {
+        mi += (k_a as f64 + k_b as f64 - k_ab as f64 - 1.0) / (2.0 * n);
+    }
+    mi / core::f64::consts::LN_2
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{BiasCorrection, MiaHistogram};
+    use crate::histogram::Quantizer;
+    use ndarray::{s, Array2};
+    use rand::distributions::Uniform;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    pub(crate) fn random_data(n: usize) -> (Array2<i8>, Array2<u16>) {
+        let mut rng = StdRng::seed_from_u64(0x3141);
+        let labels = Array2::from_shape_fn((n, 2), |_| rng.sample(Uniform::new(0u16, 4)));
+        let data = Array2::from_shape_fn((n, 5), |(i, j)| {
+            let noise: i8 = rng.sample(Uniform::new(-2, 3));
+            match j {
+                // Deterministic function of the first label.
+                0 => labels[(i, 0)] as i8 * 8,
+                // Non-linear (non-monotonic) leakage of the second label.
+                2 => [0, 8, 8, 0][labels[(i, 1)] as usize] + noise,
+                _ => noise,
+            }
+        });
+        (data, labels)
+    }
+
+    #[test]
+    fn test_mutual_information() {
+        let (data, labels) = random_data(20000);
+        let mut acc = MiaHistogram::new(2, 5, 4, Quantizer::new(-4.0, 28.0, 8));
+        acc.process_block(data.view(), labels.view());
+        assert_eq!(acc.histogram().samples_per_class().sum(), 40000);
+
+        let mi = acc.freeze_mi(BiasCorrection::None);
+        // Sample 0 reveals the first label (2 bits).
+        assert!((mi[(0, 0)] - 2.0).abs() < 1e-2, "{mi}");
+        // Sample 2 reveals one bit of the second label.
+        assert!((mi[(1, 2)] - 1.0).abs() < 1e-2, "{mi}");
+        for (t, s) in [(0, 1), (0, 2), (1, 0), (1, 4)] {
+            assert!(mi[(t, s)] < 1e-2, "{mi}");
+        }
+        let corrected = acc.freeze_mi(BiasCorrection::MillerMadow);
+        // The plug-in bias is removed for independent variables.
+        for (t, s) in [(0, 1), (0, 4), (1, 3), (1, 4)] {
+            assert!(corrected[(t, s)] < mi[(t, s)]);
+            assert!(corrected[(t, s)].abs() < 5e-4, "{corrected}");
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let (data, labels) = random_data(3000);
+        let q = Quantizer::new(-4.0, 28.0, 16);
+        let mut acc = MiaHistogram::new(2, 5, 4, q);
+        acc.process_block(data.view(), labels.view());
+
+        let mut merged = MiaHistogram::new(2, 5, 4, q);
+        merged.process_block(data.slice(s![..1000, ..]), labels.slice(s![..1000, ..]));
+        let mut other = MiaHistogram::new(2, 5, 4, q);
+        other.process_block_parallel(data.slice(s![1000.., ..]), labels.slice(s![1000.., ..]), 2);
+        merged.merge(&other);
+        assert_eq!(
+            merged.freeze_mi(BiasCorrection::MillerMadow),
+            acc.freeze_mi(BiasCorrection::MillerMadow)
+        );
+    }
+}
@@ -27,16 +27,29 @@
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
 
-use crate::{DspFloat, IntoFloat};
+use crate::traits::exact_m2;
+use crate::{Accumulate, DspFloat};
 use ndarray::{
     s, Array1, Array2, Array3, ArrayView1, ArrayView2, ArrayView3, ArrayViewMut2, ArrayViewMut3,
     Axis, Zip,
 };
 use num_traits::AsPrimitive;
 use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use std::ops::Range;
 
 pub type Label = u16;
 
+/// Exact accumulators for integer samples (see [`Accumulate`]).
+///
+/// They are allocated on the first block of integer data, and merged with
+/// the floating point state in the outputs when the accumulator is frozen.
+#[derive(Clone)]
+struct ExactMoments {
+    sum: Array3<i64>,
+    sum_sq: Array3<i64>,
+    samples: Array2<u32>,
+}
+
 #[derive(Clone)]
 pub struct CondMeanVar<I> {
     // samples_per_class[target][class][sample_idx] -> mean accumulator value at instant sample_idx
@@ -45,6 +58,7 @@ pub struct CondMeanVar<I> {
     var_per_class: Array3<I>,
     // samples_per_class[target][class] -> number of items in the class.
     samples_per_class: Array2<u32>,
+    exact: Option<ExactMoments>,
 }
 
 impl<I> CondMeanVar<I>
@@ -60,9 +74,56 @@ where
             mean_per_class: Array3::zeros([targets, classes, samples]),
             var_per_class: Array3::zeros([targets, classes, samples]),
             samples_per_class: Array2::zeros([targets, classes]),
+            exact: None,
         }
     }
 
+    /// Class means and sums of squared deviations of samples `range`, with
+    /// the exact accumulators merged in, written to `mean` and `m2`.
+    ///
+    /// The accumulator itself is left untouched, so that integer samples
+    /// keep being accumulated exactly.
+    fn merged_into(
+        &self,
+        range: Range<usize>,
+        mut mean: ArrayViewMut3<I>,
+        mut m2: ArrayViewMut3<I>,
+    ) {
+        mean.assign(&self.mean_per_class.slice(s![.., .., range.clone()]));
+        m2.assign(&self.var_per_class.slice(s![.., .., range.clone()]));
+        let Some(exact) = &self.exact else {
+            return;
+        };
+        Zip::indexed(&exact.samples).for_each(|(t, c), &n_2| {
+            if n_2 == 0 {
+                return;
+            }
+            let n_1 = self.samples_per_class[(t, c)];
+            let n = n_1 + n_2;
+            let w_2 = n_2.as_() / n.as_();
+            let w_12 = n_1.as_() * n_2.as_() / n.as_();
+            Zip::from(mean.slice_mut(s![t, c, ..]))
+                .and(m2.slice_mut(s![t, c, ..]))
+                .and(exact.sum.slice(s![t, c, range.clone()]))
+                .and(exact.sum_sq.slice(s![t, c, range.clone()]))
+                .for_each(|m, v, &sum, &sum_sq| {
+                    let m_2 = I::from_i64(sum).unwrap() / n_2.as_();
+                    let v_2 = I::from_f64(exact_m2(sum, sum_sq, n_2 as u64)).unwrap();
+                    let delta = m_2 - *m;
+                    *m += delta * w_2;
+                    *v += v_2 + delta * delta * w_12;
+                });
+        });
+    }
+
+    /// Class means and sums of squared deviations, see [`Self::merged_into`].
+    fn merged(&self) -> (Array3<I>, Array3<I>) {
+        let (mut mean, mut m2) = self.create_output_arrays();
+        let samples = self.mean_per_class.shape()[2];
+        self.merged_into(0..samples, mean.view_mut(), m2.view_mut());
+        (mean, m2)
+    }
+
     pub fn load_state(
         &mut self,
         mean_per_class: ArrayView3<I>,
@@ -72,14 +133,12 @@ where
         self.mean_per_class.assign(&mean_per_class);
         self.var_per_class.assign(&var_per_class);
         self.samples_per_class.assign(&samples_per_class);
+        self.exact = None;
     }
 
     pub fn dump_state(&self) -> (Array3<I>, Array3<I>, Array2<u32>) {
-        (
-            self.mean_per_class.clone(),
-            self.var_per_class.clone(),
-            self.samples_per_class.clone(),
-        )
+        let (mean, m2) = self.merged();
+        (mean, m2, self.samples_per_class())
     }
 
     /// Create arrays with correct shapes to be passed to [`CondMeanVar::process_into`].
@@ -93,12 +152,45 @@ where
         self.mean_per_class.shape()[1]
     }
 
+    /// Accumulate integer samples exactly, see [`Accumulate`].
+    fn process_exact<S>(&mut self, data: ArrayView1<S>, labels: ArrayView1<Label>)
+    where
+        S: AsPrimitive<i64>,
+    {
+        let shape = self.mean_per_class.raw_dim();
+        let exact = self.exact.get_or_insert_with(|| ExactMoments {
+            sum: Array3::zeros(shape),
+            sum_sq: Array3::zeros(shape),
+            samples: Array2::zeros(self.samples_per_class.raw_dim()),
+        });
+        Zip::from(labels)
+            .and(exact.samples.axis_iter_mut(Axis(0)))
+            .and(exact.sum.axis_iter_mut(Axis(0)))
+            .and(exact.sum_sq.axis_iter_mut(Axis(0)))
+            .for_each(|&label, mut sx, mut s1, mut s2| {
+                let label = label as usize;
+                sx[label] += 1;
+                Zip::from(s1.slice_mut(s![label, ..]))
+                    .and(s2.slice_mut(s![label, ..]))
+                    .and(data)
+                    .for_each(|s1, s2, &x| {
+                        let x: i64 = x.as_();
+                        *s1 += x;
+                        *s2 += x * x;
+                    });
+            });
+    }
+
     pub fn process<S>(&mut self, data: ArrayView1<S>, labels: ArrayView1<Label>)
     where
-        S: IntoFloat<I> + Copy,
+        S: Accumulate<I> + AsPrimitive<i64>,
     {
         debug_assert_eq!(data.len(), self.mean_per_class.shape()[2]);
         debug_assert_eq!(labels.shape()[0], self.samples_per_class.shape()[0]);
+        if S::EXACT {
+            self.process_exact(data, labels);
+            return;
+        }
 
         Zip::from(labels)
             .and(self.samples_per_class.axis_iter_mut(Axis(0)))
@@ -124,7 +216,7 @@ where
 
     pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView2<Label>)
     where
-        S: IntoFloat<I> + Copy,
+        S: Accumulate<I> + AsPrimitive<i64>,
     {
         Zip::from(data.outer_iter())
             .and(labels.outer_iter())
@@ -147,35 +239,36 @@ where
             });
     }
 
-    fn freeze_var_into(&self, mut var: ArrayViewMut3<I>) {
+    fn freeze_var_into(mut var: ArrayViewMut3<I>, samples_per_class: ArrayView2<u32>) {
         Zip::from(var.axis_iter_mut(Axis(0)))
-            .and(self.samples_per_class.axis_iter(Axis(0)))
+            .and(samples_per_class.axis_iter(Axis(0)))
             .for_each(|var, samples| Self::freeze_single_class(var, samples))
     }
 
-    pub fn freeze_into(&self, mut mean: ArrayViewMut3<I>, mut var: ArrayViewMut3<I>) {
-        mean.assign(&self.mean_per_class.view());
-        var.assign(&self.var_per_class.view());
-        self.freeze_var_into(var);
+    pub fn freeze_into(&self, mean: ArrayViewMut3<I>, mut var: ArrayViewMut3<I>) {
+        let samples = self.mean_per_class.shape()[2];
+        self.merged_into(0..samples, mean, var.view_mut());
+        Self::freeze_var_into(var, self.samples_per_class().view());
     }
 
     pub fn freeze(&self) -> (Array3<I>, Array3<I>) {
-        let mean = self.mean_per_class.clone();
-        let mut var = self.var_per_class.clone();
-        self.freeze_var_into(var.view_mut());
+        let (mean, mut var) = self.merged();
+        Self::freeze_var_into(var.view_mut(), self.samples_per_class().view());
         (mean, var)
     }
 
     /// Compute the global mean and variance of the accumulator.
     pub fn freeze_global_mean_var(&self) -> (Array1<I>, Array1<I>, u32) {
+        let (mean_per_class, var_per_class) = self.merged();
+        let samples_per_class = self.samples_per_class();
         // NOTE: we implement the merging algorithm here. Currently we do not have 2D accumulators.
-        let mut m_acc = self.mean_per_class.slice(s![0, 0, ..]).to_owned();
-        let mut v_acc = self.var_per_class.slice(s![0, 0, ..]).to_owned();
-        let mut samples = self.samples_per_class[(0, 0)];
+        let mut m_acc = mean_per_class.slice(s![0, 0, ..]).to_owned();
+        let mut v_acc = var_per_class.slice(s![0, 0, ..]).to_owned();
+        let mut samples = samples_per_class[(0, 0)];
 
-        Zip::from(self.var_per_class.slice(s![0, 1.., ..]).axis_iter(Axis(0)))
-            .and(self.mean_per_class.slice(s![0, 1.., ..]).axis_iter(Axis(0)))
-            .and(self.samples_per_class.slice(s![0, 1..]))
+        Zip::from(var_per_class.slice(s![0, 1.., ..]).axis_iter(Axis(0)))
+            .and(mean_per_class.slice(s![0, 1.., ..]).axis_iter(Axis(0)))
+            .and(samples_per_class.slice(s![0, 1..]))
             .for_each(|v2_row, m2_row, &s| {
                 let n_1 = samples;
                 let n_2 = s;
@@ -203,14 +296,17 @@ where
     }
 
     pub fn samples_per_class(&self) -> Array2<u32> {
-        self.samples_per_class.clone()
+        match &self.exact {
+            None => self.samples_per_class.clone(),
+            Some(exact) => &self.samples_per_class + &exact.samples,
+        }
     }
 
     /// Snapshot of the signal to noise ratio.
     pub fn freeze_snr(&self) -> Array2<I> {
-        let num = self.mean_per_class.var_axis(Axis(1), I::one());
-        let mut var = self.var_per_class.clone();
-        self.freeze_var_into(var.view_mut());
+        let (mean, mut var) = self.merged();
+        let num = mean.var_axis(Axis(1), I::one());
+        Self::freeze_var_into(var.view_mut(), self.samples_per_class().view());
         let denum = var.mean_axis(Axis(1)).unwrap();
         num / denum
     }
@@ -240,20 +336,25 @@ where
         let classes = sh[1];
         let targets = sh[0];
 
-        let chunk_count = (samples + chunk_size - 1) / chunk_size;
+        let chunk_count = samples.div_ceil(chunk_size);
         let mut workers = Vec::with_capacity(chunk_count);
         let mut indices = Vec::with_capacity(chunk_count);
         for start in (0..samples).step_by(chunk_size) {
             let end = start + chunk_size;
             let end = end.min(samples);
             debug_assert_ne!(start, end);
+            let mut mean_per_class = Array3::zeros([targets, classes, end - start]);
+            let mut var_per_class = mean_per_class.clone();
+            accum.merged_into(
+                start..end,
+                mean_per_class.view_mut(),
+                var_per_class.view_mut(),
+            );
             workers.push(CondMeanVar {
-                mean_per_class: accum
-                    .mean_per_class
-                    .slice(s![.., .., start..end])
-                    .to_owned(),
-                var_per_class: accum.var_per_class.slice(s![.., .., start..end]).to_owned(),
-                samples_per_class: accum.samples_per_class.clone(),
+                mean_per_class,
+                var_per_class,
+                samples_per_class: accum.samples_per_class(),
+                exact: None,
             });
             indices.push((start as u32, end as u32))
         }
@@ -274,21 +375,24 @@ where
             .zip(self.chunks.iter())
             .for_each(|(x, &(start, end))| {
                 let (start, end) = (start as usize, end as usize);
-                m.slice_mut(s![.., .., start..end])
-                    .assign(&x.mean_per_class);
-                v.slice_mut(s![.., .., start..end]).assign(&x.var_per_class);
+                x.merged_into(
+                    0..end - start,
+                    m.slice_mut(s![.., .., start..end]),
+                    v.slice_mut(s![.., .., start..end]),
+                );
             });
 
         CondMeanVar {
             mean_per_class: m,
             var_per_class: v,
-            samples_per_class: self.workers[0].samples_per_class.clone(),
+            samples_per_class: self.workers[0].samples_per_class(),
+            exact: None,
         }
     }
 
     pub fn process_block<S>(&mut self, data: ArrayView2<S>, labels: ArrayView2<Label>)
     where
-        S: IntoFloat<I> + Copy + Sync + Send,
+        S: Accumulate<I> + AsPrimitive<i64> + Sync + Send,
     {
         debug_assert_eq!(data.shape()[1], self.samples);
         self.workers
@@ -307,7 +411,7 @@ where
 #[cfg(test)]
 mod test {
     use super::{CondMeanVar, CondMeanVarP};
-    use ndarray::{Array2, Axis};
+    use ndarray::{s, Array2, Axis};
     use rand::distributions::Uniform;
     use rand::rngs::StdRng;
     use rand::{Rng, SeedableRng};
@@ -360,4 +464,51 @@ mod test {
         assert!(mean.abs_diff_eq(&mean_expected, 1e-3));
         assert!(var.abs_diff_eq(&var_expected, 1e-3));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cond_mean_exact() {
+        let mut rng = StdRng::seed_from_u64(0x1234);
+        const N: usize = 5000;
+        let t0 = Array2::from_shape_fn((N, 8), |_| {
+            rng.sample(Uniform::new_inclusive(-128i16, 127)) as i8
+        });
+        let t0_f = t0.mapv(|x| x as f64);
+        let labels = Array2::from_shape_fn((N, 3), |_| rng.sample(Uniform::new(0u16, 9)));
+
+        // Reference: floating point accumulation only.
+        let mut acc_f = CondMeanVar::<f64>::new(3, 8, 9);
+        acc_f.process_block(t0_f.view(), labels.view());
+
+        // Mix integer and float blocks.
+        let mut acc = CondMeanVar::<f64>::new(3, 8, 9);
+        acc.process_block(t0.slice(s![..2000, ..]), labels.slice(s![..2000, ..]));
+        acc.process_block(
+            t0_f.slice(s![2000..3000, ..]),
+            labels.slice(s![2000..3000, ..]),
+        );
+        acc.process_block(t0.slice(s![3000.., ..]), labels.slice(s![3000.., ..]));
+        assert_eq!(acc.samples_per_class(), acc_f.samples_per_class());
+
+        let (mean_f, var_f) = acc_f.freeze();
+        let (mean, var) = acc.freeze();
+        assert!(mean.abs_diff_eq(&mean_f, 1e-9));
+        assert!(var.abs_diff_eq(&var_f, 1e-7));
+
+        let mut par_acc = CondMeanVarP::<f64>::new(3, 3, 8, 9);
+        par_acc.process_block(t0.view(), labels.view());
+        let (mean_p, var_p) = par_acc.merge().freeze();
+        assert!(mean_p.abs_diff_eq(&mean_f, 1e-9));
+        assert!(var_p.abs_diff_eq(&var_f, 1e-7));
+
+        // Other snapshots include the pending exact accumulators too.
+        let (mean_s, var_s) = CondMeanVarP::split(&acc, 3).merge().freeze();
+        assert!(mean_s.abs_diff_eq(&mean_f, 1e-9));
+        assert!(var_s.abs_diff_eq(&var_f, 1e-7));
+        let (m, v, n) = acc.freeze_global_mean_var();
+        let (m_f, v_f, n_f) = acc_f.freeze_global_mean_var();
+        assert_eq!(n, n_f);
+        assert!(m.abs_diff_eq(&m_f, 1e-9) && v.abs_diff_eq(&v_f, 1e-7));
+        assert!(acc.freeze_snr().abs_diff_eq(&acc_f.freeze_snr(), 1e-7));
+        assert_eq!(acc.dump_state().2, acc_f.samples_per_class());
+    }
+}
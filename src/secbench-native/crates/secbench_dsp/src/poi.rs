@@ -0,0 +1,203 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Selection of points of interest from leakage metrics.
+//!
+//! The metric of a target is a curve over the samples of the traces (e.g.,
+//! SNR, NICV or a t-test from [`CondMeanVar`](crate::CondMeanVar)). Points
+//! are selected by decreasing metric value, with an optional threshold,
+//! number of points and minimum distance between two points (non-maximum
+//! suppression). Windows around the selected points can then be merged into
+//! contiguous ranges.
+
+use crate::DspFloat;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Parameters of [`select_pois`].
+#[derive(Clone, Debug)]
+pub struct PoiConfig<I> {
+    /// Maximum number of points selected (all points if `None`).
+    pub top_k: Option<usize>,
+    /// Only points with a metric greater or equal to this value are kept.
+    pub threshold: Option<I>,
+    /// Minimum distance between two selected points. Points closer than this
+    /// to a point with a higher metric are suppressed (0 or 1 disables the
+    /// suppression).
+    pub min_distance: usize,
+    /// Rank points with the absolute value of the metric (e.g., t-tests).
+    pub absolute: bool,
+}
+
+impl<I> Default for PoiConfig<I> {
+    fn default() -> Self {
+        PoiConfig {
+            top_k: None,
+            threshold: None,
+            min_distance: 0,
+            absolute: false,
+        }
+    }
+}
+
+/// Points of interest of a metric curve, in increasing order.
+///
+/// NaN values are never selected.
+pub fn select_pois<I: DspFloat>(metric: &[I], config: &PoiConfig<I>) -> Vec<usize> {
+    let score = |i: usize| {
+        if config.absolute {
+            metric[i].abs()
+        } else {
+            metric[i]
+        }
+    };
+    let mut candidates: Vec<usize> = (0..metric.len())
+        .filter(|&i| {
+            let x = score(i);
+            !x.is_nan() && config.threshold.is_none_or(|t| x >= t)
+        })
+        .collect();
+    // Stable sort: ties are broken by the lowest index.
+    candidates.sort_by(|&a, &b| score(b).partial_cmp(&score(a)).unwrap());
+
+    let top_k = config.top_k.unwrap_or(usize::MAX);
+    let mut selected = BTreeSet::new();
+    for p in candidates {
+        if selected.len() >= top_k {
+            break;
+        }
+        if config.min_distance > 1 {
+            let lo = p.saturating_sub(config.min_distance - 1);
+            let hi = p.saturating_add(config.min_distance - 1);
+            if selected.range(lo..=hi).next().is_some() {
+                continue;
+            }
+        }
+        selected.insert(p);
+    }
+    selected.into_iter().collect()
+}
+
+/// Merge the windows `[p - half_width, p + half_width]` around sorted points
+/// `pois` into disjoint ranges, clipped to `[0, len)`.
+///
+/// Overlapping or adjacent windows are merged.
+pub fn merge_windows(pois: &[usize], half_width: usize, len: usize) -> Vec<Range<usize>> {
+    let mut windows: Vec<Range<usize>> = Vec::new();
+    for &p in pois.iter().filter(|&&p| p < len) {
+        let start = p.saturating_sub(half_width);
+        let end = p.saturating_add(half_width).saturating_add(1).min(len);
+        match windows.last_mut() {
+            Some(last) if start <= last.end => last.end = last.end.max(end),
+            _ => windows.push(start..end),
+        }
+    }
+    windows
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merge_windows, select_pois, PoiConfig};
+
+    const METRIC: [f64; 12] = [
+        0.1,
+        0.5,
+        0.9,
+        0.8,
+        0.2,
+        f64::NAN,
+        0.3,
+        0.7,
+        0.1,
+        0.0,
+        -0.95,
+        0.6,
+    ];
+
+    #[test]
+    fn test_top_k_threshold() {
+        let all = select_pois(&METRIC, &PoiConfig::default());
+        assert_eq!(all, [0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11]);
+
+        let config = PoiConfig {
+            top_k: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [2, 3, 7]);
+
+        let config = PoiConfig {
+            threshold: Some(0.55),
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [2, 3, 7, 11]);
+
+        let config = PoiConfig {
+            top_k: Some(2),
+            threshold: Some(0.55),
+            absolute: true,
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [2, 10]);
+    }
+
+    #[test]
+    fn test_non_maximum_suppression() {
+        let config = PoiConfig {
+            min_distance: 2,
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [0, 2, 4, 7, 9, 11]);
+
+        let config = PoiConfig {
+            top_k: Some(3),
+            min_distance: 4,
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [2, 7, 11]);
+
+        // -0.95 is ranked first with absolute values.
+        let config = PoiConfig {
+            min_distance: 3,
+            absolute: true,
+            ..Default::default()
+        };
+        assert_eq!(select_pois(&METRIC, &config), [2, 7, 10]);
+    }
+
+    #[test]
+    fn test_merge_windows() {
+        assert_eq!(merge_windows(&[], 2, 10), []);
+        assert_eq!(merge_windows(&[0, 5], 0, 10), [0..1, 5..6]);
+        assert_eq!(merge_windows(&[1, 4, 9], 1, 10), [0..6, 8..10]);
+        // Points out of the trace are ignored.
+        let merged = merge_windows(&[2, 3, 12], 5, 10);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0], 0..9);
+    }
+}
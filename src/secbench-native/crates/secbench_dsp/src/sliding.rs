@@ -28,9 +28,9 @@
 // knowledge of the CeCILL license and that you accept its terms.
 
 use crate::fft::FilterState;
-use crate::traits::{DspFloat, Transform1D};
-use itertools::{izip, Itertools};
-use num_traits::AsPrimitive;
+use crate::traits::{Accumulate, DspFloat, Transform1D};
+use itertools::Itertools;
+use num_traits::{AsPrimitive, Zero};
 use std::iter::Sum;
 use std::ops::AddAssign;
 use std::{hint::black_box, marker::PhantomData};
@@ -39,6 +39,8 @@ use std::{hint::black_box, marker::PhantomData};
 pub struct MovingSum<Dst, Src> {
     window_size: usize,
     scale: Dst,
+    // cumulative sums, the windowed differences are taken with SIMD kernels
+    prefix: Vec<Dst>,
     _src: PhantomData<Src>,
 }
 
@@ -47,27 +49,59 @@ impl<Dst, Src> MovingSum<Dst, Src> {
         MovingSum {
             window_size,
             scale,
+            prefix: Vec::new(),
             _src: Default::default(),
         }
     }
 }
 
+impl<Dst, Src> MovingSum<Dst, Src>
+where
+    Dst: DspFloat + 'static,
+    Src: Accumulate<Dst>,
+{
+    /// Windowed summation with a running sum, only valid for exact accumulators.
+    fn apply_exact(&self, output: &mut [Dst], input: &[Src]) {
+        let window_size = self.window_size;
+        let mut acc = Src::Acc::zero();
+        for &x in &input[..window_size] {
+            acc += x.widen();
+        }
+        for j in 0..output.len() {
+            let mut tmp = Src::acc_into_float(acc);
+            if self.scale != Dst::one() {
+                tmp *= self.scale;
+            }
+            output[j] = tmp;
+            acc -= input[j].widen();
+            if j + window_size < input.len() {
+                acc += input[j + window_size].widen();
+            }
+        }
+    }
+}
+
 impl<Dst, Src> Transform1D<Dst, Src> for MovingSum<Dst, Src>
 where
     Dst: DspFloat + 'static,
-    Src: AsPrimitive<Dst> + Copy,
+    Src: AsPrimitive<Dst> + Accumulate<Dst>,
 {
     fn apply_inplace(&mut self, output: &mut [Dst], input: &[Src]) {
         let window_size = self.window_size;
         assert!(window_size > 0);
         assert!(window_size <= output.len());
         assert_eq!(output.len(), input.len());
+        if Src::EXACT {
+            self.apply_exact(output, input);
+            return;
+        }
         // Compute cumulative sum using Kahan Babushka NeumaierSum summation, see
         // https://en.wikipedia.org/wiki/Kahan_summation_algorithm
         let mut sum = Dst::zero();
         let mut error = Dst::zero();
-        for j in 0..output.len() {
-            let x = input[j].as_();
+        self.prefix.clear();
+        for &x in input {
+            let x = x.as_();
             let t = black_box(sum + x);
             if sum.abs() >= x.abs() {
                 error += (sum - t) + x;
@@ -75,31 +109,27 @@ where
                 error += (x - t) + sum;
             }
             sum = t;
-            output[j] = t + error;
+            self.prefix.push(t + error);
 
             // Alternative version, much faster.
             // sum += src[j].as_();
             // dst[j] = sum;
         }
 
-        // Compute windowed summation.
-        let mut s_prev = Dst::zero();
+        // Compute windowed summation, output[j] = prefix[j + w - 1] - prefix[j - 1].
+        // Windows are truncated at the end of the input.
+        let prefix = &self.prefix;
         let j_last = output.len() - 1;
         let j_end = output.len() - window_size;
-        for j in 0..output.len() {
-            let s_curr = output[j];
-            let s_end = if j <= j_end {
-                output[j + window_size - 1]
-            } else {
-                output[j_last]
-            };
-
-            let mut tmp = s_end - s_prev;
-            if self.scale != Dst::one() {
-                tmp *= self.scale;
-            }
-            output[j] = tmp;
-            s_prev = s_curr;
+        output[0] = prefix[window_size - 1] * self.scale;
+        (Dst::kernels().sub_scaled)(
+            &mut output[1..=j_end],
+            &prefix[window_size..],
+            &prefix[..j_end],
+            self.scale,
+        );
+        for (dst, &s_prev) in output[j_end + 1..].iter_mut().zip(&prefix[j_end..j_last]) {
+            *dst = (prefix[j_last] - s_prev) * self.scale;
         }
     }
 }
@@ -111,6 +141,7 @@ pub struct MatchEuclidean<Dst, Src> {
     tmp_x: Vec<Dst>,
     tmp_xx: Vec<Dst>,
     tmp_xp: Vec<Dst>,
+    moving_sum: MovingSum<Dst, Dst>,
     filter: FilterState<Dst, Src>,
 }
 
@@ -120,7 +151,7 @@ where
     Src: AsPrimitive<Dst> + Copy,
 {
     pub fn new(pattern: &[Dst], seq_length: usize) -> Self {
-        debug_assert!(pattern.len() > 0);
+        debug_assert!(!pattern.is_empty());
         debug_assert!(pattern.len() <= seq_length);
         let fft_len = pattern.len() + seq_length - 1;
         let mut filter: FilterState<Dst, Src> = FilterState::new(fft_len);
@@ -133,6 +164,7 @@ where
             tmp_x: vec![Dst::zero(); seq_length],
             tmp_xx: vec![Dst::zero(); seq_length],
             tmp_xp: vec![Dst::zero(); fft_len],
+            moving_sum: MovingSum::new(pattern.len(), Dst::one()),
             filter,
         }
     }
@@ -140,26 +172,26 @@ where
 
 impl<Dst, Src> Transform1D<Dst, Src> for MatchEuclidean<Dst, Src>
 where
-    Dst: DspFloat + 'static + AsPrimitive<Dst> + From<u8>,
+    Dst: DspFloat + 'static + AsPrimitive<Dst> + Accumulate<Dst> + From<u8>,
     Src: AsPrimitive<Dst> + Copy,
 {
     fn apply_inplace(&mut self, output: &mut [Dst], input: &[Src]) {
         debug_assert!(output.len() >= self.output_len(input.len()));
         debug_assert!(input.len() <= self.filter.fft_len());
 
-        let mut ms: MovingSum<Dst, Dst> = MovingSum::new(self.p_len, Dst::one());
         for (dst, &x) in self.tmp_x.iter_mut().zip(input.iter()) {
             *dst = x.as_() * x.as_();
         }
-        ms.apply_inplace(&mut self.tmp_xx, self.tmp_x.as_slice());
+        self.moving_sum
+            .apply_inplace(&mut self.tmp_xx, self.tmp_x.as_slice());
 
         self.filter.filter_single_pass(&mut self.tmp_xp, input);
-        output
-            .iter_mut()
-            .zip(self.tmp_xx.iter().zip(&self.tmp_xp[self.p_len - 1..]))
-            .for_each(|(dst, (&xx, &xp))| {
-                *dst = xx - <Dst as From<u8>>::from(2) * xp + self.p_square;
-            });
+        (Dst::kernels().euclidean_combine)(
+            output,
+            &self.tmp_xx,
+            &self.tmp_xp[self.p_len - 1..],
+            self.p_square,
+        );
     }
 
     fn output_len(&self, input_samples: usize) -> usize {
@@ -175,6 +207,7 @@ pub struct MatchCorrelation<Dst, Src> {
     tmp_x_ms: Vec<Dst>,
     tmp_x_std: Vec<Dst>,
     tmp_xp: Vec<Dst>,
+    moving_sum: MovingSum<Dst, Src>,
     filter: FilterState<Dst, Src>,
     sliding_std: SlidingExecutor<Dst, Src>,
 }
@@ -182,12 +215,12 @@ pub struct MatchCorrelation<Dst, Src> {
 impl<Dst, Src> MatchCorrelation<Dst, Src>
 where
     Dst: DspFloat + Sum + 'static,
-    Src: AsPrimitive<Dst> + AddAssign + Copy,
+    Src: AsPrimitive<Dst> + Accumulate<Dst> + AddAssign + Copy,
     usize: AsPrimitive<Dst>,
 {
     pub fn new(pattern: &[Dst], seq_length: usize) -> Self {
         assert!(pattern.len() <= seq_length);
-        assert!(pattern.len() > 0);
+        assert!(!pattern.is_empty());
         let fft_len = pattern.len() + seq_length - 1;
         let p_len: Dst = pattern.len().as_();
         let p_sum: Dst = pattern.iter().cloned().sum();
@@ -206,6 +239,7 @@ where
             tmp_x_ms: vec![Dst::zero(); seq_length],
             tmp_x_std: vec![Dst::zero(); seq_length],
             tmp_xp: vec![Dst::zero(); fft_len],
+            moving_sum: MovingSum::new(pattern.len(), Dst::one()),
             filter,
             sliding_std: SlidingExecutor::new(SlidingType::Std, pattern.len(), Some(Dst::one())),
         }
@@ -215,7 +249,7 @@ where
 impl<Dst, Src> Transform1D<Dst, Src> for MatchCorrelation<Dst, Src>
 where
     Dst: DspFloat + 'static + AsPrimitive<Dst> + From<u8>,
-    Src: AsPrimitive<Dst> + AddAssign + Copy,
+    Src: AsPrimitive<Dst> + Accumulate<Dst> + AddAssign + Copy,
 {
     fn apply_inplace(&mut self, output: &mut [Dst], input: &[Src]) {
         debug_assert!(input.len() >= self.p_len);
@@ -223,22 +257,21 @@ where
         debug_assert!(input.len() <= self.tmp_x_ms.len());
 
         self.filter.filter_single_pass(&mut self.tmp_xp, input);
-        let mut ms: MovingSum<Dst, Src> = MovingSum::new(self.p_len, Dst::one());
-        ms.apply_inplace(&mut self.tmp_x_ms[..input.len()], input);
+        self.moving_sum
+            .apply_inplace(&mut self.tmp_x_ms[..input.len()], input);
 
         self.sliding_std
             .apply_inplace(&mut self.tmp_x_std[..input.len()], input);
 
         let output_len = input.len() - (self.p_len - 1);
-        izip!(
+        (Dst::kernels().correlation_combine)(
             &mut output[..output_len],
             &self.tmp_xp[self.p_len - 1..],
-            self.tmp_x_ms.iter(),
+            &self.tmp_x_ms,
             &self.tmp_x_std[self.p_len - 1..],
-        )
-        .for_each(|(dst, &xp, &x_ms, &x_std)| {
-            *dst = (xp - x_ms * self.p_mean) / (x_std * self.p_std);
-        });
+            self.p_mean,
+            self.p_std,
+        );
     }
 
     fn output_len(&self, input_samples: usize) -> usize {
@@ -262,6 +295,8 @@ pub struct SlidingExecutor<Dst, Src> {
     padding_value: Option<Dst>,
 
     win_sized_cache1: Vec<Dst>,
+    // input converted to Dst, used by vectorized kernels
+    input_cache: Vec<Dst>,
 
     // used in the case of skew or kurt calculation
     coef: Dst,
@@ -273,7 +308,7 @@ pub struct SlidingExecutor<Dst, Src> {
 impl<Dst, Src> SlidingExecutor<Dst, Src>
 where
     Dst: DspFloat + 'static,
-    Src: AsPrimitive<Dst> + Copy + AddAssign,
+    Src: AsPrimitive<Dst> + Accumulate<Dst> + Copy + AddAssign,
 {
     pub fn new(sliding_type: SlidingType, window_size: usize, padding_value: Option<Dst>) -> Self {
         let (coef, subs) = match sliding_type {
@@ -314,13 +349,45 @@ where
             window_size,
             padding_value,
             win_sized_cache1: vec![Dst::zero(); window_size],
+            input_cache: Vec::new(),
             coef,
             subs,
             phantom: PhantomData,
         }
     }
 
+    /// Exact sliding sums for integer inputs.
+    ///
+    /// `f` maps the sum and the sum of squares of each window to the output value.
+    fn sliding_exact(
+        &self,
+        in_array: &[Src],
+        out_array: &mut [Dst],
+        f: impl Fn(Src::Acc, Src::Acc) -> Dst,
+    ) {
+        let w = self.window_size;
+        let (mut sum, mut sum_sq) = (Src::Acc::zero(), Src::Acc::zero());
+        for (i, &x) in in_array.iter().enumerate() {
+            let x = x.widen();
+            sum += x;
+            sum_sq += x * x;
+            if i >= w - 1 {
+                out_array[i] = f(sum, sum_sq);
+                let y = in_array[i + 1 - w].widen();
+                sum -= y;
+                sum_sq -= y * y;
+            }
+        }
+    }
+
     fn sliding_mean(&mut self, in_array: &[Src], out_array: &mut [Dst]) {
+        if Src::EXACT {
+            let o_win_size = Dst::from(self.window_size).unwrap();
+            self.sliding_exact(in_array, out_array, |s, _| {
+                Src::acc_into_float(s) / o_win_size
+            });
+            return;
+        }
         let mut error = Dst::zero();
         let mut acc = Dst::zero();
         let o_win_size = Dst::from(self.window_size).unwrap();
@@ -347,9 +414,17 @@ where
     }
 
     fn sliding_var(&mut self, in_array: &[Src], out_array: &mut [Dst]) {
+        if Src::EXACT {
+            let (w, denum) = (self.window_size, Dst::from(self.window_size - 1).unwrap());
+            self.sliding_exact(in_array, out_array, |s, ss| Src::acc_m2(s, ss, w) / denum);
+            return;
+        }
+        self.input_cache.clear();
+        self.input_cache.extend(in_array.iter().map(|x| x.as_()));
         let mut error = Dst::zero();
         let mut acc = Dst::zero();
         let o_win_size = Dst::from(self.window_size).unwrap();
+        let sum_sq_dev = Dst::kernels().sum_sq_dev;
 
         self.win_sized_cache1[self.window_size - 1] = Dst::zero();
         for i in 0..in_array.len() {
@@ -372,13 +447,8 @@ where
                 // mean of the window starting at idx i
                 let mean_i = (acc_x - self.win_sized_cache1[i % self.window_size]) / o_win_size;
 
-                let mut sum = Dst::zero();
-                for j in 0..self.window_size {
-                    // will perform for each of the element in the window
-                    // perform (X - mu)^p of each element of the window
-                    sum += (in_array[(j + i) - (self.window_size - 1)].as_() - mean_i)
-                        * (in_array[(j + i) - (self.window_size - 1)].as_() - mean_i);
-                }
+                let window = &self.input_cache[i + 1 - self.window_size..=i];
+                let sum = sum_sq_dev(window, mean_i);
 
                 out_array[i] = sum / (o_win_size - Dst::one());
             }
@@ -388,9 +458,19 @@ where
     }
 
     fn sliding_std(&mut self, in_array: &[Src], out_array: &mut [Dst]) {
+        if Src::EXACT {
+            let (w, denum) = (self.window_size, Dst::from(self.window_size - 1).unwrap());
+            self.sliding_exact(in_array, out_array, |s, ss| {
+                (Src::acc_m2(s, ss, w) / denum).sqrt()
+            });
+            return;
+        }
+        self.input_cache.clear();
+        self.input_cache.extend(in_array.iter().map(|x| x.as_()));
         let mut error = Dst::zero();
         let mut acc = Dst::zero();
         let o_win_size = Dst::from(self.window_size).unwrap();
+        let sum_sq_dev = Dst::kernels().sum_sq_dev;
 
         self.win_sized_cache1[self.window_size - 1] = Dst::zero();
         for i in 0..in_array.len() {
@@ -413,13 +493,8 @@ where
                 // mean of the window starting at idx i
                 let mean_i = (acc_x - self.win_sized_cache1[i % self.window_size]) / o_win_size;
 
-                let mut sum = Dst::zero();
-                for j in 0..self.window_size {
-                    // will perform for each of the element in the window
-                    // perform (X - mu)^p of each element of the window
-                    sum += (in_array[(j + i) - (self.window_size - 1)].as_() - mean_i)
-                        * (in_array[(j + i) - (self.window_size - 1)].as_() - mean_i);
-                }
+                let window = &self.input_cache[i + 1 - self.window_size..=i];
+                let sum = sum_sq_dev(window, mean_i);
 
                 out_array[i] = (sum / (o_win_size - Dst::one())).sqrt();
             }
@@ -539,7 +614,7 @@ where
 impl<Dst, Src> Transform1D<Dst, Src> for SlidingExecutor<Dst, Src>
 where
     Dst: DspFloat + 'static,
-    Src: AsPrimitive<Dst> + Copy + AddAssign,
+    Src: AsPrimitive<Dst> + Accumulate<Dst> + Copy + AddAssign,
 {
     /// Returns the sliding mean/std/var/... of the vector with a window size of window_size.
     /// The resulting vector is of size : vector.len()  => padding is performed
@@ -587,6 +662,7 @@ mod test {
         let mut s: MovingSum<f32, i16> = MovingSum {
             window_size: 3,
             scale: 1f32,
+            prefix: Vec::new(),
             _src: Default::default(),
         };
         let actual = s.apply_2d(t0.view());
@@ -595,4 +671,24 @@ mod test {
         let actual = s.apply_2d_parallel(t0.view(), None);
         assert_eq!(actual, expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn sliding_exact_matches_float() {
+        let t0 =
+            Array2::from_shape_fn((4, 200), |(i, j)| ((i * 31 + j * j * 7) % 255) as i16 - 127);
+        let t0_f = t0.mapv(|x| x as f64);
+        for st in [SlidingType::Mean, SlidingType::Var, SlidingType::Std] {
+            let mut s_int: SlidingExecutor<f64, i16> = SlidingExecutor::new(st.clone(), 9, None);
+            let mut s_flt: SlidingExecutor<f64, f64> = SlidingExecutor::new(st, 9, None);
+            let actual = s_int.apply_2d(t0.view());
+            let expected = s_flt.apply_2d(t0_f.view());
+            assert!(actual.abs_diff_eq(&expected, 1e-9));
+        }
+
+        let mut ms_int: MovingSum<f32, i16> = MovingSum::new(13, 0.5);
+        let mut ms_flt: MovingSum<f32, f32> = MovingSum::new(13, 0.5);
+        let actual = ms_int.apply_2d(t0.view());
+        let expected = ms_flt.apply_2d(t0.mapv(|x| x as f32).view());
+        assert!(actual.abs_diff_eq(&expected, 1e-3));
+    }
+}
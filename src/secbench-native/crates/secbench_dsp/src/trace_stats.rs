@@ -0,0 +1,277 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Per-trace statistics and outlier rejection.
+//!
+//! Clipped, saturated or glitched traces are usually removed before running an
+//! attack. [`TraceStats`] computes a small set of statistics for each trace (it
+//! is a [`Transform1D`], so it can be run in parallel with
+//! [`crate::Transform2D::apply_2d_parallel`]) and [`OutlierFilter`] turns those
+//! statistics into a keep-mask using robust (median/MAD) thresholds.
+
+use crate::traits::{DspFloat, IntoFloat, Transform1D};
+use ndarray::{Array1, ArrayView1, ArrayView2, Zip};
+use num_traits::Bounded;
+use std::marker::PhantomData;
+
+/// Columns of the output of [`TraceStats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceStat {
+    Min = 0,
+    Max = 1,
+    Mean = 2,
+    Std = 3,
+    /// Number of samples equal to the minimum or maximum value of the input type.
+    Saturated = 4,
+}
+
+impl TraceStat {
+    /// Number of statistics computed per trace.
+    pub const COUNT: usize = 5;
+
+    pub const ALL: [TraceStat; TraceStat::COUNT] = [
+        TraceStat::Min,
+        TraceStat::Max,
+        TraceStat::Mean,
+        TraceStat::Std,
+        TraceStat::Saturated,
+    ];
+}
+
+/// Compute per-trace statistics.
+///
+/// Each input row is reduced to [`TraceStat::COUNT`] values, stored in the
+/// order given by [`TraceStat`]. The standard deviation is the population one
+/// (i.e., same as `numpy.std`).
+///
+/// An empty trace has NaN statistics and no saturated samples.
+#[derive(Clone, Default)]
+pub struct TraceStats<Dst, Src> {
+    _marker: PhantomData<(Dst, Src)>,
+}
+
+impl<Dst, Src> TraceStats<Dst, Src> {
+    pub fn new() -> Self {
+        TraceStats {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Dst, Src> Transform1D<Dst, Src> for TraceStats<Dst, Src>
+where
+    Dst: DspFloat,
+    Src: IntoFloat<Dst> + Bounded + PartialOrd + Copy,
+{
+    fn apply_inplace(&mut self, output: &mut [Dst], input: &[Src]) {
+        assert!(output.len() >= TraceStat::COUNT);
+        if input.is_empty() {
+            output[..TraceStat::COUNT].fill(Dst::nan());
+            output[TraceStat::Saturated as usize] = Dst::zero();
+            return;
+        }
+
+        let (lo, hi) = (Src::min_value(), Src::max_value());
+        let mut min = input[0];
+        let mut max = input[0];
+        let mut saturated = 0usize;
+        let mut sum = Dst::zero();
+        for &x in input {
+            if x < min {
+                min = x;
+            }
+            if x > max {
+                max = x;
+            }
+            if x == lo || x == hi {
+                saturated += 1;
+            }
+            sum += x.into_float();
+        }
+        let n = Dst::from_usize(input.len()).unwrap();
+        let mean = sum / n;
+        // Second pass for the variance, this is more stable than accumulating squares.
+        let var = input
+            .iter()
+            .map(|&x| {
+                let d = x.into_float() - mean;
+                d * d
+            })
+            .fold(Dst::zero(), |acc, x| acc + x)
+            / n;
+
+        output[TraceStat::Min as usize] = min.into_float();
+        output[TraceStat::Max as usize] = max.into_float();
+        output[TraceStat::Mean as usize] = mean;
+        output[TraceStat::Std as usize] = var.sqrt();
+        output[TraceStat::Saturated as usize] = Dst::from_usize(saturated).unwrap();
+    }
+
+    fn output_len(&self, _input_samples: usize) -> usize {
+        TraceStat::COUNT
+    }
+}
+
+/// Median of a set of values, ignoring NaN (NaN if there are no other values).
+fn median<F: DspFloat>(values: ArrayView1<F>) -> F {
+    let mut v: Vec<F> = values.iter().copied().filter(|x| !x.is_nan()).collect();
+    if v.is_empty() {
+        return F::nan();
+    }
+    // NaN were removed, so the order is total.
+    v.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = v.len() / 2;
+    if v.len().is_multiple_of(2) {
+        (v[mid - 1] + v[mid]) / F::from_f64(2.0).unwrap()
+    } else {
+        v[mid]
+    }
+}
+
+/// Reject traces based on the statistics computed by [`TraceStats`].
+///
+/// A trace is rejected when:
+/// - one of the `statistics` is NaN (e.g., empty traces),
+/// - it has more than `max_saturated` saturated samples, or
+/// - one of the `statistics` deviates from the median (over all traces) by more than
+///   `mad_threshold` times the scaled median absolute deviation (MAD).
+///
+/// The MAD is scaled by 1.4826 so that the threshold is expressed in standard
+/// deviations for Gaussian data. Statistics with a null MAD (e.g., all traces
+/// share the same minimum) cannot be scaled and are ignored.
+#[derive(Clone, Debug)]
+pub struct OutlierFilter<F> {
+    pub mad_threshold: F,
+    pub max_saturated: Option<usize>,
+    pub statistics: Vec<TraceStat>,
+}
+
+impl<F> OutlierFilter<F>
+where
+    F: DspFloat,
+{
+    pub fn new(mad_threshold: F, max_saturated: Option<usize>) -> Self {
+        OutlierFilter {
+            mad_threshold,
+            max_saturated,
+            statistics: vec![
+                TraceStat::Min,
+                TraceStat::Max,
+                TraceStat::Mean,
+                TraceStat::Std,
+            ],
+        }
+    }
+
+    /// Compute a mask of traces to keep from an array of shape `(n_traces, TraceStat::COUNT)`.
+    pub fn keep_mask(&self, stats: ArrayView2<F>) -> Array1<bool> {
+        assert_eq!(stats.ncols(), TraceStat::COUNT);
+        let mut mask = Array1::from_elem(stats.nrows(), true);
+        if stats.nrows() == 0 {
+            return mask;
+        }
+
+        if let Some(max_saturated) = self.max_saturated {
+            let max_saturated = F::from_usize(max_saturated).unwrap();
+            Zip::from(&mut mask)
+                .and(stats.column(TraceStat::Saturated as usize))
+                .for_each(|keep, &s| *keep &= s <= max_saturated);
+        }
+
+        let mad_scale = F::from_f64(1.4826).unwrap();
+        for &stat in &self.statistics {
+            let column = stats.column(stat as usize);
+            Zip::from(&mut mask)
+                .and(column)
+                .for_each(|keep, &x| *keep &= !x.is_nan());
+            let med = median(column);
+            let deviations = column.mapv(|x| (x - med).abs());
+            let mad = median(deviations.view()) * mad_scale;
+            if mad.is_nan() || mad <= F::zero() {
+                continue;
+            }
+            let limit = self.mad_threshold * mad;
+            Zip::from(&mut mask)
+                .and(&deviations)
+                .for_each(|keep, &d| *keep &= d <= limit);
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Transform2D;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_trace_stats_and_outliers() {
+        let mut traces = Array2::from_shape_fn((32, 64), |(i, j)| ((i + j) % 7) as i8 - 3);
+        // Clipped trace.
+        traces.row_mut(3).fill(i8::MAX);
+        // Glitch with a large offset.
+        traces.row_mut(10).map_inplace(|x| *x += 60);
+
+        let mut tr: TraceStats<f32, i8> = TraceStats::new();
+        let stats = tr.apply_2d(traces.view());
+        assert_eq!(stats.shape(), &[32, TraceStat::COUNT]);
+        assert_eq!(stats[[3, TraceStat::Saturated as usize]], 64.0);
+        assert_eq!(stats[[0, TraceStat::Min as usize]], -3.0);
+        assert_eq!(stats[[0, TraceStat::Max as usize]], 3.0);
+
+        let stats_par = tr.apply_2d_parallel(traces.view(), Some(5));
+        assert_eq!(stats, stats_par);
+
+        let mask = OutlierFilter::new(5.0f32, Some(0)).keep_mask(stats.view());
+        assert!(!mask[3]);
+        assert!(!mask[10]);
+        assert_eq!(mask.iter().filter(|&&keep| keep).count(), 30);
+    }
+
+    #[test]
+    fn test_empty_and_nan() {
+        let mut tr: TraceStats<f32, i16> = TraceStats::new();
+        let stats = tr.apply_2d(Array2::<i16>::zeros((4, 0)).view());
+        assert!(stats.row(0).iter().take(4).all(|x| x.is_nan()));
+        assert_eq!(stats[[0, TraceStat::Saturated as usize]], 0.0);
+        let mask = OutlierFilter::new(5.0f32, Some(0)).keep_mask(stats.view());
+        assert!(mask.iter().all(|&keep| !keep));
+
+        let values = ndarray::arr1(&[f32::NAN, 3.0, 1.0, f32::NAN, 2.0]);
+        assert_eq!(median(values.view()), 2.0);
+        assert!(median(ndarray::arr1(&[f32::NAN]).view()).is_nan());
+
+        let mut stats = Array2::from_shape_fn((16, TraceStat::COUNT), |(i, j)| (i * j % 5) as f32);
+        stats[[7, TraceStat::Mean as usize]] = f32::NAN;
+        let mask = OutlierFilter::new(5.0f32, None).keep_mask(stats.view());
+        assert!(!mask[7]);
+        assert_eq!(mask.iter().filter(|&&keep| keep).count(), 15);
+    }
+}
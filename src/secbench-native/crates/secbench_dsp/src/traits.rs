@@ -26,10 +26,13 @@
 //
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
+use crate::vectorized::SimdFloat;
 use ndarray::{Array2, ArrayView2, ArrayViewMut2, Axis, Zip};
+use num_traits::float::TotalOrder;
 use num_traits::{Float, FromPrimitive, NumAssignOps, Zero};
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use realfft::FftNum;
+use std::ops::{AddAssign, Mul, Sub, SubAssign};
 
 /// Types that support trivial conversion to float.
 pub trait IntoFloat<F> {
@@ -49,15 +52,109 @@ macro_rules! impl_into_float {
 
 impl_into_float!(i8 => f32, f64);
 impl_into_float!(i16 => f32, f64);
+impl_into_float!(u8 => f32, f64);
+impl_into_float!(u16 => f32, f64);
 impl_into_float!(f32 => f32, f64);
 impl_into_float!(f64 => f32, f64);
 
+/// Sample types with a dedicated accumulator.
+///
+/// Integer ADC samples (8 and 16 bits, signed or not) are accumulated exactly in `i64`, which is also faster than the
+/// compensated summation required for floats. Accumulators are converted to `Dst` only
+/// when results are produced. Floating point samples use `Dst` as accumulator and
+/// `EXACT` is false, algorithms keep their compensated summation for them.
+pub trait Accumulate<Dst>: IntoFloat<Dst> + Copy {
+    type Acc: Copy
+        + Zero
+        + AddAssign
+        + SubAssign
+        + Sub<Output = Self::Acc>
+        + Mul<Output = Self::Acc>
+        + Send
+        + Sync;
+
+    /// Whether arithmetic on `Acc` is exact.
+    const EXACT: bool;
+
+    fn widen(self) -> Self::Acc;
+
+    fn acc_into_float(acc: Self::Acc) -> Dst;
+
+    /// Sum of squared deviations to the mean of `n` samples, given their sum and sum of squares.
+    fn acc_m2(sum: Self::Acc, sum_sq: Self::Acc, n: usize) -> Dst;
+}
+
+/// Exact sum of squared deviations for integer accumulators.
+#[inline]
+pub(crate) fn exact_m2(sum: i64, sum_sq: i64, n: u64) -> f64 {
+    let (sum, sum_sq, n) = (sum as i128, sum_sq as i128, n as i128);
+    (n * sum_sq - sum * sum) as f64 / n as f64
+}
+
+macro_rules! impl_accumulate_int {
+    ($Src:ty => $($Dst:ty),*) => { $(
+        impl Accumulate<$Dst> for $Src {
+            type Acc = i64;
+            const EXACT: bool = true;
+
+            #[inline(always)]
+            fn widen(self) -> i64 {
+                self as i64
+            }
+
+            #[inline(always)]
+            fn acc_into_float(acc: i64) -> $Dst {
+                acc as $Dst
+            }
+
+            #[inline(always)]
+            fn acc_m2(sum: i64, sum_sq: i64, n: usize) -> $Dst {
+                exact_m2(sum, sum_sq, n as u64) as $Dst
+            }
+        }
+    )* };
+}
+
+macro_rules! impl_accumulate_float {
+    ($Src:ty => $($Dst:ty),*) => { $(
+        impl Accumulate<$Dst> for $Src {
+            type Acc = $Dst;
+            const EXACT: bool = false;
+
+            #[inline(always)]
+            fn widen(self) -> $Dst {
+                self as $Dst
+            }
+
+            #[inline(always)]
+            fn acc_into_float(acc: $Dst) -> $Dst {
+                acc
+            }
+
+            #[inline(always)]
+            fn acc_m2(sum: $Dst, sum_sq: $Dst, n: usize) -> $Dst {
+                sum_sq - sum * sum / (n as $Dst)
+            }
+        }
+    )* };
+}
+
+impl_accumulate_int!(i8 => f32, f64);
+impl_accumulate_int!(i16 => f32, f64);
+impl_accumulate_int!(u8 => f32, f64);
+impl_accumulate_int!(u16 => f32, f64);
+impl_accumulate_float!(f32 => f32, f64);
+impl_accumulate_float!(f64 => f32, f64);
+
 /// Type of float used through the secbench_dsp crate.
 ///
 /// In our case, this is just an alias to f16, f32, f64...
-pub trait DspFloat: NumAssignOps + Float + FromPrimitive + FftNum {}
+pub trait DspFloat: NumAssignOps + Float + FromPrimitive + FftNum + TotalOrder + SimdFloat {}
 
-impl<T> DspFloat for T where T: NumAssignOps + Float + FromPrimitive + FftNum {}
+impl<T> DspFloat for T where
+    T: NumAssignOps + Float + FromPrimitive + FftNum + TotalOrder + SimdFloat
+{
+}
 
 pub trait Transform1D<Dst, Src> {
     fn apply_inplace(&mut self, output: &mut [Dst], input: &[Src]);
@@ -147,4 +244,4 @@ where
         self.apply_2d_inplace_parallel(result.view_mut(), input, chunk_size);
         result
     }
-}
\ No newline at end of file
+}
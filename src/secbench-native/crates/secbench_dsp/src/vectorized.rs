@@ -0,0 +1,575 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! SIMD kernels for the inner loops of sliding statistics, pattern matching
+//! and FFT filtering.
+//!
+//! Kernels are written with `std::arch` intrinsics for AVX2 and AVX-512F on
+//! x86_64, and NEON on aarch64, for `f32` and `f64`. The instruction set is
+//! detected once per process (see [`level`]). Other targets, and x86_64 CPUs
+//! without AVX2, use the [`scalar`] reference implementations.
+//!
+//! Hot loops should fetch the kernel table once with [`SimdFloat::kernels`]
+//! and call its function pointers.
+//!
+//! Element-wise kernels return exactly the same values as their scalar
+//! version. [`Kernels::sum_sq_dev`] sums in a different order, so the results
+//! only agree up to rounding.
+//!
+//! `cargo bench -p secbench_dsp --bench vectorized` compares the kernels with
+//! the [`scalar`] implementations.
+
+use num_traits::Float;
+use realfft::num_complex::Complex;
+use std::sync::OnceLock;
+
+/// Reference scalar implementations.
+pub mod scalar {
+    use num_traits::Float;
+    use realfft::num_complex::Complex;
+
+    /// Sum of squared deviations to `mean`.
+    pub fn sum_sq_dev<T: Float>(xs: &[T], mean: T) -> T {
+        let mut sum = T::zero();
+        for &x in xs {
+            sum = sum + (x - mean) * (x - mean);
+        }
+        sum
+    }
+
+    /// Squared euclidean distance from `x.x`, `x.p` and `p.p` terms.
+    pub fn euclidean_combine<T: Float>(dst: &mut [T], xx: &[T], xp: &[T], p_square: T) {
+        let two = T::one() + T::one();
+        for ((d, &xx), &xp) in dst.iter_mut().zip(xx).zip(xp) {
+            *d = xx - two * xp + p_square;
+        }
+    }
+
+    /// Pearson correlation from the correlation and moments of a window.
+    pub fn correlation_combine<T: Float>(
+        dst: &mut [T],
+        xp: &[T],
+        x_ms: &[T],
+        x_std: &[T],
+        p_mean: T,
+        p_std: T,
+    ) {
+        for (((d, &xp), &x_ms), &x_std) in dst.iter_mut().zip(xp).zip(x_ms).zip(x_std) {
+            *d = (xp - x_ms * p_mean) / (x_std * p_std);
+        }
+    }
+
+    /// Scaled difference `(a - b) * scale`.
+    pub fn sub_scaled<T: Float>(dst: &mut [T], a: &[T], b: &[T], scale: T) {
+        for ((d, &a), &b) in dst.iter_mut().zip(a).zip(b) {
+            *d = (a - b) * scale;
+        }
+    }
+
+    /// Divide all values by `d`.
+    pub fn div_assign<T: Float>(xs: &mut [T], d: T) {
+        for x in xs {
+            *x = *x / d;
+        }
+    }
+
+    /// Product of complex values, stored in `xs`.
+    pub fn complex_mul<T: Float>(xs: &mut [Complex<T>], ks: &[Complex<T>]) {
+        for (x, &k) in xs.iter_mut().zip(ks) {
+            *x = *x * k;
+        }
+    }
+}
+
+/// Kernels for one float type and one instruction set.
+///
+/// Each kernel has the semantics of the function of the same name in
+/// [`scalar`]. Slices are processed up to the length of the shortest one.
+#[allow(clippy::type_complexity)]
+pub struct Kernels<T> {
+    pub sum_sq_dev: fn(&[T], T) -> T,
+    pub euclidean_combine: fn(&mut [T], &[T], &[T], T),
+    pub correlation_combine: fn(&mut [T], &[T], &[T], &[T], T, T),
+    pub sub_scaled: fn(&mut [T], &[T], &[T], T),
+    pub div_assign: fn(&mut [T], T),
+    pub complex_mul: fn(&mut [Complex<T>], &[Complex<T>]),
+}
+
+impl<T: Float> Kernels<T> {
+    const SCALAR: Kernels<T> = Kernels {
+        sum_sq_dev: scalar::sum_sq_dev,
+        euclidean_combine: scalar::euclidean_combine,
+        correlation_combine: scalar::correlation_combine,
+        sub_scaled: scalar::sub_scaled,
+        div_assign: scalar::div_assign,
+        complex_mul: scalar::complex_mul,
+    };
+}
+
+/// Instruction set of a kernel table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// The [`scalar`] implementations.
+    Scalar,
+    /// x86_64 with AVX2.
+    Avx2,
+    /// x86_64 with AVX-512F.
+    Avx512,
+    /// aarch64 with NEON.
+    Neon,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Avx512, Level::Avx2, Level::Neon, Level::Scalar];
+
+    /// Whether the current CPU supports this instruction set.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Level::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx2 => std::arch::is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx512 => std::arch::is_x86_feature_detected!("avx512f"),
+            #[cfg(target_arch = "aarch64")]
+            Level::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+/// Best [`Level`] supported by the current CPU, detected on the first call.
+pub fn level() -> Level {
+    static LEVEL: OnceLock<Level> = OnceLock::new();
+    *LEVEL.get_or_init(|| {
+        Level::ALL
+            .into_iter()
+            .find(|l| l.is_supported())
+            .unwrap_or(Level::Scalar)
+    })
+}
+
+/// Float types with SIMD kernels.
+pub trait SimdFloat: Float + 'static {
+    /// Kernels for `level`, or `None` if the current CPU does not support it.
+    fn kernels_for(level: Level) -> Option<&'static Kernels<Self>>;
+
+    /// Best kernels for the current CPU.
+    fn kernels() -> &'static Kernels<Self> {
+        Self::kernels_for(level()).unwrap()
+    }
+}
+
+macro_rules! impl_simd_float {
+    ($t:ident, $avx2:ident, $avx512:ident, $neon:ident) => {
+        impl SimdFloat for $t {
+            fn kernels_for(level: Level) -> Option<&'static Kernels<$t>> {
+                static SCALAR: Kernels<$t> = Kernels::SCALAR;
+                if !level.is_supported() {
+                    return None;
+                }
+                // Tables of other levels are only valid once the instruction
+                // set is detected, which is checked above.
+                Some(match level {
+                    #[cfg(target_arch = "x86_64")]
+                    Level::Avx2 => &x86::$avx2::KERNELS,
+                    #[cfg(target_arch = "x86_64")]
+                    Level::Avx512 => &x86::$avx512::KERNELS,
+                    #[cfg(target_arch = "aarch64")]
+                    Level::Neon => &aarch64::$neon::KERNELS,
+                    _ => &SCALAR,
+                })
+            }
+        }
+    };
+}
+
+impl_simd_float!(f32, avx2_f32, avx512_f32, neon_f32);
+impl_simd_float!(f64, avx2_f64, avx512_f64, neon_f64);
+
+/// Define a module `$name` with the kernels of one vector type, and their
+/// table `KERNELS`.
+///
+/// The vector operations are given as paths: `$splat` broadcasts a value,
+/// `$load`/`$store` are unaligned memory accesses of `$lanes` values,
+/// `$add`... are lane-wise operations, and `$cmul` multiplies
+/// `$lanes / 2` interleaved complex values.
+macro_rules! simd_kernels {
+    (
+        mod $name:ident: $feature:literal, [$t:ident; $lanes:literal],
+        splat: $splat:path, load: $load:path, store: $store:path,
+        add: $add:path, sub: $sub:path, mul: $mul:path, div: $div:path,
+        cmul: $cmul:path $(,)?
+    ) => {
+        pub(super) mod $name {
+            use super::*;
+            use crate::vectorized::{scalar, Kernels};
+            use realfft::num_complex::Complex;
+
+            #[target_feature(enable = $feature)]
+            unsafe fn sum_sq_dev(xs: &[$t], mean: $t) -> $t {
+                let m = $splat(mean);
+                // Two accumulators hide the latency of additions.
+                let (mut acc0, mut acc1) = ($splat(0.0), $splat(0.0));
+                let chunks = xs.chunks_exact(2 * $lanes);
+                let rem = chunks.remainder();
+                for c in chunks {
+                    let d0 = $sub($load(c.as_ptr()), m);
+                    let d1 = $sub($load(c.as_ptr().add($lanes)), m);
+                    acc0 = $add(acc0, $mul(d0, d0));
+                    acc1 = $add(acc1, $mul(d1, d1));
+                }
+                let mut lanes = [0.0; $lanes];
+                $store(lanes.as_mut_ptr(), $add(acc0, acc1));
+                lanes.iter().sum::<$t>() + scalar::sum_sq_dev(rem, mean)
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn euclidean_combine(dst: &mut [$t], xx: &[$t], xp: &[$t], p_square: $t) {
+                let n = dst.len().min(xx.len()).min(xp.len());
+                let main = n - n % $lanes;
+                let (two, p) = ($splat(2.0), $splat(p_square));
+                for i in (0..main).step_by($lanes) {
+                    let xx_i = $load(xx.as_ptr().add(i));
+                    let xp_i = $load(xp.as_ptr().add(i));
+                    let d = $add($sub(xx_i, $mul(two, xp_i)), p);
+                    $store(dst.as_mut_ptr().add(i), d);
+                }
+                scalar::euclidean_combine(&mut dst[main..n], &xx[main..n], &xp[main..n], p_square);
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn correlation_combine(
+                dst: &mut [$t],
+                xp: &[$t],
+                x_ms: &[$t],
+                x_std: &[$t],
+                p_mean: $t,
+                p_std: $t,
+            ) {
+                let n = dst.len().min(xp.len()).min(x_ms.len()).min(x_std.len());
+                let main = n - n % $lanes;
+                let (p_mean_v, p_std_v) = ($splat(p_mean), $splat(p_std));
+                for i in (0..main).step_by($lanes) {
+                    let xp_i = $load(xp.as_ptr().add(i));
+                    let x_ms_i = $load(x_ms.as_ptr().add(i));
+                    let x_std_i = $load(x_std.as_ptr().add(i));
+                    let num = $sub(xp_i, $mul(x_ms_i, p_mean_v));
+                    let d = $div(num, $mul(x_std_i, p_std_v));
+                    $store(dst.as_mut_ptr().add(i), d);
+                }
+                scalar::correlation_combine(
+                    &mut dst[main..n],
+                    &xp[main..n],
+                    &x_ms[main..n],
+                    &x_std[main..n],
+                    p_mean,
+                    p_std,
+                );
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn sub_scaled(dst: &mut [$t], a: &[$t], b: &[$t], scale: $t) {
+                let n = dst.len().min(a.len()).min(b.len());
+                let main = n - n % $lanes;
+                let s = $splat(scale);
+                for i in (0..main).step_by($lanes) {
+                    let d = $sub($load(a.as_ptr().add(i)), $load(b.as_ptr().add(i)));
+                    $store(dst.as_mut_ptr().add(i), $mul(d, s));
+                }
+                scalar::sub_scaled(&mut dst[main..n], &a[main..n], &b[main..n], scale);
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn div_assign(xs: &mut [$t], d: $t) {
+                let main = xs.len() - xs.len() % $lanes;
+                let d_v = $splat(d);
+                for i in (0..main).step_by($lanes) {
+                    let p = xs.as_mut_ptr().add(i);
+                    $store(p, $div($load(p), d_v));
+                }
+                scalar::div_assign(&mut xs[main..], d);
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn complex_mul(xs: &mut [Complex<$t>], ks: &[Complex<$t>]) {
+                let n = xs.len().min(ks.len());
+                let main = n - n % ($lanes / 2);
+                // `Complex` is `repr(C)`, values are interleaved `(re, im)` pairs.
+                let x = xs.as_mut_ptr() as *mut $t;
+                let k = ks.as_ptr() as *const $t;
+                for i in (0..2 * main).step_by($lanes) {
+                    $store(x.add(i), $cmul($load(x.add(i)), $load(k.add(i))));
+                }
+                scalar::complex_mul(&mut xs[main..n], &ks[main..n]);
+            }
+
+            // SAFETY: the table is only returned by `SimdFloat::kernels_for`
+            // once the instruction set is detected.
+            pub(crate) static KERNELS: Kernels<$t> = Kernels {
+                sum_sq_dev: |xs, mean| unsafe { sum_sq_dev(xs, mean) },
+                euclidean_combine: |dst, xx, xp, p_square| unsafe {
+                    euclidean_combine(dst, xx, xp, p_square)
+                },
+                correlation_combine: |dst, xp, x_ms, x_std, p_mean, p_std| unsafe {
+                    correlation_combine(dst, xp, x_ms, x_std, p_mean, p_std)
+                },
+                sub_scaled: |dst, a, b, scale| unsafe { sub_scaled(dst, a, b, scale) },
+                div_assign: |xs, d| unsafe { div_assign(xs, d) },
+                complex_mul: |xs, ks| unsafe { complex_mul(xs, ks) },
+            };
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    // Complex products: with x = (a, b) and k = (c, d), the real part
+    // a * c - b * d is computed in even lanes and the imaginary part
+    // b * c + a * d in odd lanes.
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn cmul_avx2_ps(x: __m256, k: __m256) -> __m256 {
+        let re = _mm256_moveldup_ps(k);
+        let im = _mm256_movehdup_ps(k);
+        let swapped = _mm256_permute_ps(x, 0b1011_0001);
+        _mm256_addsub_ps(_mm256_mul_ps(x, re), _mm256_mul_ps(swapped, im))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn cmul_avx2_pd(x: __m256d, k: __m256d) -> __m256d {
+        let re = _mm256_movedup_pd(k);
+        let im = _mm256_permute_pd(k, 0b1111);
+        let swapped = _mm256_permute_pd(x, 0b0101);
+        _mm256_addsub_pd(_mm256_mul_pd(x, re), _mm256_mul_pd(swapped, im))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn cmul_avx512_ps(x: __m512, k: __m512) -> __m512 {
+        let re = _mm512_moveldup_ps(k);
+        let im = _mm512_movehdup_ps(k);
+        let swapped = _mm512_permute_ps(x, 0b1011_0001);
+        let (a, b) = (_mm512_mul_ps(x, re), _mm512_mul_ps(swapped, im));
+        _mm512_mask_sub_ps(_mm512_add_ps(a, b), 0x5555, a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn cmul_avx512_pd(x: __m512d, k: __m512d) -> __m512d {
+        let re = _mm512_movedup_pd(k);
+        let im = _mm512_permute_pd(k, 0xff);
+        let swapped = _mm512_permute_pd(x, 0x55);
+        let (a, b) = (_mm512_mul_pd(x, re), _mm512_mul_pd(swapped, im));
+        _mm512_mask_sub_pd(_mm512_add_pd(a, b), 0x55, a, b)
+    }
+
+    simd_kernels! {
+        mod avx2_f32: "avx2", [f32; 8],
+        splat: _mm256_set1_ps, load: _mm256_loadu_ps, store: _mm256_storeu_ps,
+        add: _mm256_add_ps, sub: _mm256_sub_ps, mul: _mm256_mul_ps, div: _mm256_div_ps,
+        cmul: cmul_avx2_ps,
+    }
+
+    simd_kernels! {
+        mod avx2_f64: "avx2", [f64; 4],
+        splat: _mm256_set1_pd, load: _mm256_loadu_pd, store: _mm256_storeu_pd,
+        add: _mm256_add_pd, sub: _mm256_sub_pd, mul: _mm256_mul_pd, div: _mm256_div_pd,
+        cmul: cmul_avx2_pd,
+    }
+
+    simd_kernels! {
+        mod avx512_f32: "avx512f", [f32; 16],
+        splat: _mm512_set1_ps, load: _mm512_loadu_ps, store: _mm512_storeu_ps,
+        add: _mm512_add_ps, sub: _mm512_sub_ps, mul: _mm512_mul_ps, div: _mm512_div_ps,
+        cmul: cmul_avx512_ps,
+    }
+
+    simd_kernels! {
+        mod avx512_f64: "avx512f", [f64; 8],
+        splat: _mm512_set1_pd, load: _mm512_loadu_pd, store: _mm512_storeu_pd,
+        add: _mm512_add_pd, sub: _mm512_sub_pd, mul: _mm512_mul_pd, div: _mm512_div_pd,
+        cmul: cmul_avx512_pd,
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use std::arch::aarch64::*;
+
+    // Complex products, see the x86 versions. Odd lanes of `swapped * im`
+    // are added and even lanes subtracted, through a multiplication by -1
+    // (which is exact).
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn cmul_neon_f32(x: float32x4_t, k: float32x4_t) -> float32x4_t {
+        let re = vtrn1q_f32(k, k);
+        let im = vtrn2q_f32(k, k);
+        let swapped = vrev64q_f32(x);
+        let sign = vld1q_f32([-1.0, 1.0, -1.0, 1.0].as_ptr());
+        let b = vmulq_f32(vmulq_f32(swapped, im), sign);
+        vaddq_f32(vmulq_f32(x, re), b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn cmul_neon_f64(x: float64x2_t, k: float64x2_t) -> float64x2_t {
+        let re = vtrn1q_f64(k, k);
+        let im = vtrn2q_f64(k, k);
+        let swapped = vextq_f64(x, x, 1);
+        let sign = vld1q_f64([-1.0, 1.0].as_ptr());
+        let b = vmulq_f64(vmulq_f64(swapped, im), sign);
+        vaddq_f64(vmulq_f64(x, re), b)
+    }
+
+    simd_kernels! {
+        mod neon_f32: "neon", [f32; 4],
+        splat: vdupq_n_f32, load: vld1q_f32, store: vst1q_f32,
+        add: vaddq_f32, sub: vsubq_f32, mul: vmulq_f32, div: vdivq_f32,
+        cmul: cmul_neon_f32,
+    }
+
+    simd_kernels! {
+        mod neon_f64: "neon", [f64; 2],
+        splat: vdupq_n_f64, load: vld1q_f64, store: vst1q_f64,
+        add: vaddq_f64, sub: vsubq_f64, mul: vmulq_f64, div: vdivq_f64,
+        cmul: cmul_neon_f64,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn levels() -> impl Iterator<Item = Level> {
+        Level::ALL.into_iter().filter(|l| l.is_supported())
+    }
+
+    proptest! {
+        #[test]
+        fn sum_sq_dev_matches_scalar(
+            xs in prop::collection::vec(-1000.0f32..1000.0, 0..300),
+            mean in -100.0f32..100.0,
+        ) {
+            let xs_f64: Vec<f64> = xs.iter().map(|&x| x as f64).collect();
+            let expected = scalar::sum_sq_dev(&xs, mean);
+            let expected_f64 = scalar::sum_sq_dev(&xs_f64, mean as f64);
+            for level in levels() {
+                let actual = (f32::kernels_for(level).unwrap().sum_sq_dev)(&xs, mean);
+                prop_assert!((actual - expected).abs() <= 1e-5 * expected.abs().max(1.0));
+                let actual = (f64::kernels_for(level).unwrap().sum_sq_dev)(&xs_f64, mean as f64);
+                prop_assert!((actual - expected_f64).abs() <= 1e-12 * expected_f64.abs().max(1.0));
+            }
+        }
+
+        #[test]
+        fn elementwise_matches_scalar(
+            data in prop::collection::vec((-10.0f64..10.0, -10.0f64..10.0, 0.5f64..10.0), 0..100),
+            p_mean in -5.0f64..5.0,
+            p_std in 0.5f64..5.0,
+        ) {
+            let a: Vec<f64> = data.iter().map(|x| x.0).collect();
+            let b: Vec<f64> = data.iter().map(|x| x.1).collect();
+            let c: Vec<f64> = data.iter().map(|x| x.2).collect();
+            let n = data.len();
+            for level in levels() {
+                let k = f64::kernels_for(level).unwrap();
+                let (mut expected, mut actual) = (vec![0.0; n], vec![0.0; n]);
+
+                scalar::euclidean_combine(&mut expected, &a, &b, p_mean);
+                (k.euclidean_combine)(&mut actual, &a, &b, p_mean);
+                prop_assert_eq!(&actual, &expected);
+
+                scalar::correlation_combine(&mut expected, &a, &b, &c, p_mean, p_std);
+                (k.correlation_combine)(&mut actual, &a, &b, &c, p_mean, p_std);
+                prop_assert_eq!(&actual, &expected);
+
+                scalar::sub_scaled(&mut expected, &a, &b, p_std);
+                (k.sub_scaled)(&mut actual, &a, &b, p_std);
+                prop_assert_eq!(&actual, &expected);
+
+                let mut actual = a.clone();
+                let mut expected = a.clone();
+                scalar::div_assign(&mut expected, p_std);
+                (k.div_assign)(&mut actual, p_std);
+                prop_assert_eq!(&actual, &expected);
+
+                // Same checks in f32.
+                let k = f32::kernels_for(level).unwrap();
+                let a: Vec<f32> = a.iter().map(|&x| x as f32).collect();
+                let b: Vec<f32> = b.iter().map(|&x| x as f32).collect();
+                let c: Vec<f32> = c.iter().map(|&x| x as f32).collect();
+                let (p_mean, p_std) = (p_mean as f32, p_std as f32);
+                let (mut expected, mut actual) = (vec![0.0; n], vec![0.0; n]);
+                scalar::euclidean_combine(&mut expected, &a, &b, p_mean);
+                (k.euclidean_combine)(&mut actual, &a, &b, p_mean);
+                prop_assert_eq!(&actual, &expected);
+                scalar::correlation_combine(&mut expected, &a, &b, &c, p_mean, p_std);
+                (k.correlation_combine)(&mut actual, &a, &b, &c, p_mean, p_std);
+                prop_assert_eq!(&actual, &expected);
+            }
+        }
+
+        #[test]
+        fn complex_mul_matches_scalar(
+            data in prop::collection::vec((-10.0f64..10.0, -10.0f64..10.0), 0..50),
+        ) {
+            let xs: Vec<Complex<f64>> = data.iter().map(|&(re, im)| Complex::new(re, im)).collect();
+            let ks: Vec<Complex<f64>> = xs.iter().rev().map(|x| x * 0.5).collect();
+            let mut expected = xs.clone();
+            scalar::complex_mul(&mut expected, &ks);
+            let xs_f32: Vec<Complex<f32>> = xs.iter().map(|x| Complex::new(x.re as f32, x.im as f32)).collect();
+            let ks_f32: Vec<Complex<f32>> = ks.iter().map(|x| Complex::new(x.re as f32, x.im as f32)).collect();
+            let mut expected_f32 = xs_f32.clone();
+            scalar::complex_mul(&mut expected_f32, &ks_f32);
+            for level in levels() {
+                let mut actual = xs.clone();
+                (f64::kernels_for(level).unwrap().complex_mul)(&mut actual, &ks);
+                prop_assert_eq!(&actual, &expected);
+                let mut actual = xs_f32.clone();
+                (f32::kernels_for(level).unwrap().complex_mul)(&mut actual, &ks_f32);
+                prop_assert_eq!(&actual, &expected_f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_level() {
+        assert!(level().is_supported());
+        assert!(f32::kernels_for(Level::Scalar).is_some());
+        #[cfg(target_arch = "x86_64")]
+        assert!(f64::kernels_for(Level::Neon).is_none());
+    }
+}
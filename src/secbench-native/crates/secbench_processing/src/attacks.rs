@@ -0,0 +1,719 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use core::ops::ControlFlow;
+
+use numpy::ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::crypto::check_selection_data;
+use crate::{assert_shape_match, dispatch_dtype};
+use rand_core::RngCore;
+use secbench_crypto::aes::{Aes, Block};
+use secbench_crypto::labels::{Selection, UnknownSelection};
+use secbench_crypto::{Pcg32, Pcg32Seed};
+
+/// Labels of the attack traces under each key hypothesis.
+enum HypothesisLabels<'a> {
+    /// Label of trace `t` under hypothesis `h` at `[h, t]`.
+    Table(ArrayView2<'a, u16>),
+    /// Labels computed with an AES selection function from one data byte
+    /// (and the register byte, see [`Selection::register_byte`]).
+    Aes {
+        data: ArrayView1<'a, u8>,
+        register: Option<ArrayView1<'a, u8>>,
+        selection: Selection,
+    },
+}
+
+impl HypothesisLabels<'_> {
+    fn n_hypotheses(&self) -> usize {
+        match self {
+            HypothesisLabels::Table(t) => t.nrows(),
+            HypothesisLabels::Aes { .. } => 256,
+        }
+    }
+
+    /// Largest label plus one.
+    fn n_classes(&self) -> usize {
+        match self {
+            HypothesisLabels::Table(t) => t.iter().max().map_or(0, |&m| m as usize + 1),
+            HypothesisLabels::Aes { selection, .. } => selection.classes(),
+        }
+    }
+
+    fn label(&self, h: usize, t: usize) -> usize {
+        match self {
+            HypothesisLabels::Table(table) => table[(h, t)] as usize,
+            HypothesisLabels::Aes {
+                data,
+                register,
+                selection,
+            } => selection.apply(data[t], h as u8, register.map_or(0, |r| r[t])) as usize,
+        }
+    }
+}
+
+/// Check that checkpoints are non-decreasing trace counts, defaulting to
+/// all traces.
+fn checkpoints_or_default(
+    checkpoints: Option<Vec<usize>>,
+    n_traces: usize,
+) -> PyResult<Vec<usize>> {
+    let checkpoints = checkpoints.unwrap_or_else(|| vec![n_traces]);
+    if checkpoints.windows(2).any(|w| w[0] > w[1]) {
+        return Err(PyValueError::new_err("checkpoints must be sorted"));
+    }
+    if let Some(c) = checkpoints.iter().find(|&&c| c > n_traces) {
+        return Err(PyValueError::new_err(format!(
+            "checkpoint {c} exceeds the number of traces ({n_traces})"
+        )));
+    }
+    Ok(checkpoints)
+}
+
+/// Scores of each hypothesis (columns) after each checkpoint (rows).
+fn key_scores<T>(
+    log_probas: ArrayView2<T>,
+    labels: &HypothesisLabels,
+    checkpoints: &[usize],
+    parallel: bool,
+) -> Array2<f64>
+where
+    T: Copy + Into<f64> + Sync,
+{
+    let scores_of = |h: usize| {
+        let mut scores = Vec::with_capacity(checkpoints.len());
+        let (mut acc, mut t) = (0.0, 0);
+        for &c in checkpoints {
+            for t in t..c {
+                acc += log_probas[(t, labels.label(h, t))].into();
+            }
+            t = c;
+            scores.push(acc);
+        }
+        scores
+    };
+    let n_hyp = labels.n_hypotheses();
+    let columns: Vec<Vec<f64>> = if parallel {
+        (0..n_hyp).into_par_iter().map(scores_of).collect()
+    } else {
+        (0..n_hyp).map(scores_of).collect()
+    };
+    Array2::from_shape_fn((checkpoints.len(), n_hyp), |(i, h)| columns[h][i])
+}
+
+/// Cumulative log-likelihood of key hypotheses, from the log-probabilities
+/// of a classifier.
+///
+/// `log_probas` has shape `(n_traces, n_classes)` (f32 or f64). The labels of
+/// each trace under each hypothesis are given either by a `labels` table of
+/// shape `(n_hypotheses, n_traces)`, or by an AES `selection` (see
+/// `aes_labels`) applied to column `byte` of `data` for the 256 key guesses.
+///
+/// Returns an array of shape `(len(checkpoints), n_hypotheses)`, row `i` being
+/// the scores using the first `checkpoints[i]` traces (all traces by default).
+#[pyfunction]
+#[pyo3(signature = (log_probas, *, labels=None, data=None, selection=None, byte=0, checkpoints=None, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn accumulate_key_scores<'py>(
+    log_probas: Bound<'py, PyAny>,
+    labels: Option<PyReadonlyArray2<'py, u16>>,
+    data: Option<PyReadonlyArray2<'py, u8>>,
+    selection: Option<&str>,
+    byte: usize,
+    checkpoints: Option<Vec<usize>>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let py = log_probas.py();
+    let table = labels.as_ref().map(|l| l.as_array());
+    let data_view = data.as_ref().map(|d| d.as_array());
+    let hyp_labels = match (table, data_view, selection) {
+        (Some(table), None, None) => HypothesisLabels::Table(table),
+        (None, Some(data), Some(selection)) => {
+            if byte >= data.ncols() {
+                return Err(PyValueError::new_err(format!(
+                    "byte index {byte} out of range for data with {} columns",
+                    data.ncols()
+                )));
+            }
+            let selection: Selection = selection
+                .parse()
+                .map_err(|e: UnknownSelection| PyValueError::new_err(e.to_string()))?;
+            check_selection_data(selection, data.ncols())?;
+            HypothesisLabels::Aes {
+                data: data.index_axis_move(Axis(1), byte),
+                register: selection
+                    .register_byte(byte)
+                    .map(|r| data.index_axis_move(Axis(1), r)),
+                selection,
+            }
+        }
+        _ => {
+            return Err(PyValueError::new_err(
+                "either labels, or data and selection must be given",
+            ))
+        }
+    };
+    dispatch_dtype!("accumulate_key_scores", log_probas, [f32, f64], |T| {
+        let log_probas = log_probas.downcast_into::<PyArray2<T>>()?.readonly();
+        let log_probas = log_probas.as_array();
+        let n_traces = match &hyp_labels {
+            HypothesisLabels::Table(table) => {
+                assert_shape_match!([_, log_probas.nrows()] => table);
+                log_probas.nrows()
+            }
+            HypothesisLabels::Aes { data, .. } => {
+                assert_shape_match!([log_probas.nrows()] => data);
+                log_probas.nrows()
+            }
+        };
+        if hyp_labels.n_classes() > log_probas.ncols() {
+            return Err(PyValueError::new_err(format!(
+                "labels go up to {}, but log_probas only has {} classes",
+                hyp_labels.n_classes() - 1,
+                log_probas.ncols()
+            )));
+        }
+        let checkpoints = checkpoints_or_default(checkpoints, n_traces)?;
+        let scores = key_scores(log_probas, &hyp_labels, &checkpoints, parallel);
+        Ok(scores.into_pyarray_bound(py))
+    })
+}
+
+/// Uniform integer in `0..n`, using a multiply-shift reduction.
+pub(crate) fn bounded(rng: &mut Pcg32, n: usize) -> usize {
+    ((rng.next_u32() as u64 * n as u64) >> 32) as usize
+}
+
+/// Rank (from 0) of hypothesis `key` in `scores`, ties being broken uniformly
+/// at random (as `rank_of(randomize=True)`).
+fn randomized_rank(scores: &[f64], key: usize, rng: &mut Pcg32) -> usize {
+    let s = scores[key];
+    let greater = scores.iter().filter(|&&x| x > s).count();
+    let equal = scores.iter().filter(|&&x| x == s).count();
+    greater + bounded(rng, equal)
+}
+
+/// Rank of `key` at each checkpoint, for a random ordering of the traces.
+fn attack_ranks<T>(
+    scores: ArrayView2<T>,
+    key: usize,
+    checkpoints: &[usize],
+    rng: &mut Pcg32,
+) -> Vec<usize>
+where
+    T: Copy + Into<f64>,
+{
+    let mut order: Vec<usize> = (0..scores.nrows()).collect();
+    // Fisher-Yates shuffle.
+    for i in (1..order.len()).rev() {
+        order.swap(i, bounded(rng, i + 1));
+    }
+    let mut acc = vec![0.0; scores.ncols()];
+    let mut ranks = Vec::with_capacity(checkpoints.len());
+    let mut t = 0;
+    for &c in checkpoints {
+        for &i in &order[t..c] {
+            acc.iter_mut()
+                .zip(scores.row(i))
+                .for_each(|(a, &x)| *a += x.into());
+        }
+        t = c;
+        ranks.push(randomized_rank(&acc, key, rng));
+    }
+    ranks
+}
+
+/// Percentile `p` (in `[0, 100]`) of sorted values, with linear interpolation
+/// (as `numpy.percentile`).
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let pos = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Guessing entropy, success rate and rank percentiles of an attack, over
+/// random orderings of the attack traces.
+///
+/// `scores` has shape `(n_traces, n_hypotheses)` and contains the score (e.g.,
+/// log-likelihood) of each hypothesis for each trace. For each of the
+/// `n_experiments` experiments, traces are shuffled (experiment `i` uses
+/// `Pcg32(seed, i)`) and the rank of the hypothesis `key` is computed after the
+/// first `checkpoints[j]` traces (1 to `n_traces` by default). Ties are broken
+/// at random, as in `rank_of(randomize=True)`, and ranks start at 0.
+///
+/// Returns `(ge, sr, rank_percentiles)`: the mean rank and the rate of ranks
+/// below `sr_order` for each checkpoint, and an array of shape
+/// `(len(percentiles), len(checkpoints))`.
+#[pyfunction]
+#[pyo3(signature = (scores, key, *, checkpoints=None, n_experiments=100, percentiles=None, sr_order=1, seed=0, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn guessing_entropy<'py>(
+    scores: Bound<'py, PyAny>,
+    key: usize,
+    checkpoints: Option<Vec<usize>>,
+    n_experiments: usize,
+    percentiles: Option<Vec<f64>>,
+    sr_order: usize,
+    seed: u64,
+    parallel: bool,
+) -> PyResult<(
+    Bound<'py, PyArray1<f64>>,
+    Bound<'py, PyArray1<f64>>,
+    Bound<'py, PyArray2<f64>>,
+)> {
+    let py = scores.py();
+    if n_experiments == 0 {
+        return Err(PyValueError::new_err("n_experiments must be positive"));
+    }
+    let percentiles = percentiles.unwrap_or_default();
+    if percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) {
+        return Err(PyValueError::new_err("percentiles must be in [0, 100]"));
+    }
+    dispatch_dtype!("guessing_entropy", scores, [f32, f64], |T| {
+        let scores = scores.downcast_into::<PyArray2<T>>()?.readonly();
+        let scores = scores.as_array();
+        if key >= scores.ncols() {
+            return Err(PyValueError::new_err(format!(
+                "key {key} out of range for {} hypotheses",
+                scores.ncols()
+            )));
+        }
+        if scores.iter().any(|x| x.is_nan()) {
+            return Err(PyValueError::new_err("NaN in scores"));
+        }
+        let checkpoints = checkpoints_or_default(
+            checkpoints
+                .clone()
+                .or_else(|| Some((1..=scores.nrows()).collect())),
+            scores.nrows(),
+        )?;
+        let run = |i: usize| {
+            let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(seed, i as u64));
+            attack_ranks(scores, key, &checkpoints, &mut rng)
+        };
+        let ranks: Vec<Vec<usize>> = if parallel {
+            (0..n_experiments).into_par_iter().map(run).collect()
+        } else {
+            (0..n_experiments).map(run).collect()
+        };
+
+        let n = n_experiments as f64;
+        let mut ge = Array1::zeros(checkpoints.len());
+        let mut sr = Array1::zeros(checkpoints.len());
+        let mut rank_percentiles = Array2::zeros((percentiles.len(), checkpoints.len()));
+        for j in 0..checkpoints.len() {
+            let mut r: Vec<f64> = ranks.iter().map(|r| r[j] as f64).collect();
+            ge[j] = r.iter().sum::<f64>() / n;
+            sr[j] = r.iter().filter(|&&r| r < sr_order as f64).count() as f64 / n;
+            r.sort_by(f64::total_cmp);
+            for (k, &p) in percentiles.iter().enumerate() {
+                rank_percentiles[(k, j)] = percentile(&r, p);
+            }
+        }
+        Ok((
+            ge.into_pyarray_bound(py),
+            sr.into_pyarray_bound(py),
+            rank_percentiles.into_pyarray_bound(py),
+        ))
+    })
+}
+
+/// Quantized subkey scores and their convolution, for rank estimation and key
+/// enumeration (Glowacz et al., "Simpler and More Efficient Rank Estimation
+/// for Side-Channel Security Assessment", FSE 2015).
+struct ScoreHistograms {
+    /// Values of each subkey in each bin.
+    values: Vec<Vec<Vec<u32>>>,
+    /// Bin of each value of each subkey.
+    value_bins: Vec<Vec<usize>>,
+    /// `conv[i]`: histogram of the sum of bins of the first `i` subkeys.
+    conv: Vec<Vec<f64>>,
+}
+
+impl ScoreHistograms {
+    fn new(tables: &[ArrayView1<f64>], bins: usize) -> Self {
+        let finite = || {
+            tables
+                .iter()
+                .flat_map(|t| t.iter())
+                .filter(|x| x.is_finite())
+        };
+        let lo = finite().copied().fold(f64::INFINITY, f64::min);
+        let hi = finite().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = if hi > lo {
+            (hi - lo) / bins as f64
+        } else {
+            1.0
+        };
+        // -inf scores fall in the first bin.
+        let bin = |s: f64| (((s - lo) / width) as usize).min(bins - 1);
+
+        let mut values = Vec::with_capacity(tables.len());
+        let mut value_bins = Vec::with_capacity(tables.len());
+        let mut conv = vec![vec![1.0]];
+        for table in tables {
+            let mut v = vec![Vec::new(); bins];
+            let b: Vec<usize> = table.iter().map(|&s| bin(s)).collect();
+            b.iter().enumerate().for_each(|(x, &b)| v[b].push(x as u32));
+            // Histograms of subkeys are sparse (at most one bin per value).
+            let prev = conv.last().unwrap();
+            let mut out = vec![0.0; prev.len() + bins - 1];
+            for (j, vj) in v.iter().enumerate().filter(|(_, vj)| !vj.is_empty()) {
+                let c = vj.len() as f64;
+                out[j..]
+                    .iter_mut()
+                    .zip(prev)
+                    .for_each(|(o, &x)| *o += x * c);
+            }
+            conv.push(out);
+            values.push(v);
+            value_bins.push(b);
+        }
+        ScoreHistograms {
+            values,
+            value_bins,
+            conv,
+        }
+    }
+
+    fn n_subkeys(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Histogram of the sum of bins of all subkeys.
+    fn full(&self) -> &[f64] {
+        self.conv.last().unwrap()
+    }
+
+    /// Number of keys with a strictly better score (lower bound) and number
+    /// of keys whose score may be better or equal (upper bound, which counts
+    /// the key itself).
+    fn rank_bounds(&self, key: &[usize]) -> (f64, f64) {
+        let key_bin: usize = key.iter().zip(&self.value_bins).map(|(&k, b)| b[k]).sum();
+        // Binning shifts the score of a key by less than one bin per subkey.
+        let (n, hist) = (self.n_subkeys(), self.full());
+        let lower = hist[(key_bin + n).min(hist.len())..].iter().sum();
+        let upper = hist[(key_bin + 1).saturating_sub(n)..].iter().sum();
+        (lower, upper)
+    }
+
+    /// Call `f` on each key of the first `i` subkeys whose bins sum to `b`.
+    fn for_each_key(
+        &self,
+        i: usize,
+        b: usize,
+        key: &mut [u32],
+        f: &mut dyn FnMut(&[u32]) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        if i == 0 {
+            return f(key);
+        }
+        let (sub, prev) = (i - 1, &self.conv[i - 1]);
+        for (j, values) in self.values[sub].iter().enumerate().take(b + 1) {
+            if values.is_empty() || prev.get(b - j).is_none_or(|&c| c == 0.0) {
+                continue;
+            }
+            for &v in values {
+                key[sub] = v;
+                self.for_each_key(sub, b - j, key, f)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Call `f` on all keys, by decreasing bin (i.e., decreasing score up to
+    /// the quantization).
+    fn enumerate(&self, f: &mut dyn FnMut(&[u32]) -> ControlFlow<()>) -> ControlFlow<()> {
+        let mut key = vec![0; self.n_subkeys()];
+        for (b, &c) in self.full().iter().enumerate().rev() {
+            if c != 0.0 {
+                self.for_each_key(self.n_subkeys(), b, &mut key, f)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Check that there is a score table per subkey and no NaN score.
+fn check_score_tables(tables: &[ArrayView1<f64>], bins: usize) -> PyResult<()> {
+    if bins == 0 {
+        return Err(PyValueError::new_err("bins must be positive"));
+    }
+    for (i, t) in tables.iter().enumerate() {
+        if t.is_empty() {
+            return Err(PyValueError::new_err(format!(
+                "empty scores for subkey {i}"
+            )));
+        }
+        if t.iter().any(|x| x.is_nan()) {
+            return Err(PyValueError::new_err(format!(
+                "NaN in scores of subkey {i}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Bounds on the rank of a full key from independent subkey scores, in log2.
+///
+/// `scores` is a list of tables (e.g., an array of shape `(16, 256)` for the
+/// AES key bytes), the table `i` containing the log-probability of each value
+/// of subkey `i`. Subkeys may have different sizes (e.g., 8-bit and 16-bit
+/// subkeys). `key` contains the value of each subkey of the correct key.
+///
+/// Scores are quantized in `bins` bins of equal width, a larger value gives
+/// tighter bounds. Returns `(log2_lower, log2_upper)`, where the rank of the
+/// key (1 for the best key) is between `2**log2_lower` and `2**log2_upper`.
+#[pyfunction]
+#[pyo3(signature = (scores, key, *, bins=4096))]
+pub fn key_rank_bounds(
+    scores: Vec<PyReadonlyArray1<f64>>,
+    key: Vec<usize>,
+    bins: usize,
+) -> PyResult<(f64, f64)> {
+    let tables: Vec<_> = scores.iter().map(|s| s.as_array()).collect();
+    check_score_tables(&tables, bins)?;
+    if tables.len() != key.len() {
+        return Err(PyValueError::new_err(format!(
+            "got {} score tables but {} subkeys",
+            tables.len(),
+            key.len()
+        )));
+    }
+    for (i, (t, &k)) in tables.iter().zip(&key).enumerate() {
+        if k >= t.len() {
+            return Err(PyValueError::new_err(format!(
+                "subkey {i} value {k} out of range for a table of {} scores",
+                t.len()
+            )));
+        }
+    }
+    let (lower, upper) = ScoreHistograms::new(&tables, bins).rank_bounds(&key);
+    Ok(((lower + 1.0).log2(), upper.log2()))
+}
+
+/// Test of candidate keys, given as subkey values.
+enum KeyTest<'py> {
+    /// Encryption of a known plaintext/ciphertext pair, subkeys being the
+    /// big-endian encoding of `bytes[i]` key bytes.
+    Aes {
+        plaintext: Block,
+        ciphertext: Block,
+        bytes: Vec<usize>,
+    },
+    /// Python callback, returning the index of the correct key in a batch.
+    Callback(Bound<'py, PyAny>),
+}
+
+impl KeyTest<'_> {
+    /// Index of the first correct key of the batch (of `n` subkeys per key).
+    fn check(&self, batch: &[u32], n: usize, parallel: bool) -> PyResult<Option<usize>> {
+        let count = batch.len() / n;
+        match self {
+            KeyTest::Aes {
+                plaintext,
+                ciphertext,
+                bytes,
+            } => {
+                let is_key = |key: &[u32]| {
+                    let key: Vec<u8> = key
+                        .iter()
+                        .zip(bytes)
+                        .flat_map(|(&v, &m)| v.to_be_bytes()[4 - m..].to_vec())
+                        .collect();
+                    Aes::new(&key).is_ok_and(|aes| aes.encrypt(plaintext) == *ciphertext)
+                };
+                Ok(if parallel {
+                    batch.par_chunks(n).position_first(is_key)
+                } else {
+                    batch.chunks(n).position(is_key)
+                })
+            }
+            KeyTest::Callback(f) => {
+                let keys = Array2::from_shape_vec((count, n), batch.to_vec()).unwrap();
+                let index: Option<usize> =
+                    f.call1((keys.into_pyarray_bound(f.py()),))?.extract()?;
+                if let Some(i) = index.filter(|&i| i >= count) {
+                    return Err(PyValueError::new_err(format!(
+                        "key_test returned index {i} for a batch of {count} keys"
+                    )));
+                }
+                Ok(index)
+            }
+        }
+    }
+}
+
+/// State of a key enumeration, testing candidates by batches.
+struct Enumeration<'a, 'py> {
+    test: &'a KeyTest<'py>,
+    n_subkeys: usize,
+    batch_size: usize,
+    budget: u64,
+    parallel: bool,
+    batch: Vec<u32>,
+    tested: u64,
+    result: PyResult<Option<Vec<u32>>>,
+}
+
+impl Enumeration<'_, '_> {
+    fn push(&mut self, key: &[u32]) -> ControlFlow<()> {
+        self.batch.extend_from_slice(key);
+        let pending = (self.batch.len() / self.n_subkeys) as u64;
+        if pending == self.batch_size as u64 || self.tested + pending == self.budget {
+            self.flush()
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn flush(&mut self) -> ControlFlow<()> {
+        let n = self.n_subkeys;
+        if self.batch.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        match self.test.check(&self.batch, n, self.parallel) {
+            Ok(Some(i)) => {
+                self.tested += i as u64 + 1;
+                self.result = Ok(Some(self.batch[i * n..(i + 1) * n].to_vec()));
+                ControlFlow::Break(())
+            }
+            Ok(None) => {
+                self.tested += (self.batch.len() / n) as u64;
+                self.batch.clear();
+                if self.tested >= self.budget {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+            Err(e) => {
+                self.result = Err(e);
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
+
+/// Enumerate full keys by decreasing likelihood, until one passes a test.
+///
+/// `scores` contains a log-probability table per subkey (as in
+/// `key_rank_bounds`). Keys are enumerated with the histogram method, so
+/// their order is exact up to the quantization in `bins` bins.
+///
+/// Candidates are tested by batches of `batch_size` keys, either:
+///
+/// - against a known `plaintext`/`ciphertext` pair with the native AES (in
+///   parallel with `parallel`). Table sizes must be powers of 256, the key
+///   bytes being the big-endian encoding of each subkey.
+/// - with `key_test(keys)`, where `keys` has shape `(batch, n_subkeys)`,
+///   which returns the index of the correct key in the batch or `None`.
+///
+/// The enumeration stops after testing `budget` keys. Returns
+/// `(key, n_tested)`, where `key` contains the subkey values of the correct
+/// key (or `None` if it was not found).
+#[pyfunction]
+#[pyo3(signature = (scores, *, plaintext=None, ciphertext=None, key_test=None, budget=1 << 32, bins=4096, batch_size=65536, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn enumerate_keys<'py>(
+    py: Python<'py>,
+    scores: Vec<PyReadonlyArray1<f64>>,
+    plaintext: Option<PyReadonlyArray1<u8>>,
+    ciphertext: Option<PyReadonlyArray1<u8>>,
+    key_test: Option<Bound<'py, PyAny>>,
+    budget: u64,
+    bins: usize,
+    batch_size: usize,
+    parallel: bool,
+) -> PyResult<(Option<Bound<'py, PyArray1<u32>>>, u64)> {
+    let tables: Vec<_> = scores.iter().map(|s| s.as_array()).collect();
+    check_score_tables(&tables, bins)?;
+    if tables.is_empty() || batch_size == 0 {
+        return Err(PyValueError::new_err(
+            "at least one subkey and a positive batch_size are required",
+        ));
+    }
+    let to_block = |a: PyReadonlyArray1<u8>| -> PyResult<Block> {
+        let a = a.as_array();
+        assert_shape_match!([16] => a);
+        Ok(core::array::from_fn(|i| a[i]))
+    };
+    let test = match (plaintext, ciphertext, key_test) {
+        (Some(p), Some(c), None) => {
+            let bytes = tables
+                .iter()
+                .map(|t| match t.len() {
+                    0x100 => Ok(1),
+                    0x1_0000 => Ok(2),
+                    0x100_0000 => Ok(3),
+                    n => Err(PyValueError::new_err(format!(
+                        "cannot map a subkey with {n} values to AES key bytes"
+                    ))),
+                })
+                .collect::<PyResult<Vec<usize>>>()?;
+            let key_len: usize = bytes.iter().sum();
+            Aes::new(&vec![0; key_len]).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            KeyTest::Aes {
+                plaintext: to_block(p)?,
+                ciphertext: to_block(c)?,
+                bytes,
+            }
+        }
+        (None, None, Some(f)) => KeyTest::Callback(f),
+        _ => {
+            return Err(PyValueError::new_err(
+                "either plaintext and ciphertext, or key_test must be given",
+            ))
+        }
+    };
+    if budget == 0 {
+        return Ok((None, 0));
+    }
+    let hist = ScoreHistograms::new(&tables, bins);
+    let mut state = Enumeration {
+        test: &test,
+        n_subkeys: tables.len(),
+        batch_size,
+        budget,
+        parallel,
+        batch: Vec::new(),
+        tested: 0,
+        result: Ok(None),
+    };
+    if hist.enumerate(&mut |key| state.push(key)).is_continue() {
+        let _ = state.flush();
+    }
+    let key = state.result?.map(|k| k.into_pyarray_bound(py));
+    Ok((key, state.tested))
+}
@@ -27,10 +27,23 @@
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
 
-use numpy::{PyArray1, PyArrayMethods};
+use numpy::ndarray::{Array2, ArrayView1, ArrayView2, Axis};
+use numpy::{
+    IntoPyArray, PyArray1, PyArray2, PyArray4, PyArrayDyn, PyArrayMethods, PyReadonlyArray1,
+    PyReadonlyArray2, ToPyArray,
+};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use rand_core::RngCore;
+use rayon::prelude::*;
 
+use crate::{assert_shape_match, dispatch_dtype};
 use secbench_crypto as sb;
+use secbench_crypto::aes::{Aes, AesError, AesOp, Block, Intermediate};
+use secbench_crypto::crc::{CrcError, CrcParams};
+use secbench_crypto::keccak::{self, KeccakError, KeccakState, KeccakStep};
+use secbench_crypto::labels::{LabelGenerator, Selection};
+use secbench_crypto::masked_aes::{MaskedAesSimulator, SimulatorConfig, SimulatorError};
 
 #[pyclass]
 #[derive(Clone)]
@@ -52,9 +65,769 @@ impl Pcg32 {
         Ok(self.inner.generate())
     }
 
-    pub fn fill(&mut self, dst: &Bound<PyArray1<u64>>) -> PyResult<()> {
-        let mut dst_view = unsafe { dst.as_array_mut() };
-        dst_view.iter_mut().for_each(|x| *x = self.inner.generate());
+    /// Skip `delta` outputs (a negative `delta` goes backward).
+    pub fn advance(&mut self, delta: i128) {
+        self.inner.advance(delta as u64);
+    }
+
+    /// Raw generator state `(state, inc)`, which can be restored with
+    /// `set_state`.
+    pub fn get_state(&self) -> (u64, u64) {
+        self.inner.state()
+    }
+
+    pub fn set_state(&mut self, state: (u64, u64)) -> PyResult<()> {
+        self.inner = sb::Pcg32::from_state(state.0, state.1)
+            .ok_or_else(|| PyValueError::new_err("invalid Pcg32 state: inc must be odd"))?;
+        Ok(())
+    }
+
+    pub fn __getstate__(&self) -> (u64, u64) {
+        self.get_state()
+    }
+
+    pub fn __setstate__(&mut self, state: (u64, u64)) -> PyResult<()> {
+        self.set_state(state)
+    }
+
+    pub fn __getnewargs__(&self) -> (u64, u64) {
+        (0, 0)
+    }
+
+    pub fn __copy__(&self) -> Self {
+        Pcg32 {
+            inner: self.inner.clone(),
+        }
+    }
+
+    pub fn __deepcopy__(&self, _memo: Bound<PyAny>) -> Self {
+        self.__copy__()
+    }
+
+    /// Fill a contiguous array of u8, u16, u32, u64, f32 or f64 values.
+    ///
+    /// Integers take one (truncated) output per value, or several values per
+    /// output in little-endian order when `packed` is set. Floats are uniform
+    /// in `[0, 1)`. With `parallel`, chunks are filled by jumping ahead of the
+    /// stream, which gives the same result as a sequential fill.
+    #[pyo3(signature = (dst, *, packed=false, parallel=false))]
+    pub fn fill(&mut self, dst: Bound<PyAny>, packed: bool, parallel: bool) -> PyResult<()> {
+        dispatch_dtype!("fill", dst, [u8, u16, u32, u64, f32, f64], |T| {
+            let dst = dst.downcast_into::<PyArrayDyn<T>>()?;
+            let mut dst = dst.try_readwrite()?;
+            let dst = dst
+                .as_slice_mut()
+                .map_err(|_| PyValueError::new_err("the destination array must be contiguous"))?;
+            pcg32_fill(&mut self.inner, dst, packed, parallel);
+            Ok(())
+        })
+    }
+}
+
+/// Fill `dst` and leave `rng` after the last consumed output.
+fn pcg32_fill<T: sb::RngValue + Send>(
+    rng: &mut sb::Pcg32,
+    dst: &mut [T],
+    packed: bool,
+    parallel: bool,
+) {
+    const MIN_CHUNK: usize = 1 << 14;
+    let per_output = if packed { T::PACKED } else { 1 };
+    let fill = |rng: &mut sb::Pcg32, dst: &mut [T]| {
+        if packed {
+            rng.fill_packed(dst)
+        } else {
+            rng.fill(dst)
+        }
+    };
+    if !parallel || dst.len() <= MIN_CHUNK {
+        fill(rng, dst);
+        return;
+    }
+    // Chunks must start on an output boundary.
+    let chunk = (dst.len() / rayon::current_num_threads())
+        .max(MIN_CHUNK)
+        .next_multiple_of(per_output);
+    dst.par_chunks_mut(chunk).enumerate().for_each(|(i, dst)| {
+        let mut rng = rng.clone();
+        rng.advance((i * chunk / per_output) as u64);
+        fill(&mut rng, dst);
+    });
+    rng.advance(dst.len().div_ceil(per_output) as u64);
+}
+
+#[derive(Clone)]
+enum PrngImpl {
+    Pcg32(sb::Pcg32),
+    Xorshift32(sb::Xorshift32),
+    Xoshiro128StarStar(sb::Xoshiro128StarStar),
+    GaloisLfsr(sb::GaloisLfsr),
+    ChaCha20(sb::ChaCha20Rng),
+}
+
+impl PrngImpl {
+    fn rng(&mut self) -> &mut dyn RngCore {
+        match self {
+            PrngImpl::Pcg32(rng) => rng,
+            PrngImpl::Xorshift32(rng) => rng,
+            PrngImpl::Xoshiro128StarStar(rng) => rng,
+            PrngImpl::GaloisLfsr(rng) => rng,
+            PrngImpl::ChaCha20(rng) => rng,
+        }
+    }
+
+    fn state(&self) -> Vec<u64> {
+        match self {
+            PrngImpl::Pcg32(rng) => {
+                let (state, inc) = rng.state();
+                vec![state, inc]
+            }
+            PrngImpl::Xorshift32(rng) => vec![rng.state() as u64],
+            PrngImpl::Xoshiro128StarStar(rng) => rng.state().map(u64::from).to_vec(),
+            PrngImpl::GaloisLfsr(rng) => vec![rng.taps(), rng.state()],
+            PrngImpl::ChaCha20(rng) => {
+                let mut words: Vec<u64> = [&rng.key()[..], &rng.nonce()[..]]
+                    .concat()
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as u64)
+                    .collect();
+                words.push(rng.word_pos());
+                words
+            }
+        }
+    }
+
+    fn from_state(name: &str, words: &[u64]) -> PyResult<Self> {
+        let invalid = || PyValueError::new_err(format!("invalid state for Prng.{name}: {words:?}"));
+        let u32_words = |words: &[u64]| -> PyResult<Vec<u32>> {
+            words
+                .iter()
+                .map(|&w| u32::try_from(w).map_err(|_| invalid()))
+                .collect()
+        };
+        match (name, words) {
+            ("pcg32", &[state, inc]) => sb::Pcg32::from_state(state, inc)
+                .map(PrngImpl::Pcg32)
+                .ok_or_else(|| PyValueError::new_err("invalid Pcg32 state: inc must be odd")),
+            ("xorshift32", [_]) => Ok(PrngImpl::Xorshift32(sb::Xorshift32::new(
+                u32_words(words)?[0],
+            ))),
+            ("xoshiro128starstar", [_, _, _, _]) => {
+                let state = u32_words(words)?.try_into().unwrap();
+                Ok(PrngImpl::Xoshiro128StarStar(sb::Xoshiro128StarStar::new(
+                    state,
+                )))
+            }
+            ("galois_lfsr", &[taps, state]) => {
+                Ok(PrngImpl::GaloisLfsr(sb::GaloisLfsr::new(taps, state)))
+            }
+            // Key words, nonce words and keystream position (2^36 words).
+            ("chacha20", [.., pos]) if words.len() == 12 && *pos < 1 << 36 => {
+                let bytes: Vec<u8> = u32_words(&words[..11])?
+                    .iter()
+                    .flat_map(|w| w.to_le_bytes())
+                    .collect();
+                let key = bytes[..32].try_into().unwrap();
+                let nonce = bytes[32..].try_into().unwrap();
+                let mut rng = sb::ChaCha20Rng::new(key, nonce, 0);
+                rng.set_word_pos(*pos);
+                Ok(PrngImpl::ChaCha20(rng))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Common wrapper for the PRNGs used by target firmwares.
+///
+/// Instances are created with one of the static constructors (e.g.,
+/// `Prng.xorshift32(1)`).
+#[pyclass]
+#[derive(Clone)]
+pub struct Prng {
+    inner: PrngImpl,
+}
+
+#[pymethods]
+impl Prng {
+    #[staticmethod]
+    pub fn pcg32(state: u64, inc: u64) -> Self {
+        let seed = sb::Pcg32Seed::from_state_inc(state, inc);
+        Prng {
+            inner: PrngImpl::Pcg32(sb::Pcg32::new(seed)),
+        }
+    }
+
+    #[staticmethod]
+    pub fn xorshift32(state: u32) -> Self {
+        Prng {
+            inner: PrngImpl::Xorshift32(sb::Xorshift32::new(state)),
+        }
+    }
+
+    #[staticmethod]
+    pub fn xoshiro128starstar(state: [u32; 4]) -> Self {
+        Prng {
+            inner: PrngImpl::Xoshiro128StarStar(sb::Xoshiro128StarStar::new(state)),
+        }
+    }
+
+    #[staticmethod]
+    pub fn galois_lfsr(taps: u64, state: u64) -> Self {
+        Prng {
+            inner: PrngImpl::GaloisLfsr(sb::GaloisLfsr::new(taps, state)),
+        }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (key, nonce=None, counter=0))]
+    pub fn chacha20(key: &[u8], nonce: Option<&[u8]>, counter: u32) -> PyResult<Self> {
+        let key = key
+            .try_into()
+            .map_err(|_| PyValueError::new_err("ChaCha20 key must be 32 bytes"))?;
+        let nonce = nonce
+            .unwrap_or(&[0; 12])
+            .try_into()
+            .map_err(|_| PyValueError::new_err("ChaCha20 nonce must be 12 bytes"))?;
+        Ok(Prng {
+            inner: PrngImpl::ChaCha20(sb::ChaCha20Rng::new(key, nonce, counter)),
+        })
+    }
+
+    #[getter]
+    pub fn name(&self) -> &'static str {
+        match self.inner {
+            PrngImpl::Pcg32(_) => "pcg32",
+            PrngImpl::Xorshift32(_) => "xorshift32",
+            PrngImpl::Xoshiro128StarStar(_) => "xoshiro128starstar",
+            PrngImpl::GaloisLfsr(_) => "galois_lfsr",
+            PrngImpl::ChaCha20(_) => "chacha20",
+        }
+    }
+
+    /// Next 32-bit output.
+    pub fn generate(&mut self) -> u32 {
+        self.inner.rng().next_u32()
+    }
+
+    /// Fill a contiguous array of u8, u16, u32, u64, f32 or f64 values.
+    ///
+    /// This has the same semantics as `Pcg32.fill`.
+    #[pyo3(signature = (dst, *, packed=false))]
+    pub fn fill(&mut self, dst: Bound<PyAny>, packed: bool) -> PyResult<()> {
+        dispatch_dtype!("fill", dst, [u8, u16, u32, u64, f32, f64], |T| {
+            let dst = dst.downcast_into::<PyArrayDyn<T>>()?;
+            let mut dst = dst.try_readwrite()?;
+            let dst = dst
+                .as_slice_mut()
+                .map_err(|_| PyValueError::new_err("the destination array must be contiguous"))?;
+            if packed {
+                sb::fill_values_packed(self.inner.rng(), dst)
+            } else {
+                sb::fill_values(self.inner.rng(), dst)
+            }
+            Ok(())
+        })
+    }
+
+    /// Generator name and raw state words, which can be restored with
+    /// `set_state` or `Prng.from_state`.
+    ///
+    /// The state words are `(state, inc)` for pcg32, the register for
+    /// xorshift32 and xoshiro128starstar, `(taps, state)` for galois_lfsr, and
+    /// the 8 key words, the 3 nonce words and the keystream position (in
+    /// 32-bit words) for chacha20.
+    pub fn get_state(&self) -> (&'static str, Vec<u64>) {
+        (self.name(), self.inner.state())
+    }
+
+    pub fn set_state(&mut self, state: (String, Vec<u64>)) -> PyResult<()> {
+        self.inner = PrngImpl::from_state(&state.0, &state.1)?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[staticmethod]
+    pub fn from_state(state: (String, Vec<u64>)) -> PyResult<Self> {
+        Ok(Prng {
+            inner: PrngImpl::from_state(&state.0, &state.1)?,
+        })
+    }
+
+    pub fn __getstate__(&self) -> (&'static str, Vec<u64>) {
+        self.get_state()
+    }
+
+    pub fn __setstate__(&mut self, state: (String, Vec<u64>)) -> PyResult<()> {
+        self.set_state(state)
+    }
+
+    /// `Prng` has no constructor, so instances are unpickled with `from_state`.
+    pub fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyAny>, ((&'static str, Vec<u64>),))> {
+        let from_state = slf.get_type().getattr("from_state")?;
+        Ok((from_state, (slf.borrow().get_state(),)))
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    pub fn __deepcopy__(&self, _memo: Bound<PyAny>) -> Self {
+        self.clone()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Prng.{}(...)", self.name())
+    }
+}
+
+fn aes_error(e: AesError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Compute each row of the output with `f(row_index, input_row, output_row)`.
+fn map_rows<T, U, E, F>(
+    input: ArrayView2<T>,
+    out_cols: usize,
+    parallel: bool,
+    f: F,
+) -> Result<Array2<U>, E>
+where
+    T: Sync,
+    U: Clone + Default + Send,
+    E: Send,
+    F: Fn(usize, ArrayView1<T>, &mut [U]) -> Result<(), E> + Sync,
+{
+    let n = input.nrows();
+    if out_cols == 0 {
+        return Ok(Array2::default((n, 0)));
+    }
+    let mut out = vec![U::default(); n * out_cols];
+    let process = |(i, dst): (usize, &mut [U])| f(i, input.row(i), dst);
+    if parallel {
+        out.par_chunks_mut(out_cols)
+            .enumerate()
+            .try_for_each(process)?;
+    } else {
+        out.chunks_mut(out_cols).enumerate().try_for_each(process)?;
+    }
+    Ok(Array2::from_shape_vec((n, out_cols), out).unwrap())
+}
+
+/// Apply `f` on each row of `blocks`, with the cipher of the matching row of `keys`.
+///
+/// `keys` has either one row per block, or a single row shared by all blocks.
+fn aes_map_blocks<F>(
+    blocks: ArrayView2<u8>,
+    keys: ArrayView2<u8>,
+    parallel: bool,
+    f: F,
+) -> PyResult<Array2<u8>>
+where
+    F: Fn(&Aes, &Block) -> Result<Block, AesError> + Sync,
+{
+    assert_shape_match!([_, 16] => blocks);
+    if keys.nrows() != 1 {
+        assert_shape_match!([blocks.nrows(), _] => keys);
+    }
+    let ciphers = keys
+        .rows()
+        .into_iter()
+        .map(|k| Aes::new(&k.to_vec()).map_err(aes_error))
+        .collect::<PyResult<Vec<_>>>()?;
+    map_rows(blocks, 16, parallel, |i, src, dst| {
+        let block: Block = core::array::from_fn(|j| src[j]);
+        let cipher = &ciphers[if ciphers.len() == 1 { 0 } else { i }];
+        dst.copy_from_slice(&f(cipher, &block)?);
+        Ok(())
+    })
+    .map_err(aes_error)
+}
+
+/// Encrypt a batch of blocks with AES-128/192/256.
+#[pyfunction]
+#[pyo3(signature = (plaintexts, keys, *, parallel=false))]
+pub fn aes_encrypt<'py>(
+    plaintexts: PyReadonlyArray2<'py, u8>,
+    keys: PyReadonlyArray2<'py, u8>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    let out = aes_map_blocks(plaintexts.as_array(), keys.as_array(), parallel, |c, b| {
+        Ok(c.encrypt(b))
+    })?;
+    Ok(out.to_pyarray_bound(plaintexts.py()))
+}
+
+/// Decrypt a batch of blocks with AES-128/192/256.
+#[pyfunction]
+#[pyo3(signature = (ciphertexts, keys, *, parallel=false))]
+pub fn aes_decrypt<'py>(
+    ciphertexts: PyReadonlyArray2<'py, u8>,
+    keys: PyReadonlyArray2<'py, u8>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    let out = aes_map_blocks(ciphertexts.as_array(), keys.as_array(), parallel, |c, b| {
+        Ok(c.decrypt(b))
+    })?;
+    Ok(out.to_pyarray_bound(ciphertexts.py()))
+}
+
+/// Intermediate states of AES encryptions.
+///
+/// `target` is an operation name (`"sub_bytes"`, `"shift_rows"`,
+/// `"mix_columns"` or `"add_round_key"`), which requires a `round`, or
+/// `"last_round_hd"`.
+#[pyfunction]
+#[pyo3(signature = (plaintexts, keys, *, target, round=None, parallel=false))]
+pub fn aes_intermediates<'py>(
+    plaintexts: PyReadonlyArray2<'py, u8>,
+    keys: PyReadonlyArray2<'py, u8>,
+    target: &str,
+    round: Option<usize>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    let target = match (target, round) {
+        ("last_round_hd", _) => Intermediate::LastRoundHd,
+        (op, Some(round)) => Intermediate::Op {
+            round,
+            op: op.parse::<AesOp>().map_err(aes_error)?,
+        },
+        (op, None) => {
+            return Err(PyValueError::new_err(format!(
+                "a round is required for target {op:?}"
+            )))
+        }
+    };
+    let out = aes_map_blocks(plaintexts.as_array(), keys.as_array(), parallel, |c, b| {
+        c.intermediate(b, target)
+    })?;
+    Ok(out.to_pyarray_bound(plaintexts.py()))
+}
+
+fn keccak_error(e: KeccakError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn keccak_state(lanes: ArrayView1<u64>) -> KeccakState {
+    core::array::from_fn(|i| lanes[i])
+}
+
+/// Apply Keccak-f[1600] on a batch of `(n, 25)` states.
+#[pyfunction]
+#[pyo3(signature = (states, *, parallel=false))]
+pub fn keccak_f1600<'py>(
+    states: PyReadonlyArray2<'py, u64>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u64>>> {
+    let py = states.py();
+    let states = states.as_array();
+    assert_shape_match!([_, 25] => states);
+    let out = map_rows(states, 25, parallel, |_, src, dst| -> PyResult<()> {
+        let mut a = keccak_state(src);
+        keccak::keccak_f1600(&mut a);
+        dst.copy_from_slice(&a);
+        Ok(())
+    })?;
+    Ok(out.to_pyarray_bound(py))
+}
+
+/// Intermediate states of Keccak-f[1600] on a batch of `(n, 25)` states.
+///
+/// Returns an array of shape `(n, len(rounds), len(steps), 25)` with the
+/// lanes after each requested step. By default, all rounds and all steps
+/// (`"theta"`, `"rho"`, `"pi"`, `"chi"`, `"iota"`) are returned.
+#[pyfunction]
+#[pyo3(signature = (states, *, rounds=None, steps=None, parallel=false))]
+pub fn keccak_f1600_intermediates<'py>(
+    states: PyReadonlyArray2<'py, u64>,
+    rounds: Option<Vec<usize>>,
+    steps: Option<Vec<String>>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray4<u64>>> {
+    let py = states.py();
+    let states = states.as_array();
+    assert_shape_match!([_, 25] => states);
+    let rounds = rounds.unwrap_or_else(|| (0..keccak::ROUNDS).collect());
+    if let Some(r) = rounds.iter().find(|&&r| r >= keccak::ROUNDS) {
+        return Err(PyValueError::new_err(format!(
+            "invalid Keccak-f[1600] round: {r}"
+        )));
+    }
+    let steps = match steps {
+        Some(s) => s
+            .iter()
+            .map(|s| s.parse::<KeccakStep>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(keccak_error)?,
+        None => KeccakStep::ALL.to_vec(),
+    };
+    let (n_rounds, n_steps) = (rounds.len(), steps.len());
+    let out = map_rows(
+        states,
+        n_rounds * n_steps * 25,
+        parallel,
+        |_, src, dst| -> PyResult<()> {
+            let mut a = keccak_state(src);
+            keccak::keccak_f1600_traced(&mut a, |round, step, lanes| {
+                for (i, _) in rounds.iter().enumerate().filter(|(_, &r)| r == round) {
+                    for (j, _) in steps.iter().enumerate().filter(|(_, &s)| s == step) {
+                        let offset = (i * n_steps + j) * 25;
+                        dst[offset..offset + 25].copy_from_slice(lanes);
+                    }
+                }
+            });
+            Ok(())
+        },
+    )?;
+    let n = out.nrows();
+    let out = out.into_shape((n, n_rounds, n_steps, 25)).unwrap();
+    Ok(out.to_pyarray_bound(py))
+}
+
+/// SHA-3 digests of each row of `messages`.
+#[pyfunction]
+#[pyo3(signature = (messages, *, bits=256, parallel=false))]
+pub fn sha3<'py>(
+    messages: PyReadonlyArray2<'py, u8>,
+    bits: usize,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    keccak::Sponge::sha3(bits).map_err(keccak_error)?;
+    let out = map_rows(messages.as_array(), bits / 8, parallel, |_, src, dst| {
+        let digest = keccak::sha3(&src.to_vec(), bits)?;
+        dst.copy_from_slice(&digest);
+        Ok(())
+    })
+    .map_err(keccak_error)?;
+    Ok(out.to_pyarray_bound(messages.py()))
+}
+
+/// SHAKE outputs of `output_len` bytes for each row of `messages`.
+#[pyfunction]
+#[pyo3(signature = (messages, *, output_len, security=128, parallel=false))]
+pub fn shake<'py>(
+    messages: PyReadonlyArray2<'py, u8>,
+    output_len: usize,
+    security: usize,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    keccak::Sponge::shake(security).map_err(keccak_error)?;
+    let out = map_rows(messages.as_array(), output_len, parallel, |_, src, dst| {
+        let out = keccak::shake(&src.to_vec(), security, output_len)?;
+        dst.copy_from_slice(&out);
+        Ok(())
+    })
+    .map_err(keccak_error)?;
+    Ok(out.to_pyarray_bound(messages.py()))
+}
+
+fn crc_error(e: CrcError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Convert a CRC parameter given as a Python integer, which may be negative or
+/// larger than 64 bits.
+fn crc_parameter(name: &str, value: i128) -> PyResult<u64> {
+    u64::try_from(value).map_err(|_| {
+        PyValueError::new_err(format!(
+            "CRC parameter {name}={value} is not a 64-bit unsigned integer"
+        ))
+    })
+}
+
+/// Parameterizable CRC (width, polynomial, reflections, init and xorout).
+///
+/// Raises `ValueError` when `poly`, `init` or `xorout` does not fit in
+/// `width` bits.
+#[pyclass]
+#[derive(Clone)]
+pub struct Crc {
+    inner: sb::crc::Crc,
+}
+
+#[pymethods]
+impl Crc {
+    #[new]
+    #[pyo3(signature = (width, poly, *, init=0, refin=false, refout=false, xorout=0))]
+    pub fn new(
+        width: u32,
+        poly: i128,
+        init: i128,
+        refin: bool,
+        refout: bool,
+        xorout: i128,
+    ) -> PyResult<Self> {
+        let params = CrcParams {
+            width,
+            poly: crc_parameter("poly", poly)?,
+            init: crc_parameter("init", init)?,
+            refin,
+            refout,
+            xorout: crc_parameter("xorout", xorout)?,
+        };
+        Ok(Crc {
+            inner: sb::crc::Crc::new(params).map_err(crc_error)?,
+        })
+    }
+
+    /// Create a common CRC from its name (e.g., `"crc32"`, `"crc16_xmodem"`).
+    #[staticmethod]
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        let params = CrcParams::from_name(name)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown CRC: {name:?}")))?;
+        Ok(Crc {
+            inner: sb::crc::Crc::new(params).map_err(crc_error)?,
+        })
+    }
+
+    /// CRC of each row of `messages`.
+    #[pyo3(signature = (messages, *, parallel=false))]
+    pub fn checksum<'py>(
+        &self,
+        messages: PyReadonlyArray2<'py, u8>,
+        parallel: bool,
+    ) -> PyResult<Bound<'py, PyArray1<u64>>> {
+        let out = map_rows(
+            messages.as_array(),
+            1,
+            parallel,
+            |_, src, dst| -> PyResult<()> {
+                dst[0] = self.inner.checksum(&src.to_vec());
+                Ok(())
+            },
+        )?;
+        Ok(out.remove_axis(Axis(1)).into_pyarray_bound(messages.py()))
+    }
+
+    /// Register after each byte of each row of `messages`.
+    ///
+    /// Values are stored as a table-driven implementation would: reflected
+    /// when `refin` is set, before the final reflection and `xorout`.
+    #[pyo3(signature = (messages, *, parallel=false))]
+    pub fn registers<'py>(
+        &self,
+        messages: PyReadonlyArray2<'py, u8>,
+        parallel: bool,
+    ) -> PyResult<Bound<'py, PyArray2<u64>>> {
+        let messages_view = messages.as_array();
+        let out = map_rows(
+            messages_view,
+            messages_view.ncols(),
+            parallel,
+            |_, src, dst| -> PyResult<()> {
+                self.inner.registers(&src.to_vec(), dst);
+                Ok(())
+            },
+        )?;
+        Ok(out.into_pyarray_bound(messages.py()))
+    }
+}
+
+/// Check that `data` with `n_cols` columns can be labeled with `selection`.
+pub(crate) fn check_selection_data(selection: Selection, n_cols: usize) -> PyResult<()> {
+    if selection.register_byte(0).is_some() && n_cols != 16 {
+        return Err(PyValueError::new_err(format!(
+            "selection {selection:?} requires 16-byte ciphertexts, data has {n_cols} columns"
+        )));
+    }
+    Ok(())
+}
+
+/// Key-hypothesis labels of AES bytes, for `CondMeanVar` or CPA.
+///
+/// `data` contains plaintexts (or ciphertexts for last round selections).
+/// `selection` is one of `"sbox_out"`, `"sbox_out_hw"`, `"sbox_out_hd_in"`,
+/// `"inv_sbox_out"` and `"inv_sbox_out_hd"` (which requires full 16-byte
+/// ciphertexts, see `Selection::register_byte`). By default, all bytes of
+/// `data` and all 256 guesses are used.
+///
+/// Returns an array of shape `(n_traces, len(bytes) * len(guesses))`, the label
+/// of `bytes[i]` with `guesses[j]` being in column `i * len(guesses) + j`.
+#[pyfunction]
+#[pyo3(signature = (data, *, selection, bytes=None, guesses=None, parallel=false))]
+pub fn aes_labels<'py>(
+    data: PyReadonlyArray2<'py, u8>,
+    selection: &str,
+    bytes: Option<Vec<usize>>,
+    guesses: Option<Vec<u8>>,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<u16>>> {
+    let selection: Selection = selection
+        .parse()
+        .map_err(|e: sb::labels::UnknownSelection| PyValueError::new_err(e.to_string()))?;
+    let data_view = data.as_array();
+    check_selection_data(selection, data_view.ncols())?;
+    let bytes = bytes.unwrap_or_else(|| (0..data_view.ncols()).collect());
+    if let Some(b) = bytes.iter().find(|&&b| b >= data_view.ncols()) {
+        return Err(PyValueError::new_err(format!(
+            "byte index {b} out of range for data with {} columns",
+            data_view.ncols()
+        )));
+    }
+    let guesses = guesses.unwrap_or_else(|| (0..=255).collect());
+    let gen = LabelGenerator::new(selection, bytes, guesses);
+    let out = map_rows(
+        data_view,
+        gen.n_targets(),
+        parallel,
+        |_, src, dst| -> PyResult<()> {
+            gen.fill(&src.to_vec(), dst);
+            Ok(())
+        },
+    )?;
+    Ok(out.into_pyarray_bound(data.py()))
+}
+
+/// Simulate the leakage of a Boolean-masked AES encryption for each plaintext.
+///
+/// `model` is one of `"identity"`, `"hw"` and `"hd"`, `noise` is the standard
+/// deviation of the Gaussian noise and `max_delay` the maximum number of
+/// dummy samples inserted before each operation. The trace `i` is generated
+/// with `Pcg32(seed, offset + i)`, so that batches are reproducible,
+/// including in parallel (use `offset` to continue a previous batch).
+///
+/// Returns an array of shape `(n_traces, n_samples)`, see
+/// `secbench_crypto::masked_aes` for the sample layout.
+#[pyfunction]
+#[pyo3(signature = (plaintexts, key, *, order=1, model="hw", noise=1.0, rounds=1, max_delay=0, seed=0, offset=0, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn aes_simulate_masked<'py>(
+    plaintexts: PyReadonlyArray2<'py, u8>,
+    key: PyReadonlyArray1<'py, u8>,
+    order: usize,
+    model: &str,
+    noise: f64,
+    rounds: usize,
+    max_delay: usize,
+    seed: u64,
+    offset: u64,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<f32>>> {
+    let plaintexts_view = plaintexts.as_array();
+    assert_shape_match!([_, 16] => plaintexts_view);
+    let config = SimulatorConfig {
+        order,
+        model: model.parse().map_err(simulator_error)?,
+        noise,
+        rounds,
+        max_delay,
+    };
+    let sim = MaskedAesSimulator::new(&key.as_array().to_vec(), config).map_err(simulator_error)?;
+    let out = map_rows(
+        plaintexts_view,
+        sim.n_samples(),
+        parallel,
+        |i, src, dst| -> PyResult<()> {
+            let block: Block = core::array::from_fn(|j| src[j]);
+            let seed = sb::Pcg32Seed::from_state_inc(seed, offset + i as u64);
+            sim.simulate(&block, &mut sb::Pcg32::new(seed), dst);
+            Ok(())
+        },
+    )?;
+    Ok(out.into_pyarray_bound(plaintexts.py()))
+}
+
+fn simulator_error(e: SimulatorError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
@@ -0,0 +1,156 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+//! Routing of Python calls to monomorphized implementations based on numpy dtypes.
+
+use numpy::{
+    dtype_bound, Element, PyArrayDescr, PyArrayDescrMethods, PyUntypedArray, PyUntypedArrayMethods,
+};
+use pyo3::prelude::*;
+
+/// Data type of a numpy array.
+pub fn array_dtype<'py>(array: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyArrayDescr>> {
+    Ok(array.downcast::<PyUntypedArray>()?.dtype())
+}
+
+/// Output dtype of a transform.
+///
+/// This is the dtype of `output` if given, then `dtype`. Otherwise, the output is
+/// `float64` for `float64` inputs and `float32` for any other input.
+pub fn output_dtype<'py>(
+    output: Option<&Bound<'py, PyAny>>,
+    dtype: Option<&Bound<'py, PyAny>>,
+    input_dtype: &Bound<'py, PyArrayDescr>,
+) -> PyResult<Bound<'py, PyArrayDescr>> {
+    let py = input_dtype.py();
+    match (output, dtype) {
+        (Some(output), _) => array_dtype(output),
+        (None, Some(dtype)) => PyArrayDescr::new_bound(py, dtype),
+        (None, None) if is_dtype::<f64>(input_dtype) => Ok(dtype_bound::<f64>(py)),
+        (None, None) => Ok(dtype_bound::<f32>(py)),
+    }
+}
+
+pub fn is_dtype<T: Element>(dtype: &Bound<PyArrayDescr>) -> bool {
+    dtype.is_equiv_to(&dtype_bound::<T>(dtype.py()))
+}
+
+pub fn dtype_name<T: Element>(py: Python) -> String {
+    dtype_bound::<T>(py).to_string()
+}
+
+/// Define a `#[pyfunction]` that runs a transform on the monomorphization matching
+/// the dtypes of its input and output arrays.
+///
+/// The function takes an optional `output` array, an `input` array, the extra
+/// arguments given, and an optional trailing `dtype` (see [`output_dtype`]). In
+/// `$body`, `$output` and `$input` are typed arrays and `$Dst` is the output
+/// element type. Unsupported combinations raise a `DtypeException`.
+///
+/// The list of `Src => Dst` combinations can be replaced by `dsp_types`, which
+/// covers all 8/16-bit integers and floats to `f32` and `f64`.
+#[macro_export]
+macro_rules! dispatch_transform {
+    (
+        $(#[$meta:meta])*
+        pub fn $name:ident<$py:lifetime>($output:ident, $input:ident $(, $arg:ident: $ty:ty)* $(,)?)
+        for dsp_types with |$Dst:ident| $body:expr
+    ) => {
+        $crate::dispatch_transform!(
+            $(#[$meta])*
+            pub fn $name<$py>($output, $input $(, $arg: $ty)*)
+            for [
+                i8 => f32, i16 => f32, u8 => f32, u16 => f32, f32 => f32, f64 => f32,
+                i8 => f64, i16 => f64, u8 => f64, u16 => f64, f32 => f64, f64 => f64,
+            ]
+            with |$Dst| $body
+        );
+    };
+    (
+        $(#[$meta:meta])*
+        pub fn $name:ident<$py:lifetime>($output:ident, $input:ident $(, $arg:ident: $ty:ty)* $(,)?)
+        for [$($Src:ty => $D:ty),* $(,)?] with |$Dst:ident| $body:expr
+    ) => {
+        $(#[$meta])*
+        pub fn $name<$py>(
+            $output: Option<Bound<$py, PyAny>>,
+            $input: Bound<$py, PyAny>,
+            $($arg: $ty,)*
+            dtype: Option<Bound<$py, PyAny>>,
+        ) -> PyResult<Bound<$py, PyAny>> {
+            use $crate::dispatch::{array_dtype, dtype_name, is_dtype, output_dtype};
+            let py = $input.py();
+            let src = array_dtype(&$input)?;
+            let dst = output_dtype($output.as_ref(), dtype.as_ref(), &src)?;
+            $(
+                if is_dtype::<$Src>(&src) && is_dtype::<$D>(&dst) {
+                    #[allow(dead_code)]
+                    type $Dst = $D;
+                    let $output = $output
+                        .map(|x| x.downcast_into::<PyArray2<$D>>())
+                        .transpose()?;
+                    let $input = $input.downcast_into::<PyArray2<$Src>>()?;
+                    return $body.map(Bound::into_any);
+                }
+            )*
+            Err($crate::errors::DtypeException::unsupported(
+                stringify!($name),
+                dst,
+                src,
+                &[$((dtype_name::<$D>(py), dtype_name::<$Src>(py))),*],
+            ))
+        }
+    };
+}
+
+/// Evaluate `$body` with `$T` bound to the element type of `$array`, among a
+/// list of supported types.
+///
+/// This expands to an early `return` of `$body`, and raises a `DtypeException`
+/// if the array has another type.
+#[macro_export]
+macro_rules! dispatch_dtype {
+    ($name:expr, $array:expr, [$($Ts:ty),* $(,)?], |$T:ident| $body:expr) => {{
+        use $crate::dispatch::{array_dtype, dtype_name, is_dtype};
+        let dtype = array_dtype(&$array)?;
+        $(
+            if is_dtype::<$Ts>(&dtype) {
+                #[allow(dead_code)]
+                type $T = $Ts;
+                return $body;
+            }
+        )*
+        let py = dtype.py();
+        Err($crate::errors::DtypeException::unsupported_input(
+            $name,
+            &dtype,
+            &[$(dtype_name::<$Ts>(py)),*],
+        ))
+    }};
+}
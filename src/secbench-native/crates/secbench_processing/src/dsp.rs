@@ -30,15 +30,26 @@
 use std::iter::Sum;
 use std::ops::AddAssign;
 
-use crate::assert_shape_match;
-use num_traits::AsPrimitive;
-use numpy::{Element, PyArray1, PyArray2, PyArray3, PyArrayMethods, ToPyArray};
+use crate::{assert_shape_match, dispatch_dtype, dispatch_transform};
+use num_traits::{AsPrimitive, Bounded};
+use numpy::ndarray::{ArrayView1, ArrayView2};
+use numpy::{
+    Element, IntoPyArray, PyArray1, PyArray2, PyArray3, PyArray4, PyArrayMethods, ToPyArray,
+};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use secbench_dsp::fft::{
     FilterSinglePass, FilterState, FilterTwoPass, PhaseCorrelation, RFftMag, TransformState,
 };
-use secbench_dsp::sliding::{MatchCorrelation, MatchEuclidean, MovingSum, SlidingExecutor, SlidingType};
-use secbench_dsp::{DspFloat, IntoFloat, Transform2D};
+use secbench_dsp::histogram::{JointHistogram, Quantizer};
+use secbench_dsp::mia::BiasCorrection;
+use secbench_dsp::poi::{self, PoiConfig};
+use secbench_dsp::sliding::{
+    MatchCorrelation, MatchEuclidean, MovingSum, SlidingExecutor, SlidingType,
+};
+use secbench_dsp::trace_stats::{OutlierFilter, TraceStat, TraceStats};
+use secbench_dsp::{Accumulate, DspFloat, IntoFloat, Transform2D};
 
 /// Wrapper for running a Transform2D in many different configurations.
 ///
@@ -98,50 +109,27 @@ pub fn generic_moving_sum<'py, Dst, Src>(
     scale: Dst,
 ) -> PyResult<Bound<'py, PyArray2<Dst>>>
 where
-    Src: Element + AsPrimitive<Dst> + Copy + Sync + Send,
+    Src: Element + AsPrimitive<Dst> + Accumulate<Dst> + Sync + Send,
     Dst: Element + DspFloat + 'static + Sync + Send,
 {
     let mut ms: MovingSum<Dst, Src> = MovingSum::new(window_size, scale);
     run_transform(&mut ms, output, input, parallel, chunk_size)
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, scale))]
-pub fn moving_sum_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    window_size: usize,
-    scale: f32,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_moving_sum(output, input, parallel, chunk_size, window_size, scale)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, scale))]
-pub fn moving_sum_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    window_size: usize,
-    scale: f32,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_moving_sum(output, input, parallel, chunk_size, window_size, scale)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, scale))]
-pub fn moving_sum_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    window_size: usize,
-    scale: f32,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_moving_sum(output, input, parallel, chunk_size, window_size, scale)
+dispatch_transform! {
+    /// Moving sum, see [`MovingSum`].
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, scale, dtype=None))]
+    pub fn moving_sum<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        scale: f64,
+    ) for dsp_types with |Dst| {
+        generic_moving_sum(output, input, parallel, chunk_size, window_size, scale as Dst)
+    }
 }
 
 // ====
@@ -172,43 +160,21 @@ where
     }
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, two_pass))]
-pub fn fft_filter_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    two_pass: bool,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_filter(output, input, kernel, parallel, chunk_size, two_pass)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, two_pass))]
-pub fn fft_filter_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    two_pass: bool,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_filter(output, input, kernel, parallel, chunk_size, two_pass)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, two_pass))]
-pub fn fft_filter_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-    two_pass: bool,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_filter(output, input, kernel, parallel, chunk_size, two_pass)
+dispatch_transform! {
+    /// FFT-based FIR filter, applied in one or two (forward-backward) passes.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, two_pass, dtype=None))]
+    pub fn fft_filter<'py>(
+        output,
+        input,
+        kernel: Bound<'py, PyAny>,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        two_pass: bool,
+    ) for dsp_types with |Dst| {
+        let kernel = kernel.downcast_into::<PyArray1<Dst>>()?;
+        generic_filter(output, input, kernel, parallel, chunk_size, two_pass)
+    }
 }
 
 // ====
@@ -233,40 +199,20 @@ where
     run_transform(&mut tr, output, input, parallel, chunk_size)
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn phase_correlation_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_phase_correlation(output, input, kernel, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn phase_correlation_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_phase_correlation(output, input, kernel, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn phase_correlation_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_phase_correlation(output, input, kernel, parallel, chunk_size)
+dispatch_transform! {
+    /// Phase correlation of each trace with a kernel.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, dtype=None))]
+    pub fn phase_correlation<'py>(
+        output,
+        input,
+        kernel: Bound<'py, PyAny>,
+        parallel: bool,
+        chunk_size: Option<usize>,
+    ) for dsp_types with |Dst| {
+        let kernel = kernel.downcast_into::<PyArray1<Dst>>()?;
+        generic_phase_correlation(output, input, kernel, parallel, chunk_size)
+    }
 }
 
 // ===
@@ -289,37 +235,18 @@ where
     run_transform(&mut tr, output, input, parallel, chunk_size)
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size))]
-pub fn rfft_mag_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_rfft_mag(output, input, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size))]
-pub fn rfft_mag_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_rfft_mag(output, input, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, *, parallel, chunk_size))]
-pub fn rfft_mag_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_rfft_mag(output, input, parallel, chunk_size)
+dispatch_transform! {
+    /// Magnitude of the real FFT of each trace.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, dtype=None))]
+    pub fn rfft_mag<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+    ) for dsp_types with |Dst| {
+        generic_rfft_mag(output, input, parallel, chunk_size)
+    }
 }
 
 // ====
@@ -336,63 +263,97 @@ pub fn generic_sliding_x<'py, Dst, Src>(
     st: SlidingType,
 ) -> PyResult<Bound<'py, PyArray2<Dst>>>
 where
-    Src: Element + AsPrimitive<Dst> + Copy + Sync + Send + AddAssign,
+    Src: Element + AsPrimitive<Dst> + Accumulate<Dst> + Sync + Send + AddAssign,
     Dst: Element + DspFloat + 'static + Sync + Send,
 {
     let mut sx: SlidingExecutor<Dst, Src> = SlidingExecutor::new(st, window_size, padding_value);
     run_transform(&mut sx, output, input, parallel, chunk_size)
 }
 
-macro_rules! def_sliding {
-    ($fn_name:ident, $sliding_type:expr, $Src:ty => $($Dst:ty),*) => { $(
-        #[pyfunction]
-        #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value))]
-        pub fn $fn_name<'py>(
-            output: Option<Bound<'py, PyArray2<$Dst>>>,
-            input: Bound<'py, PyArray2<$Src>>,
-            parallel: bool,
-            chunk_size: Option<usize>,
-            window_size: usize,
-            padding_value: Option<$Dst>,
-        ) -> PyResult<Bound<'py, PyArray2<$Dst>>> {
-            generic_sliding_x(
-                output,
-                input,
-                parallel,
-                chunk_size,
-                window_size,
-                padding_value,
-                $sliding_type,
-            )
-        }
-    )* };
+dispatch_transform! {
+    /// Sliding mean over a window of `window_size` samples.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value, dtype=None))]
+    pub fn sliding_mean<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        padding_value: Option<f64>,
+    ) for dsp_types with |Dst| {
+        let padding_value = padding_value.map(|x| x as Dst);
+        generic_sliding_x(output, input, parallel, chunk_size, window_size, padding_value, SlidingType::Mean)
+    }
 }
 
-def_sliding!(sliding_mean_f32_i8, SlidingType::Mean, i8 => f32);
-def_sliding!(sliding_mean_f32_i16, SlidingType::Mean, i16 => f32);
-def_sliding!(sliding_mean_f32_f32, SlidingType::Mean, f32 => f32);
-def_sliding!(sliding_mean_f64_f64, SlidingType::Mean, f64 => f64);
-
-def_sliding!(sliding_var_f32_i8, SlidingType::Var, i8 => f32);
-def_sliding!(sliding_var_f32_i16, SlidingType::Var, i16 => f32);
-def_sliding!(sliding_var_f32_f32, SlidingType::Var, f32 => f32);
-def_sliding!(sliding_var_f64_f64, SlidingType::Var, f64 => f64);
-
-def_sliding!(sliding_std_f32_i8, SlidingType::Std, i8 => f32);
-def_sliding!(sliding_std_f32_i16, SlidingType::Std, i16 => f32);
-def_sliding!(sliding_std_f32_f32, SlidingType::Std, f32 => f32);
-def_sliding!(sliding_std_f64_f64, SlidingType::Std, f64 => f64);
+dispatch_transform! {
+    /// Sliding var over a window of `window_size` samples.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value, dtype=None))]
+    pub fn sliding_var<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        padding_value: Option<f64>,
+    ) for dsp_types with |Dst| {
+        let padding_value = padding_value.map(|x| x as Dst);
+        generic_sliding_x(output, input, parallel, chunk_size, window_size, padding_value, SlidingType::Var)
+    }
+}
 
-def_sliding!(sliding_skew_f32_i8, SlidingType::Skew, i8 => f32);
-def_sliding!(sliding_skew_f32_i16, SlidingType::Skew, i16 => f32);
-def_sliding!(sliding_skew_f32_f32, SlidingType::Skew, f32 => f32);
-def_sliding!(sliding_skew_f64_f64, SlidingType::Skew, f64 => f64);
+dispatch_transform! {
+    /// Sliding std over a window of `window_size` samples.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value, dtype=None))]
+    pub fn sliding_std<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        padding_value: Option<f64>,
+    ) for dsp_types with |Dst| {
+        let padding_value = padding_value.map(|x| x as Dst);
+        generic_sliding_x(output, input, parallel, chunk_size, window_size, padding_value, SlidingType::Std)
+    }
+}
 
-def_sliding!(sliding_kurt_f32_i8, SlidingType::Kurt, i8 => f32);
-def_sliding!(sliding_kurt_f32_i16, SlidingType::Kurt, i16 => f32);
-def_sliding!(sliding_kurt_f32_f32, SlidingType::Kurt, f32 => f32);
-def_sliding!(sliding_kurt_f64_f64, SlidingType::Kurt, f64 => f64);
+dispatch_transform! {
+    /// Sliding skew over a window of `window_size` samples.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value, dtype=None))]
+    pub fn sliding_skew<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        padding_value: Option<f64>,
+    ) for dsp_types with |Dst| {
+        let padding_value = padding_value.map(|x| x as Dst);
+        generic_sliding_x(output, input, parallel, chunk_size, window_size, padding_value, SlidingType::Skew)
+    }
+}
 
+dispatch_transform! {
+    /// Sliding kurt over a window of `window_size` samples.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, window_size, padding_value, dtype=None))]
+    pub fn sliding_kurt<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+        window_size: usize,
+        padding_value: Option<f64>,
+    ) for dsp_types with |Dst| {
+        let padding_value = padding_value.map(|x| x as Dst);
+        generic_sliding_x(output, input, parallel, chunk_size, window_size, padding_value, SlidingType::Kurt)
+    }
+}
 
 // ====
 // Euclidean pattern matching
@@ -406,48 +367,37 @@ pub fn generic_match_euclidean<'py, Dst, Src>(
 ) -> PyResult<Bound<'py, PyArray2<Dst>>>
 where
     Src: Element + AsPrimitive<Dst> + Copy + Sync + Send,
-    Dst: Element + DspFloat + 'static + AsPrimitive<Dst> + Sum + From<u8> + Sync + Send,
+    Dst: Element
+        + DspFloat
+        + 'static
+        + AsPrimitive<Dst>
+        + Accumulate<Dst>
+        + Sum
+        + From<u8>
+        + Sync
+        + Send,
 {
     let i_array = input.readonly();
     let i_array = i_array.as_array();
-    let mut tr: MatchEuclidean<Dst, Src> = MatchEuclidean::new(kernel.readonly().as_slice().unwrap(), i_array.ncols());
+    let mut tr: MatchEuclidean<Dst, Src> =
+        MatchEuclidean::new(kernel.readonly().as_slice().unwrap(), i_array.ncols());
     run_transform(&mut tr, output, input, parallel, chunk_size)
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_euclidean_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_euclidean(output, input, kernel, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_euclidean_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_euclidean(output, input, kernel, parallel, chunk_size)
-}
-
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_euclidean_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    kernel: Bound<'py, PyArray1<f32>>,
-    parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_euclidean(output, input, kernel, parallel, chunk_size)
+dispatch_transform! {
+    /// Euclidean distance between a kernel and each window of the traces.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, dtype=None))]
+    pub fn match_euclidean<'py>(
+        output,
+        input,
+        kernel: Bound<'py, PyAny>,
+        parallel: bool,
+        chunk_size: Option<usize>,
+    ) for dsp_types with |Dst| {
+        let kernel = kernel.downcast_into::<PyArray1<Dst>>()?;
+        generic_match_euclidean(output, input, kernel, parallel, chunk_size)
+    }
 }
 
 // ====
@@ -461,50 +411,187 @@ pub fn generic_match_correlation<'py, Dst, Src>(
     chunk_size: Option<usize>,
 ) -> PyResult<Bound<'py, PyArray2<Dst>>>
 where
-    Src: Element + AsPrimitive<Dst> + AddAssign + Copy + Sync + Send,
+    Src: Element + AsPrimitive<Dst> + Accumulate<Dst> + AddAssign + Sync + Send,
     Dst: Element + DspFloat + Sum + 'static + AsPrimitive<Dst> + From<u8> + Sync + Send,
-    usize: AsPrimitive<Dst>
+    usize: AsPrimitive<Dst>,
 {
     let i_array = input.readonly();
     let i_array = i_array.as_array();
-    let mut tr: MatchCorrelation<Dst, Src> = MatchCorrelation::new(kernel.readonly().as_slice().unwrap(), i_array.ncols());
+    let mut tr: MatchCorrelation<Dst, Src> =
+        MatchCorrelation::new(kernel.readonly().as_slice().unwrap(), i_array.ncols());
     run_transform(&mut tr, output, input, parallel, chunk_size)
 }
 
-#[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_correlation_i8<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i8>>,
-    kernel: Bound<'py, PyArray1<f32>>,
+dispatch_transform! {
+    /// Pearson correlation between a kernel and each window of the traces.
+    #[pyfunction]
+    #[pyo3(signature = (output, input, kernel, *, parallel, chunk_size, dtype=None))]
+    pub fn match_correlation<'py>(
+        output,
+        input,
+        kernel: Bound<'py, PyAny>,
+        parallel: bool,
+        chunk_size: Option<usize>,
+    ) for dsp_types with |Dst| {
+        let kernel = kernel.downcast_into::<PyArray1<Dst>>()?;
+        generic_match_correlation(output, input, kernel, parallel, chunk_size)
+    }
+}
+
+// ====
+// Trace statistics and outlier rejection
+// ====
+/// Output columns are (min, max, mean, std, saturated samples), see [`TraceStat`].
+pub fn generic_trace_stats<'py, Dst, Src>(
+    output: Option<Bound<'py, PyArray2<Dst>>>,
+    input: Bound<'py, PyArray2<Src>>,
     parallel: bool,
     chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_correlation(output, input, kernel, parallel, chunk_size)
+) -> PyResult<Bound<'py, PyArray2<Dst>>>
+where
+    Src: Element + IntoFloat<Dst> + Bounded + PartialOrd + Copy + Sync + Send,
+    Dst: Element + DspFloat + Sync + Send,
+{
+    let mut tr: TraceStats<Dst, Src> = TraceStats::new();
+    run_transform(&mut tr, output, input, parallel, chunk_size)
+}
+
+dispatch_transform! {
+    /// Per-trace statistics, see [`generic_trace_stats`].
+    #[pyfunction]
+    #[pyo3(signature = (output, input, *, parallel, chunk_size, dtype=None))]
+    pub fn trace_stats<'py>(
+        output,
+        input,
+        parallel: bool,
+        chunk_size: Option<usize>,
+    ) for [i8 => f32, i16 => f32, u8 => f32, u16 => f32] with |Dst| {
+        generic_trace_stats(output, input, parallel, chunk_size)
+    }
 }
 
+/// Compute a keep-mask from the output of `trace_stats_*` functions.
 #[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_correlation_i16<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<i16>>,
-    kernel: Bound<'py, PyArray1<f32>>,
+#[pyo3(signature = (stats, *, mad_threshold, max_saturated))]
+pub fn outlier_keep_mask<'py>(
+    stats: Bound<'py, PyArray2<f32>>,
+    mad_threshold: f32,
+    max_saturated: Option<usize>,
+) -> PyResult<Bound<'py, PyArray1<bool>>> {
+    let s = stats.readonly();
+    let s = s.as_array();
+    assert_shape_match!([_, TraceStat::COUNT] => s);
+    let mask = OutlierFilter::new(mad_threshold, max_saturated).keep_mask(s);
+    Ok(mask.to_pyarray_bound(stats.py()))
+}
+
+/// Threshold of POI selection, shared by all targets or per target.
+#[derive(FromPyObject)]
+pub enum PoiThreshold {
+    Shared(f64),
+    PerTarget(Vec<f64>),
+}
+
+fn generic_select_pois<'py, T>(
+    metrics: Bound<'py, PyArray2<T>>,
+    config: PoiConfig<f64>,
+    threshold: Option<PoiThreshold>,
+    window: usize,
     parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_correlation(output, input, kernel, parallel, chunk_size)
+) -> PyResult<Vec<Bound<'py, PyArray1<usize>>>>
+where
+    T: Element + DspFloat + Sync,
+{
+    let py = metrics.py();
+    let m = metrics.readonly();
+    let m = m.as_array();
+    let m = m.as_standard_layout();
+    let thresholds = match threshold {
+        None => vec![None; m.nrows()],
+        Some(PoiThreshold::Shared(t)) => vec![Some(t); m.nrows()],
+        Some(PoiThreshold::PerTarget(t)) => {
+            if t.len() != m.nrows() {
+                return Err(PyValueError::new_err(format!(
+                    "{} thresholds given for {} targets",
+                    t.len(),
+                    m.nrows()
+                )));
+            }
+            t.into_iter().map(Some).collect()
+        }
+    };
+    let select = |(row, t): (ArrayView1<T>, &Option<f64>)| {
+        let config = PoiConfig {
+            top_k: config.top_k,
+            threshold: t.map(|t| T::from_f64(t).unwrap()),
+            min_distance: config.min_distance,
+            absolute: config.absolute,
+        };
+        let row = row.as_slice().unwrap();
+        let pois = poi::select_pois(row, &config);
+        if window == 0 {
+            return pois;
+        }
+        poi::merge_windows(&pois, window, row.len())
+            .into_iter()
+            .flatten()
+            .collect()
+    };
+    let rows: Vec<_> = m.outer_iter().zip(&thresholds).collect();
+    let pois: Vec<Vec<usize>> = if parallel {
+        rows.into_par_iter().map(select).collect()
+    } else {
+        rows.into_iter().map(select).collect()
+    };
+    Ok(pois.into_iter().map(|p| p.into_pyarray_bound(py)).collect())
 }
 
+/// Select points of interest from leakage metrics of shape `(targets,
+/// samples)` (e.g., the output of `CondMeanVar.freeze_snr`).
+///
+/// Returns the sorted indices selected for each target.
 #[pyfunction]
-#[pyo3(signature = (output, input, kernel, *, parallel, chunk_size))]
-pub fn match_correlation_f32<'py>(
-    output: Option<Bound<'py, PyArray2<f32>>>,
-    input: Bound<'py, PyArray2<f32>>,
-    kernel: Bound<'py, PyArray1<f32>>,
+#[pyo3(signature = (metrics, *, k=None, threshold=None, min_distance=0, window=0, absolute=false, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn select_pois<'py>(
+    metrics: Bound<'py, PyAny>,
+    k: Option<usize>,
+    threshold: Option<PoiThreshold>,
+    min_distance: usize,
+    window: usize,
+    absolute: bool,
     parallel: bool,
-    chunk_size: Option<usize>,
-) -> PyResult<Bound<'py, PyArray2<f32>>> {
-    generic_match_correlation(output, input, kernel, parallel, chunk_size)
+) -> PyResult<Vec<Bound<'py, PyArray1<usize>>>> {
+    let config = PoiConfig {
+        top_k: k,
+        threshold: None,
+        min_distance,
+        absolute,
+    };
+    dispatch_dtype!("select_pois", metrics, [f32, f64], |T| {
+        generic_select_pois(
+            metrics.downcast_into::<PyArray2<T>>()?,
+            config,
+            threshold,
+            window,
+            parallel,
+        )
+    })
+}
+
+/// Merge windows of `2 * half_width + 1` samples around sorted points of
+/// interest into disjoint `(start, stop)` ranges within `[0, n_samples)`.
+#[pyfunction]
+#[pyo3(signature = (pois, *, half_width, n_samples))]
+pub fn merge_poi_windows(
+    pois: Vec<usize>,
+    half_width: usize,
+    n_samples: usize,
+) -> Vec<(usize, usize)> {
+    poi::merge_windows(&pois, half_width, n_samples)
+        .into_iter()
+        .map(|r| (r.start, r.end))
+        .collect()
 }
 
 type F = f64;
@@ -521,7 +608,7 @@ impl CondMeanVar {
         labels: Bound<PyArray2<u16>>,
     ) -> PyResult<()>
     where
-        I: IntoFloat<F> + Element + Copy + 'static,
+        I: Accumulate<F> + AsPrimitive<i64> + Element,
     {
         let data = data.readonly();
         let data = data.as_array();
@@ -570,36 +657,15 @@ impl CondMeanVar {
         ))
     }
 
-    pub fn process_block_i8(
-        &mut self,
-        data: Bound<PyArray2<i8>>,
-        labels: Bound<PyArray2<u16>>,
-    ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
-    }
-
-    pub fn process_block_i16(
-        &mut self,
-        data: Bound<PyArray2<i16>>,
-        labels: Bound<PyArray2<u16>>,
-    ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
-    }
-
-    pub fn process_block_f32(
+    /// Accumulate a block of traces, of any 8/16-bit integer or float dtype.
+    pub fn process_block(
         &mut self,
-        data: Bound<PyArray2<f32>>,
+        data: Bound<PyAny>,
         labels: Bound<PyArray2<u16>>,
     ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
-    }
-
-    pub fn process_block_f64(
-        &mut self,
-        data: Bound<PyArray2<f64>>,
-        labels: Bound<PyArray2<u16>>,
-    ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
+        dispatch_dtype!("process_block", data, [i8, i16, u8, u16, f32, f64], |T| {
+            self.process_block_inner(data.downcast_into::<PyArray2<T>>()?, labels)
+        })
     }
 
     pub fn freeze_mean_var<'py>(
@@ -649,7 +715,7 @@ impl CondMeanVarP {
         labels: Bound<PyArray2<u16>>,
     ) -> PyResult<()>
     where
-        I: IntoFloat<F> + Element + Copy + 'static + Sync + Send,
+        I: Accumulate<F> + AsPrimitive<i64> + Element + Sync + Send,
     {
         let data = data.readonly();
         let data = data.as_array();
@@ -679,35 +745,316 @@ impl CondMeanVarP {
         )
     }
 
-    pub fn process_block_i8(
+    /// Accumulate a block of traces, of any 8/16-bit integer or float dtype.
+    pub fn process_block(
         &mut self,
-        data: Bound<PyArray2<i8>>,
+        data: Bound<PyAny>,
         labels: Bound<PyArray2<u16>>,
     ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
+        dispatch_dtype!("process_block", data, [i8, i16, u8, u16, f32, f64], |T| {
+            self.process_block_inner(data.downcast_into::<PyArray2<T>>()?, labels)
+        })
+    }
+}
+
+/// Check the quantization parameters of a `JointHistogram`.
+fn histogram_quantizer(
+    targets: usize,
+    samples: usize,
+    classes: usize,
+    range: (f64, f64),
+    bins: usize,
+) -> PyResult<Quantizer> {
+    if targets == 0 || samples == 0 || classes == 0 {
+        return Err(PyValueError::new_err(
+            "targets, samples and classes must be positive",
+        ));
+    }
+    if !(1..=1 << 16).contains(&bins) {
+        return Err(PyValueError::new_err("bins must be in [1, 65536]"));
+    }
+    let (low, high) = range;
+    if high <= low || !low.is_finite() || !high.is_finite() {
+        return Err(PyValueError::new_err("invalid range"));
     }
+    Ok(Quantizer::new(low, high, bins))
+}
+
+/// Check the shapes of a block of traces and the range of its labels.
+fn check_histogram_block<I>(
+    hist: &JointHistogram,
+    data: ArrayView2<I>,
+    labels: ArrayView2<u16>,
+) -> PyResult<()> {
+    let shape = hist.histograms().shape();
+    assert_shape_match!([labels.shape()[0], shape[2]] => data);
+    assert_shape_match!([data.shape()[0], shape[0]] => labels);
+    let classes = hist.num_classes();
+    if let Some(l) = labels.iter().find(|&&l| l as usize >= classes) {
+        return Err(PyValueError::new_err(format!(
+            "label {l} out of range for {classes} classes"
+        )));
+    }
+    Ok(())
+}
+
+fn check_histogram_mergeable(a: &JointHistogram, b: &JointHistogram) -> PyResult<()> {
+    if a.quantizer() != b.quantizer() || a.histograms().shape() != b.histograms().shape() {
+        return Err(PyValueError::new_err(
+            "cannot merge histograms with different shapes or quantization",
+        ));
+    }
+    Ok(())
+}
 
-    pub fn process_block_i16(
+/// Joint histograms of labels and quantized samples, for mutual information
+/// analysis.
+///
+/// Samples are quantized in `bins` bins over `range = (low, high)`, values
+/// out of the range being put in the first or last bin. Data and labels have
+/// the same layout as `CondMeanVar`.
+#[pyclass]
+#[derive(Clone)]
+pub struct MiaHistogram {
+    inner: secbench_dsp::MiaHistogram,
+}
+
+impl MiaHistogram {
+    fn process_block_inner<I>(
         &mut self,
-        data: Bound<PyArray2<i16>>,
+        data: Bound<PyArray2<I>>,
         labels: Bound<PyArray2<u16>>,
-    ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
+        parallel: bool,
+        chunk_size: usize,
+    ) -> PyResult<()>
+    where
+        I: IntoFloat<f64> + Element + Copy + Sync,
+    {
+        let data = data.readonly();
+        let data = data.as_array();
+        let labels = labels.readonly();
+        let labels = labels.as_array();
+        check_histogram_block(self.inner.histogram(), data, labels)?;
+        if parallel {
+            self.inner.process_block_parallel(data, labels, chunk_size);
+        } else {
+            self.inner.process_block(data, labels);
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl MiaHistogram {
+    #[new]
+    #[pyo3(signature = (targets, samples, classes, *, range, bins=16))]
+    pub fn new(
+        targets: usize,
+        samples: usize,
+        classes: usize,
+        range: (f64, f64),
+        bins: usize,
+    ) -> PyResult<Self> {
+        let quantizer = histogram_quantizer(targets, samples, classes, range, bins)?;
+        Ok(Self {
+            inner: secbench_dsp::MiaHistogram::new(targets, samples, classes, quantizer),
+        })
     }
 
-    pub fn process_block_f32(
+    /// Accumulate a block of traces (of any 8/16-bit integer or float
+    /// dtype) with labels of shape `(n, targets)`.
+    ///
+    /// With `parallel`, targets and chunks of `chunk_size` samples are
+    /// processed in parallel.
+    #[pyo3(signature = (data, labels, *, parallel=false, chunk_size=1024))]
+    pub fn process_block(
         &mut self,
-        data: Bound<PyArray2<f32>>,
+        data: Bound<PyAny>,
         labels: Bound<PyArray2<u16>>,
+        parallel: bool,
+        chunk_size: usize,
     ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
+        dispatch_dtype!("process_block", data, [i8, i16, u8, u16, f32, f64], |T| {
+            self.process_block_inner(
+                data.downcast_into::<PyArray2<T>>()?,
+                labels,
+                parallel,
+                chunk_size,
+            )
+        })
+    }
+
+    /// Merge histograms accumulated on other traces.
+    pub fn merge(&mut self, other: PyRef<MiaHistogram>) -> PyResult<()> {
+        check_histogram_mergeable(self.inner.histogram(), other.inner.histogram())?;
+        self.inner.merge(&other.inner);
+        Ok(())
+    }
+
+    pub fn freeze_samples_per_class<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        self.inner
+            .histogram()
+            .samples_per_class()
+            .to_pyarray_bound(py)
+    }
+
+    /// Joint histograms, of shape `(targets, classes, samples, bins)`.
+    pub fn histograms<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray4<u32>> {
+        self.inner.histogram().histograms().to_pyarray_bound(py)
+    }
+
+    /// Mutual information (in bits) between each target and each sample,
+    /// shape `(targets, samples)`.
+    ///
+    /// `bias_correction` is either `"miller-madow"` or `"none"` (plug-in
+    /// estimator).
+    #[pyo3(signature = (*, bias_correction="miller-madow"))]
+    pub fn freeze_mi<'py>(
+        &self,
+        py: Python<'py>,
+        bias_correction: &str,
+    ) -> PyResult<Bound<'py, PyArray2<F>>> {
+        let correction = match bias_correction {
+            "miller-madow" => BiasCorrection::MillerMadow,
+            "none" => BiasCorrection::None,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown bias correction \"{bias_correction}\""
+                )))
+            }
+        };
+        Ok(self.inner.freeze_mi(correction).into_pyarray_bound(py))
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
     }
+}
 
-    pub fn process_block_f64(
+/// Joint histograms of labels and quantized samples, for chi-squared and
+/// Kolmogorov-Smirnov leakage detection tests.
+///
+/// Labels are the classes of each target (e.g., 0 for fixed and 1 for random
+/// traces, or the value of an intermediate variable). Samples are quantized
+/// in `bins` bins over `range = (low, high)`, values out of the range being
+/// put in the first or last bin. Data and labels have the same layout as
+/// `CondMeanVar`.
+#[pyclass]
+#[derive(Clone)]
+pub struct DistributionTest {
+    inner: secbench_dsp::DistributionTest,
+}
+
+impl DistributionTest {
+    fn process_block_inner<I>(
         &mut self,
-        data: Bound<PyArray2<f64>>,
+        data: Bound<PyArray2<I>>,
         labels: Bound<PyArray2<u16>>,
+        parallel: bool,
+        chunk_size: usize,
+    ) -> PyResult<()>
+    where
+        I: IntoFloat<f64> + Element + Copy + Sync,
+    {
+        let data = data.readonly();
+        let data = data.as_array();
+        let labels = labels.readonly();
+        let labels = labels.as_array();
+        check_histogram_block(self.inner.histogram(), data, labels)?;
+        if parallel {
+            self.inner.process_block_parallel(data, labels, chunk_size);
+        } else {
+            self.inner.process_block(data, labels);
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl DistributionTest {
+    #[new]
+    #[pyo3(signature = (targets, samples, classes, *, range, bins=16))]
+    pub fn new(
+        targets: usize,
+        samples: usize,
+        classes: usize,
+        range: (f64, f64),
+        bins: usize,
+    ) -> PyResult<Self> {
+        let quantizer = histogram_quantizer(targets, samples, classes, range, bins)?;
+        Ok(Self {
+            inner: secbench_dsp::DistributionTest::new(targets, samples, classes, quantizer),
+        })
+    }
+
+    /// Accumulate a block of traces (of any 8/16-bit integer or float
+    /// dtype) with labels of shape `(n, targets)`.
+    ///
+    /// With `parallel`, targets and chunks of `chunk_size` samples are
+    /// processed in parallel.
+    #[pyo3(signature = (data, labels, *, parallel=false, chunk_size=1024))]
+    pub fn process_block(
+        &mut self,
+        data: Bound<PyAny>,
+        labels: Bound<PyArray2<u16>>,
+        parallel: bool,
+        chunk_size: usize,
     ) -> PyResult<()> {
-        self.process_block_inner(data, labels)
+        dispatch_dtype!("process_block", data, [i8, i16, u8, u16, f32, f64], |T| {
+            self.process_block_inner(
+                data.downcast_into::<PyArray2<T>>()?,
+                labels,
+                parallel,
+                chunk_size,
+            )
+        })
+    }
+
+    /// Merge histograms accumulated on other traces.
+    pub fn merge(&mut self, other: PyRef<DistributionTest>) -> PyResult<()> {
+        check_histogram_mergeable(self.inner.histogram(), other.inner.histogram())?;
+        self.inner.merge(&other.inner);
+        Ok(())
+    }
+
+    pub fn freeze_samples_per_class<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        self.inner
+            .histogram()
+            .samples_per_class()
+            .to_pyarray_bound(py)
+    }
+
+    /// Joint histograms, of shape `(targets, classes, samples, bins)`.
+    pub fn histograms<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray4<u32>> {
+        self.inner.histogram().histograms().to_pyarray_bound(py)
+    }
+
+    /// Chi-squared test of independence between each target and each
+    /// sample: returns `(statistics, pvalues)`, of shape `(targets, samples)`.
+    pub fn freeze_chi2_test<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray2<F>>, Bound<'py, PyArray2<F>>) {
+        let (chi2, p) = self.inner.freeze_chi2_test();
+        (chi2.into_pyarray_bound(py), p.into_pyarray_bound(py))
     }
-}
\ No newline at end of file
+
+    /// Two-sample Kolmogorov-Smirnov tests between the classes of each
+    /// target: returns `(statistics, pvalues)`, of shape `(targets, samples)`.
+    ///
+    /// With more than two classes, each class is compared with all other
+    /// traces; the largest statistic and the smallest p-value (with a
+    /// Bonferroni correction) are returned. The statistic is computed on
+    /// quantized samples, and p-values are asymptotic.
+    pub fn freeze_ks_test<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray2<F>>, Bound<'py, PyArray2<F>>) {
+        let (d, p) = self.inner.freeze_ks_test();
+        (d.into_pyarray_bound(py), p.into_pyarray_bound(py))
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
+    }
+}
@@ -27,7 +27,10 @@
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
 
-use pyo3::{exceptions::PyException, prelude::*};
+use pyo3::{
+    exceptions::{PyException, PyTypeError},
+    prelude::*,
+};
 
 pyo3::create_exception!(secbench_ffi, ShapeException, PyException, "Incorrect shape");
 
@@ -37,14 +40,52 @@ impl ShapeException {
     }
 }
 
+pyo3::create_exception!(
+    secbench_ffi,
+    DtypeException,
+    PyTypeError,
+    "Unsupported array dtype"
+);
+
+impl DtypeException {
+    /// Error for a `function` called with an unsupported `(output, input)` dtype combination.
+    pub fn unsupported(
+        function: &str,
+        output: impl std::fmt::Display,
+        input: impl std::fmt::Display,
+        supported: &[(String, String)],
+    ) -> PyErr {
+        let supported = supported
+            .iter()
+            .map(|(o, i)| format!("({o}, {i})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::new_err(format!(
+            "{function} does not support output dtype {output} with input dtype {input}, supported (output, input) combinations are: {supported}"
+        ))
+    }
+
+    /// Error for a `function` called on an array of unsupported dtype.
+    pub fn unsupported_input(
+        function: &str,
+        input: impl std::fmt::Display,
+        supported: &[String],
+    ) -> PyErr {
+        Self::new_err(format!(
+            "{function} does not support dtype {input}, supported dtypes are: {}",
+            supported.join(", ")
+        ))
+    }
+}
+
 /// Macro to assert that the given array matches a specific shape
 ///
 /// ## Usage
 ///
 /// To check if an array2 has the correct amount of columns (axis 1) but we don't care amount the
 /// amount of rows (axis 0):
-/// ```rust
-/// let data = Array2::zeros([10, 200]);
+/// ```rust,ignore
+/// let data = Array2::<f32>::zeros([10, 200]);
 /// assert_shape_match!([_, 200] => data);
 /// ```
 #[macro_export]
@@ -81,4 +122,22 @@ macro_rules! assert_shape_match {
     };
     // terminal case
     (@expand_check [$t:expr, $i:expr]) => { false };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use numpy::ndarray::Array2;
+    use pyo3::PyResult;
+
+    fn check(data: &Array2<f32>) -> PyResult<()> {
+        assert_shape_match!([_, 200] => data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_assert_shape_match() {
+        assert!(check(&Array2::zeros([10, 200])).is_ok());
+        assert!(check(&Array2::zeros([0, 200])).is_ok());
+        assert!(check(&Array2::zeros([10, 199])).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use numpy::ndarray::{Array2, ArrayView1, ArrayView2};
+use numpy::{IntoPyArray, PyArray2, PyArrayMethods, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::attacks::{bounded, percentile};
+use crate::{assert_shape_match, dispatch_dtype};
+use secbench_crypto::{Pcg32, Pcg32Seed};
+
+/// Per-trace terms of the information estimators.
+struct TraceTerms {
+    labels: Vec<usize>,
+    /// `log2 m(y | x)` for the correct label `y`, with `m(y | x)` clipped
+    /// to `min_proba`.
+    log_proba: Vec<f64>,
+    /// `sum_y m(y | x) log2 m(y | x)`.
+    neg_entropy: Vec<f64>,
+}
+
+impl TraceTerms {
+    fn new<T: Copy + Into<f64>>(
+        probas: ArrayView2<T>,
+        labels: ArrayView1<u16>,
+        min_proba: f64,
+    ) -> Self {
+        let log_proba = probas
+            .outer_iter()
+            .zip(labels)
+            .map(|(p, &y)| {
+                let p = p[y as usize].into();
+                // Keeps NaN, unlike `f64::max`.
+                if p < min_proba { min_proba } else { p }.log2()
+            })
+            .collect();
+        let neg_entropy = probas
+            .outer_iter()
+            .map(|p| {
+                p.iter()
+                    .map(|&x| x.into())
+                    .filter(|&x| x > 0.0)
+                    .map(|x| x * x.log2())
+                    .sum()
+            })
+            .collect();
+        TraceTerms {
+            labels: labels.iter().map(|&y| y as usize).collect(),
+            log_proba,
+            neg_entropy,
+        }
+    }
+
+    /// Perceived and hypothetical information over traces `indices`.
+    ///
+    /// Traces are averaged per class, and classes are weighted by `prior`
+    /// (renormalized over the classes present).
+    fn estimate(&self, indices: impl Iterator<Item = usize>, prior: &Prior) -> [f64; 2] {
+        let classes = prior.probas.len();
+        let mut sums = vec![[0.0; 2]; classes];
+        let mut counts = vec![0u32; classes];
+        for i in indices {
+            let y = self.labels[i];
+            sums[y][0] += self.log_proba[i];
+            sums[y][1] += self.neg_entropy[i];
+            counts[y] += 1;
+        }
+        let present: f64 = (0..classes)
+            .filter(|&y| counts[y] != 0)
+            .map(|y| prior.probas[y])
+            .sum();
+        let mut info = [prior.entropy; 2];
+        for y in (0..classes).filter(|&y| counts[y] != 0 && prior.probas[y] > 0.0) {
+            let w = prior.probas[y] / present / counts[y] as f64;
+            info[0] += w * sums[y][0];
+            info[1] += w * sums[y][1];
+        }
+        info
+    }
+}
+
+/// Class probabilities and their entropy (in bits).
+struct Prior {
+    probas: Vec<f64>,
+    entropy: f64,
+}
+
+impl Prior {
+    fn new(prior: Option<Vec<f64>>, labels: ArrayView1<u16>, classes: usize) -> PyResult<Self> {
+        let weights = match prior {
+            Some(p) => {
+                if p.len() != classes {
+                    return Err(PyValueError::new_err(format!(
+                        "prior has {} values for {classes} classes",
+                        p.len()
+                    )));
+                }
+                if p.iter().any(|&x| x.is_nan() || x < 0.0) || p.iter().sum::<f64>() <= 0.0 {
+                    return Err(PyValueError::new_err(
+                        "prior must be non-negative with a positive sum",
+                    ));
+                }
+                p
+            }
+            None => {
+                let mut counts = vec![0.0; classes];
+                labels.iter().for_each(|&y| counts[y as usize] += 1.0);
+                counts
+            }
+        };
+        let total: f64 = weights.iter().sum();
+        let probas: Vec<f64> = weights.iter().map(|&w| w / total).collect();
+        let entropy = -probas
+            .iter()
+            .filter(|&&p| p > 0.0)
+            .map(|&p| p * p.log2())
+            .sum::<f64>();
+        Ok(Prior { probas, entropy })
+    }
+}
+
+/// Perceived and hypothetical information (in bits per trace) of a model,
+/// from its output probabilities.
+///
+/// `probas` has shape `(n_traces, n_classes)` (f32 or f64) and contains the
+/// probabilities `m(y | x)` given by the model for each trace, whose correct
+/// class is given in `labels`. The perceived information is
+/// `H(Y) + E[log2 m(y | x)]` (or the training information when `probas` are
+/// computed on the training set), and the hypothetical information is
+/// `H(Y) + E[sum_y m(y | x) log2 m(y | x)]`. Expectations are taken per class
+/// and weighted by `prior` (the label frequencies by default).
+///
+/// A single trace whose correct class has a zero probability makes the
+/// perceived information `-inf`. To avoid this, `m(y | x)` is clipped to
+/// `min_proba` (in `[0, 1)`, 0 disables the clipping) in the perceived
+/// information. The hypothetical information is not affected.
+///
+/// With `n_bootstrap > 0`, confidence intervals at level `confidence` are
+/// estimated with bootstrap percentiles (replicate `i` uses `Pcg32(seed, i)`).
+///
+/// Returns an array of shape `(2, 3)`, with the perceived and hypothetical
+/// information in rows and, in columns, the estimate and the bounds of the
+/// confidence interval (NaN without bootstrap).
+#[pyfunction]
+#[pyo3(signature = (probas, labels, *, prior=None, min_proba=1e-15, n_bootstrap=0, confidence=0.95, seed=0, parallel=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn information_metrics<'py>(
+    probas: Bound<'py, PyAny>,
+    labels: PyReadonlyArray1<'py, u16>,
+    prior: Option<Vec<f64>>,
+    min_proba: f64,
+    n_bootstrap: usize,
+    confidence: f64,
+    seed: u64,
+    parallel: bool,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let py = probas.py();
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(PyValueError::new_err("confidence must be in (0, 1)"));
+    }
+    if !(0.0..1.0).contains(&min_proba) {
+        return Err(PyValueError::new_err("min_proba must be in [0, 1)"));
+    }
+    let labels = labels.as_array();
+    dispatch_dtype!("information_metrics", probas, [f32, f64], |T| {
+        let probas = probas.downcast_into::<PyArray2<T>>()?.readonly();
+        let probas = probas.as_array();
+        assert_shape_match!([labels.len(), _] => probas);
+        let (n, classes) = probas.dim();
+        if n == 0 {
+            return Err(PyValueError::new_err("at least one trace is required"));
+        }
+        if let Some(y) = labels.iter().find(|&&y| y as usize >= classes) {
+            return Err(PyValueError::new_err(format!(
+                "label {y} out of range for {classes} classes"
+            )));
+        }
+        let prior = Prior::new(prior.clone(), labels, classes)?;
+        let terms = TraceTerms::new(probas, labels, min_proba);
+
+        let mut out = Array2::from_elem((2, 3), f64::NAN);
+        let info = terms.estimate(0..n, &prior);
+        if n_bootstrap > 0 {
+            let replicate = |b: usize| {
+                let mut rng = Pcg32::new(Pcg32Seed::from_state_inc(seed, b as u64));
+                let indices: Vec<usize> = (0..n).map(|_| bounded(&mut rng, n)).collect();
+                terms.estimate(indices.into_iter(), &prior)
+            };
+            let replicates: Vec<[f64; 2]> = if parallel {
+                (0..n_bootstrap).into_par_iter().map(replicate).collect()
+            } else {
+                (0..n_bootstrap).map(replicate).collect()
+            };
+            let alpha = (1.0 - confidence) / 2.0 * 100.0;
+            for k in 0..2 {
+                let mut values: Vec<f64> = replicates.iter().map(|r| r[k]).collect();
+                values.sort_by(f64::total_cmp);
+                out[(k, 1)] = percentile(&values, alpha);
+                out[(k, 2)] = percentile(&values, 100.0 - alpha);
+            }
+        }
+        out[(0, 0)] = info[0];
+        out[(1, 0)] = info[1];
+        Ok(out.into_pyarray_bound(py))
+    })
+}
@@ -27,14 +27,29 @@
 // The fact that you are presently reading this means that you have had
 // knowledge of the CeCILL license and that you accept its terms.
 
+// Python bindings routinely return tuples of arrays.
+#![allow(clippy::type_complexity)]
+
 use pyo3::prelude::*;
 use tracing::level_filters::LevelFilter;
 
-use crate::errors::ShapeException;
+use crate::errors::{DtypeException, ShapeException};
 
+mod attacks;
 mod crypto;
+mod dispatch;
 mod dsp;
 mod errors;
+mod information;
+mod models;
+mod profiled;
+
+/// Register several `#[pyfunction]` in a module.
+macro_rules! add_functions {
+    ($m:expr, $($f:path),* $(,)?) => {
+        $( $m.add_function(wrap_pyfunction!($f, &$m)?)?; )*
+    };
+}
 
 pub fn make_secbench_processing(py: Python) -> PyResult<Bound<PyModule>> {
     let m = PyModule::new_bound(py, "processing")?;
@@ -47,63 +62,75 @@ pub fn make_secbench_processing(py: Python) -> PyResult<Bound<PyModule>> {
 
     // Errors
     m.add("ShapeException", py.get_type_bound::<ShapeException>())?;
+    m.add("DtypeException", py.get_type_bound::<DtypeException>())?;
 
 
     // Dsp
     m.add_class::<dsp::CondMeanVar>()?;
     m.add_class::<dsp::CondMeanVarP>()?;
-    m.add_function(wrap_pyfunction!(dsp::moving_sum_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::moving_sum_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::moving_sum_f32, &m)?)?;
-    
-    m.add_function(wrap_pyfunction!(dsp::fft_filter_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::fft_filter_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::fft_filter_f32, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::phase_correlation_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::phase_correlation_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::phase_correlation_f32, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::rfft_mag_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::rfft_mag_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::rfft_mag_f32, &m)?)?;
-    
-    m.add_function(wrap_pyfunction!(dsp::match_euclidean_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::match_euclidean_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::match_euclidean_f32, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::match_correlation_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::match_correlation_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::match_correlation_f32, &m)?)?;
-
-
-    // Add Pcg32
+    m.add_class::<dsp::MiaHistogram>()?;
+    m.add_class::<dsp::DistributionTest>()?;
+    add_functions!(
+        m,
+        dsp::moving_sum,
+        dsp::fft_filter,
+        dsp::phase_correlation,
+        dsp::rfft_mag,
+        dsp::match_euclidean,
+        dsp::match_correlation,
+        dsp::sliding_mean,
+        dsp::sliding_var,
+        dsp::sliding_std,
+        dsp::sliding_skew,
+        dsp::sliding_kurt,
+        dsp::trace_stats,
+        dsp::outlier_keep_mask,
+        dsp::select_pois,
+        dsp::merge_poi_windows,
+    );
+
+    // Leakage models
+    add_functions!(
+        m,
+        models::hamming_weight,
+        models::hamming_distance,
+        models::unpackbits,
+        models::lra_unpackbits,
+        models::lra_unpackbits_2nd_order,
+    );
+
+    // Information metrics
+    add_functions!(m, information::information_metrics);
+
+    // Profiled attacks
+    m.add_class::<profiled::GaussianTemplates>()?;
+    m.add_class::<profiled::ClassScatter>()?;
+
+    // Attacks
+    add_functions!(
+        m,
+        attacks::accumulate_key_scores,
+        attacks::guessing_entropy,
+        attacks::key_rank_bounds,
+        attacks::enumerate_keys,
+    );
+
+    // Crypto
     m.add_class::<crypto::Pcg32>()?;
-
-    m.add_function(wrap_pyfunction!(dsp::sliding_mean_f32_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_mean_f32_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_mean_f32_f32, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_mean_f64_f64, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::sliding_var_f32_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_var_f32_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_var_f32_f32, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_var_f64_f64, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::sliding_std_f32_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_std_f32_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_std_f32_f32, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_std_f64_f64, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::sliding_skew_f32_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_skew_f32_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_skew_f32_f32, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_skew_f64_f64, &m)?)?;
-
-    m.add_function(wrap_pyfunction!(dsp::sliding_kurt_f32_i8, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_kurt_f32_i16, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_kurt_f32_f32, &m)?)?;
-    m.add_function(wrap_pyfunction!(dsp::sliding_kurt_f64_f64, &m)?)?;
+    m.add_class::<crypto::Crc>()?;
+    m.add_class::<crypto::Prng>()?;
+    add_functions!(
+        m,
+        crypto::aes_encrypt,
+        crypto::aes_decrypt,
+        crypto::aes_intermediates,
+        crypto::aes_labels,
+        crypto::aes_simulate_masked,
+        crypto::keccak_f1600,
+        crypto::keccak_f1600_intermediates,
+        crypto::sha3,
+        crypto::shake,
+    );
 
     Ok(m)
 }
\ No newline at end of file
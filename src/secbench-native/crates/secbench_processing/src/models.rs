@@ -0,0 +1,187 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use num_traits::PrimInt;
+use numpy::ndarray::{ArrayD, ArrayViewD};
+use numpy::{IntoPyArray, PyArrayDyn, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+use crate::dispatch_dtype;
+use crate::errors::ShapeException;
+
+/// Number of bits kept in a decomposition (all bits if `count` is 0 or too large).
+fn n_bits<T>(count: usize) -> usize {
+    let n = 8 * std::mem::size_of::<T>();
+    if count > 0 && count < n {
+        count
+    } else {
+        n
+    }
+}
+
+fn bit<T: PrimInt>(v: T, i: usize) -> i8 {
+    ((v >> i) & T::one()).to_i8().unwrap()
+}
+
+/// Map each element of `y` to `width` values, stored in a new last axis.
+fn decompose<T, O, F>(y: ArrayViewD<T>, width: usize, f: F) -> ArrayD<O>
+where
+    T: Copy,
+    O: Clone + Default,
+    F: Fn(T, &mut [O]),
+{
+    let mut shape = y.shape().to_vec();
+    shape.push(width);
+    let mut out = vec![O::default(); y.len() * width];
+    for (&v, dst) in y.iter().zip(out.chunks_mut(width)) {
+        f(v, dst);
+    }
+    ArrayD::from_shape_vec(shape, out).unwrap()
+}
+
+fn lra_first_order<T: PrimInt>(v: T, n: usize, center: bool, dst: &mut [i8]) {
+    for (i, d) in dst[..n].iter_mut().enumerate() {
+        let b = bit(v, i);
+        *d = if center { 2 * b - 1 } else { b };
+    }
+}
+
+fn lra_second_order<T: PrimInt>(v: T, n: usize, center: bool, dst: &mut [i8]) {
+    lra_first_order(v, n, center, dst);
+    let mut k = n;
+    for i in 0..n {
+        for j in i + 1..n {
+            let b = bit(v, i) * bit(v, j);
+            dst[k] = if center { 4 * b - 1 } else { b };
+            k += 1;
+        }
+    }
+}
+
+/// Number of bits set in each element of `x`, as `uint8`.
+#[pyfunction]
+pub fn hamming_weight<'py>(x: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    dispatch_dtype!("hamming_weight", x, [u8, u16, u32, u64], |T| {
+        let x = x.downcast::<PyArrayDyn<T>>()?;
+        let hw = x.readonly().as_array().mapv(|v| v.count_ones() as u8);
+        Ok(hw.into_pyarray_bound(x.py()).into_any())
+    })
+}
+
+/// Number of bits that differ between `x` and `y`, as `uint8`.
+#[pyfunction]
+pub fn hamming_distance<'py>(
+    x: Bound<'py, PyAny>,
+    y: Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    dispatch_dtype!("hamming_distance", x, [u8, u16, u32, u64], |T| {
+        let x = x.downcast::<PyArrayDyn<T>>()?;
+        let y = y.downcast::<PyArrayDyn<T>>()?;
+        if x.shape() != y.shape() {
+            return Err(ShapeException::from_expected_shapes(x.shape(), y.shape()));
+        }
+        let (x_ro, y_ro) = (x.readonly(), y.readonly());
+        let mut hd = x_ro.as_array().to_owned();
+        hd.zip_mut_with(&y_ro.as_array(), |a, &b| *a ^= b);
+        let hd = hd.mapv(|v| v.count_ones() as u8);
+        Ok(hd.into_pyarray_bound(x.py()).into_any())
+    })
+}
+
+/// Bits of each element of `y` (least significant first) in a new last axis, as `uint8`.
+#[pyfunction]
+#[pyo3(signature = (y, *, count=0))]
+pub fn unpackbits<'py>(y: Bound<'py, PyAny>, count: usize) -> PyResult<Bound<'py, PyAny>> {
+    dispatch_dtype!("unpackbits", y, [u8, u16, u32, u64], |T| {
+        let y = y.downcast::<PyArrayDyn<T>>()?;
+        let n = n_bits::<T>(count);
+        let bits = decompose(y.readonly().as_array(), n, |v, dst: &mut [u8]| {
+            for (i, d) in dst.iter_mut().enumerate() {
+                *d = bit(v, i) as u8;
+            }
+        });
+        Ok(bits.into_pyarray_bound(y.py()).into_any())
+    })
+}
+
+/// First order bit decomposition of `y` for LRA, as `int8`.
+///
+/// The last axis contains the bits (least significant first), followed by a
+/// column of ones if `with_intercept` is set. Bits are mapped to {-1, 1}
+/// if `center` is set.
+#[pyfunction]
+#[pyo3(signature = (y, *, center=false, with_intercept=true, count=0))]
+pub fn lra_unpackbits<'py>(
+    y: Bound<'py, PyAny>,
+    center: bool,
+    with_intercept: bool,
+    count: usize,
+) -> PyResult<Bound<'py, PyAny>> {
+    dispatch_dtype!("lra_unpackbits", y, [u8, u16, u32, u64], |T| {
+        let y = y.downcast::<PyArrayDyn<T>>()?;
+        let n = n_bits::<T>(count);
+        let width = n + with_intercept as usize;
+        let bits = decompose(y.readonly().as_array(), width, |v, dst: &mut [i8]| {
+            lra_first_order(v, n, center, dst);
+            if with_intercept {
+                dst[n] = 1;
+            }
+        });
+        Ok(bits.into_pyarray_bound(y.py()).into_any())
+    })
+}
+
+/// Second order bit decomposition of `y` for LRA, as `int8`.
+///
+/// The last axis contains the bits `y_i`, then the products `y_i * y_j` for
+/// `i < j` in lexicographic order, followed by a column of ones if
+/// `with_intercept` is set. If `center` is set, bits are mapped to
+/// `2 * y_i - 1` and products to `4 * y_i * y_j - 1`.
+#[pyfunction]
+#[pyo3(signature = (y, *, with_intercept=true, count=0, center=false))]
+pub fn lra_unpackbits_2nd_order<'py>(
+    y: Bound<'py, PyAny>,
+    with_intercept: bool,
+    count: usize,
+    center: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    dispatch_dtype!("lra_unpackbits_2nd_order", y, [u8, u16, u32, u64], |T| {
+        let y = y.downcast::<PyArrayDyn<T>>()?;
+        let n = n_bits::<T>(count);
+        let terms = n * (n + 1) / 2;
+        let width = terms + with_intercept as usize;
+        let bits = decompose(y.readonly().as_array(), width, |v, dst: &mut [i8]| {
+            lra_second_order(v, n, center, dst);
+            if with_intercept {
+                dst[terms] = 1;
+            }
+        });
+        Ok(bits.into_pyarray_bound(y.py()).into_any())
+    })
+}
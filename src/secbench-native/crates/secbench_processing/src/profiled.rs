@@ -0,0 +1,397 @@
+// Copyright CEA (Commissariat à l'énergie atomique et aux
+// énergies alternatives) (2017-2025)
+//
+// This software is governed by the CeCILL  license under French law and
+// abiding by the rules of distribution of free software.  You can  use,
+// modify and/ or redistribute the software under the terms of the CeCILL
+// license as circulated by CEA, CNRS and INRIA at the following URL
+// "http://www.cecill.info".
+//
+// As a counterpart to the access to the source code and  rights to copy,
+// modify and redistribute granted by the license, users are provided only
+// with a limited warranty  and the software's author,  the holder of the
+// economic rights,  and the successive licensors  have only  limited
+// liability.
+//
+// In this respect, the user's attention is drawn to the risks associated
+// with loading,  using,  modifying and/or developing or reproducing the
+// software by the user in light of its specific status of free software,
+// that may mean  that it is complicated to manipulate,  and  that  also
+// therefore means  that it is reserved for developers  and  experienced
+// professionals having in-depth computer knowledge. Users are therefore
+// encouraged to load and test the software's suitability as regards their
+// requirements in conditions enabling the security of their systems and/or
+// data to be ensured and,  more generally, to use and operate it in the
+// same conditions as regards security.
+//
+// The fact that you are presently reading this means that you have had
+// knowledge of the CeCILL license and that you accept its terms.
+
+use numpy::ndarray::{ArrayView1, ArrayView2, Axis};
+use numpy::{
+    Element, IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1, ToPyArray,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use secbench_dsp::{CondMeanCov, IntoFloat, Projection};
+use std::sync::OnceLock;
+
+use crate::{assert_shape_match, dispatch_dtype};
+
+type F = f64;
+
+/// Check that labels are valid classes and that `pois` are in the traces.
+fn check_block<S>(
+    data: ArrayView2<S>,
+    labels: ArrayView1<u16>,
+    pois: &[usize],
+    classes: usize,
+) -> PyResult<()> {
+    assert_shape_match!([labels.len(), _] => data);
+    check_pois(data, pois)?;
+    if let Some(l) = labels.iter().find(|&&l| l as usize >= classes) {
+        return Err(PyValueError::new_err(format!(
+            "label {l} out of range for {classes} classes"
+        )));
+    }
+    Ok(())
+}
+
+fn check_pois<S>(data: ArrayView2<S>, pois: &[usize]) -> PyResult<()> {
+    if let Some(p) = pois.iter().find(|&&p| p >= data.ncols()) {
+        return Err(PyValueError::new_err(format!(
+            "point of interest {p} out of range for traces of {} samples",
+            data.ncols()
+        )));
+    }
+    Ok(())
+}
+
+/// Accumulate a block of traces of any supported dtype in `acc`.
+fn process_block_into(
+    acc: &mut CondMeanCov<F>,
+    data: Bound<PyAny>,
+    labels: PyReadonlyArray1<u16>,
+    parallel: bool,
+    chunk_size: usize,
+) -> PyResult<()> {
+    fn inner<S>(
+        acc: &mut CondMeanCov<F>,
+        data: Bound<PyArray2<S>>,
+        labels: ArrayView1<u16>,
+        parallel: bool,
+        chunk_size: usize,
+    ) -> PyResult<()>
+    where
+        S: IntoFloat<F> + Element + Copy + Sync,
+    {
+        let data = data.readonly();
+        let data = data.as_array();
+        check_block(data, labels, acc.pois(), acc.num_classes())?;
+        if parallel {
+            acc.process_block_parallel(data, labels, chunk_size);
+        } else {
+            acc.process_block(data, labels);
+        }
+        Ok(())
+    }
+
+    let labels = labels.as_array();
+    dispatch_dtype!("process_block", data, [i8, i16, u8, u16, f32, f64], |T| {
+        inner(
+            acc,
+            data.downcast_into::<PyArray2<T>>()?,
+            labels,
+            parallel,
+            chunk_size,
+        )
+    })
+}
+
+fn new_accumulator(pois: Vec<usize>, classes: usize) -> PyResult<CondMeanCov<F>> {
+    if pois.is_empty() || classes == 0 {
+        return Err(PyValueError::new_err(
+            "at least one point of interest and one class are required",
+        ));
+    }
+    Ok(CondMeanCov::new(pois, classes))
+}
+
+fn check_mergeable(a: &CondMeanCov<F>, b: &CondMeanCov<F>) -> PyResult<()> {
+    if a.pois() != b.pois() || a.num_classes() != b.num_classes() {
+        return Err(PyValueError::new_err(
+            "cannot merge accumulators with different points of interest or classes",
+        ));
+    }
+    Ok(())
+}
+
+/// Gaussian templates with a pooled covariance matrix.
+///
+/// Class means and the pooled covariance over the samples `pois` are
+/// accumulated incrementally with `process_block`. Accumulators built on
+/// different traces can be merged.
+#[pyclass]
+#[derive(Clone)]
+pub struct GaussianTemplates {
+    inner: CondMeanCov<F>,
+    // templates frozen on the first `log_likelihood` call, reset when traces
+    // are accumulated
+    frozen: OnceLock<secbench_dsp::GaussianTemplates<F>>,
+}
+
+impl GaussianTemplates {
+    fn frozen(&self) -> PyResult<&secbench_dsp::GaussianTemplates<F>> {
+        if let Some(templates) = self.frozen.get() {
+            return Ok(templates);
+        }
+        check_finite(&self.inner)?;
+        let templates = self.inner.freeze().ok_or_else(|| {
+            PyValueError::new_err("the pooled covariance is not positive definite")
+        })?;
+        Ok(self.frozen.get_or_init(|| templates))
+    }
+
+    fn log_likelihood_inner<'py, S>(
+        &self,
+        data: Bound<'py, PyArray2<S>>,
+        normalize: bool,
+        parallel: bool,
+    ) -> PyResult<Bound<'py, PyArray2<F>>>
+    where
+        S: IntoFloat<F> + Element + Copy + Sync,
+    {
+        let py = data.py();
+        let data = data.readonly();
+        let data = data.as_array();
+        check_pois(data, self.inner.pois())?;
+        let templates = self.frozen()?;
+        let mut ll = templates.log_likelihood_block(data, parallel);
+        if normalize {
+            // Log-posterior probabilities with a uniform prior.
+            ll.axis_iter_mut(Axis(0)).for_each(|mut row| {
+                let max = row.fold(F::NEG_INFINITY, |a, &b| a.max(b));
+                let lse = max + row.iter().map(|&x| (x - max).exp()).sum::<F>().ln();
+                row.mapv_inplace(|x| x - lse);
+            });
+        }
+        Ok(ll.to_pyarray_bound(py))
+    }
+}
+
+#[pymethods]
+impl GaussianTemplates {
+    #[new]
+    pub fn new(pois: Vec<usize>, classes: usize) -> PyResult<Self> {
+        Ok(GaussianTemplates {
+            inner: new_accumulator(pois, classes)?,
+            frozen: OnceLock::new(),
+        })
+    }
+
+    #[getter]
+    pub fn pois(&self) -> Vec<usize> {
+        self.inner.pois().to_vec()
+    }
+
+    #[getter]
+    pub fn num_classes(&self) -> usize {
+        self.inner.num_classes()
+    }
+
+    /// Accumulate a block of traces (of any 8/16-bit integer or float
+    /// dtype) with their labels.
+    #[pyo3(signature = (data, labels, *, parallel=false, chunk_size=4096))]
+    pub fn process_block(
+        &mut self,
+        data: Bound<PyAny>,
+        labels: PyReadonlyArray1<u16>,
+        parallel: bool,
+        chunk_size: usize,
+    ) -> PyResult<()> {
+        self.frozen.take();
+        process_block_into(&mut self.inner, data, labels, parallel, chunk_size)
+    }
+
+    /// Merge templates accumulated on other traces.
+    pub fn merge(&mut self, other: PyRef<GaussianTemplates>) -> PyResult<()> {
+        check_mergeable(&self.inner, &other.inner)?;
+        self.frozen.take();
+        self.inner.merge(&other.inner);
+        Ok(())
+    }
+
+    pub fn samples_per_class<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<u32>> {
+        self.inner.samples_per_class().to_pyarray_bound(py)
+    }
+
+    /// Mean of each class, shape `(classes, len(pois))`.
+    pub fn means<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<F>> {
+        self.inner.means().to_pyarray_bound(py)
+    }
+
+    /// Pooled covariance matrix, shape `(len(pois), len(pois))`.
+    pub fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<F>> {
+        self.inner.covariance().to_pyarray_bound(py)
+    }
+
+    /// Log-likelihood of each class for attack traces, shape
+    /// `(n_traces, classes)` (`-inf` for classes without profiling traces).
+    ///
+    /// With `normalize`, log-posterior probabilities (assuming uniform class
+    /// priors) are returned instead. The output can be given to
+    /// `accumulate_key_scores`.
+    #[pyo3(signature = (data, *, normalize=false, parallel=false))]
+    pub fn log_likelihood<'py>(
+        &self,
+        data: Bound<'py, PyAny>,
+        normalize: bool,
+        parallel: bool,
+    ) -> PyResult<Bound<'py, PyArray2<F>>> {
+        dispatch_dtype!("log_likelihood", data, [i8, i16, u8, u16, f32, f64], |T| {
+            self.log_likelihood_inner(data.downcast_into::<PyArray2<T>>()?, normalize, parallel)
+        })
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Projections of samples containing NaN or infinite values are meaningless.
+fn check_finite(acc: &CondMeanCov<F>) -> PyResult<()> {
+    if acc
+        .means()
+        .iter()
+        .chain(acc.scatter())
+        .all(|x| x.is_finite())
+    {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(
+            "the class means or the within-class scatter are not finite",
+        ))
+    }
+}
+
+fn projection_to_py<'py>(
+    py: Python<'py>,
+    projection: Projection<F>,
+) -> (Bound<'py, PyArray1<F>>, Bound<'py, PyArray2<F>>) {
+    (
+        projection.values.into_pyarray_bound(py),
+        projection.components.into_pyarray_bound(py),
+    )
+}
+
+/// Class means, within-class and between-class scatter matrices.
+///
+/// The scatter matrices over the samples `pois` (e.g., `range(start, stop)`
+/// for a window) are accumulated incrementally with `process_block`.
+/// Accumulators built on different traces can be merged. LDA and class PCA
+/// projections are derived from them.
+#[pyclass]
+#[derive(Clone)]
+pub struct ClassScatter {
+    inner: CondMeanCov<F>,
+}
+
+#[pymethods]
+impl ClassScatter {
+    #[new]
+    pub fn new(pois: Vec<usize>, classes: usize) -> PyResult<Self> {
+        Ok(ClassScatter {
+            inner: new_accumulator(pois, classes)?,
+        })
+    }
+
+    #[getter]
+    pub fn pois(&self) -> Vec<usize> {
+        self.inner.pois().to_vec()
+    }
+
+    #[getter]
+    pub fn num_classes(&self) -> usize {
+        self.inner.num_classes()
+    }
+
+    /// Accumulate a block of traces (of any 8/16-bit integer or float
+    /// dtype) with their labels.
+    #[pyo3(signature = (data, labels, *, parallel=false, chunk_size=4096))]
+    pub fn process_block(
+        &mut self,
+        data: Bound<PyAny>,
+        labels: PyReadonlyArray1<u16>,
+        parallel: bool,
+        chunk_size: usize,
+    ) -> PyResult<()> {
+        process_block_into(&mut self.inner, data, labels, parallel, chunk_size)
+    }
+
+    /// Merge an accumulator built on other traces.
+    pub fn merge(&mut self, other: PyRef<ClassScatter>) -> PyResult<()> {
+        check_mergeable(&self.inner, &other.inner)?;
+        self.inner.merge(&other.inner);
+        Ok(())
+    }
+
+    pub fn samples_per_class<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<u32>> {
+        self.inner.samples_per_class().to_pyarray_bound(py)
+    }
+
+    /// Mean of each class, shape `(classes, len(pois))`.
+    pub fn means<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<F>> {
+        self.inner.means().to_pyarray_bound(py)
+    }
+
+    /// Within-class scatter matrix, shape `(len(pois), len(pois))`.
+    pub fn within_scatter<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<F>> {
+        self.inner.scatter().to_pyarray_bound(py)
+    }
+
+    /// Between-class scatter matrix, shape `(len(pois), len(pois))`.
+    pub fn between_scatter<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<F>> {
+        self.inner.between_scatter().into_pyarray_bound(py)
+    }
+
+    /// Fisher LDA basis, returns the eigenvalues (decreasing) and the
+    /// components, of shape `(n_components, len(pois))`.
+    ///
+    /// Components are unit vectors, traces are projected with
+    /// `X[:, pois] @ components.T`.
+    ///
+    /// Raises `ValueError` if the accumulated samples are not finite.
+    #[pyo3(signature = (n_components=None))]
+    pub fn lda<'py>(
+        &self,
+        py: Python<'py>,
+        n_components: Option<usize>,
+    ) -> PyResult<(Bound<'py, PyArray1<F>>, Bound<'py, PyArray2<F>>)> {
+        check_finite(&self.inner)?;
+        let components = n_components.unwrap_or(self.inner.num_classes() - 1);
+        let projection = self.inner.lda(components).ok_or_else(|| {
+            PyValueError::new_err("the within-class scatter is not positive definite")
+        })?;
+        Ok(projection_to_py(py, projection))
+    }
+
+    /// Class PCA basis (principal components of the class means), returns
+    /// the explained variances (decreasing) and the components, of shape
+    /// `(n_components, len(pois))`.
+    ///
+    /// Raises `ValueError` if the accumulated samples are not finite.
+    #[pyo3(signature = (n_components=None))]
+    pub fn class_pca<'py>(
+        &self,
+        py: Python<'py>,
+        n_components: Option<usize>,
+    ) -> PyResult<(Bound<'py, PyArray1<F>>, Bound<'py, PyArray2<F>>)> {
+        check_finite(&self.inner)?;
+        let components = n_components.unwrap_or(self.inner.pois().len());
+        Ok(projection_to_py(py, self.inner.class_pca(components)))
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
+    }
+}
@@ -63,6 +63,7 @@ fn version() -> (u32, u32, u32) {
 }
 
 #[pyfunction]
+#[allow(clippy::vec_init_then_push)]
 fn features() -> Vec<String> {
     let mut features = Vec::new();
     #[cfg(feature = "secbench_processing")]
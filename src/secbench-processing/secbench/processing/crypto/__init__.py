@@ -29,8 +29,36 @@
 # knowledge of the CeCILL license and that you accept its terms.
 ###
 
-from .aes import AES, AesOps, aes_nist_key
-from .keccak import Keccak_f
+from .aes import (
+    AES,
+    AesOps,
+    aes_decrypt,
+    aes_encrypt,
+    aes_intermediates,
+    aes_labels,
+    aes_nist_key,
+    aes_simulate_masked,
+)
+from .crc import Crc
+from .keccak import Keccak_f, keccak_f1600, keccak_f1600_intermediates, sha3, shake
 from .pcg32 import Pcg32
+from .prng import Prng
 
-__all__ = ["Pcg32", "aes_nist_key", "AES", "AesOps", "Keccak_f"]
\ No newline at end of file
+__all__ = [
+    "Pcg32",
+    "Prng",
+    "Crc",
+    "aes_nist_key",
+    "aes_encrypt",
+    "aes_decrypt",
+    "aes_intermediates",
+    "aes_labels",
+    "aes_simulate_masked",
+    "AES",
+    "AesOps",
+    "Keccak_f",
+    "keccak_f1600",
+    "keccak_f1600_intermediates",
+    "sha3",
+    "shake",
+]
\ No newline at end of file
@@ -34,8 +34,15 @@ from typing import List, Optional, Tuple, Union
 
 import numpy as np
 
+from .._native import secbench_native_import
 from ..models import hamming_weight
 
+aes_encrypt = secbench_native_import("aes_encrypt")
+aes_decrypt = secbench_native_import("aes_decrypt")
+aes_intermediates = secbench_native_import("aes_intermediates")
+aes_labels = secbench_native_import("aes_labels")
+aes_simulate_masked = secbench_native_import("aes_simulate_masked")
+
 # fmt: off
 AES_SBOX_TABLE = np.array([
     0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5,
@@ -635,6 +642,8 @@ def aes_sbox_leakage(k, p: np.ndarray, noise=0.1) -> np.ndarray:
     ``hamming_weight(aes_sbox(k ^ p))``, with some optional noise.
 
     :param noise: Noise magnitude (gaussian noise is added)
+
+    See :func:`aes_simulate_masked` for multi-sample traces of a masked AES.
     """
     err = np.random.normal(0, noise, size=p.shape[0])
     return hamming_weight(aes_sbox(k ^ p)) + err
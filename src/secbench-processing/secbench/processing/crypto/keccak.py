@@ -30,6 +30,13 @@
 
 import numpy as np
 
+from .._native import secbench_native_import
+
+keccak_f1600 = secbench_native_import("keccak_f1600")
+keccak_f1600_intermediates = secbench_native_import("keccak_f1600_intermediates")
+sha3 = secbench_native_import("sha3")
+shake = secbench_native_import("shake")
+
 MAX_ROUNDS = 18
 # fmt: off
 PI_P_MAT = np.array([0, 6, 12, 18, 24, 3, 9, 10, 16, 22, 1, 7, 13, 19, 20, 4, 5, 11, 17, 23, 2, 8, 14, 15, 21], dtype=np.uint8)
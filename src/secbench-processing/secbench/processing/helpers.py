@@ -267,6 +267,11 @@ def key_scores(
 
     :return: the score of each key. This array has shape (n_classes,).
         Where ``n_classes`` is the size of the ``secret_values`` iterator.
+
+    .. seealso::
+
+        :py:func:`secbench.processing.attacks.accumulate_key_scores`, a native
+        (much faster) version that also returns scores at several trace counts.
     """
     scores = []
     for k_hyp in secret_values:
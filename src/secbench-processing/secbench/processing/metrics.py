@@ -37,8 +37,9 @@ import abc
 import functools
 import itertools
 import logging
+from dataclasses import dataclass
 from pathlib import Path
-from typing import Optional
+from typing import Optional, Sequence
 
 import numpy as np
 import numpy.typing as npt
@@ -495,6 +496,11 @@ def identity_fn(X, _y):
 
 
 _CondMeanVar = secbench_native_import("CondMeanVar")
+_select_pois = secbench_native_import("select_pois")
+_merge_poi_windows = secbench_native_import("merge_poi_windows")
+_information_metrics = secbench_native_import("information_metrics")
+MiaHistogram = secbench_native_import("MiaHistogram")
+DistributionTest = secbench_native_import("DistributionTest")
 
 
 class CondMeanVarBase(abc.ABC):
@@ -524,17 +530,7 @@ class CondMeanVarBase(abc.ABC):
         if not isinstance(y, np.uint16):
             y = y.astype(np.uint16)
 
-        impl = self._impl()
-        if X.dtype == np.int8:
-            impl.process_block_i8(X, y)
-        elif X.dtype == np.int16:
-            impl.process_block_i16(X, y)
-        elif X.dtype == np.float32:
-            impl.process_block_f32(X, y)
-        elif X.dtype == np.float64:
-            impl.process_block_f64(X, y)
-        else:
-            raise NotImplementedError(f"unsupported array dtype: {X.dtype}")
+        self._impl().process_block(X, y)
 
     def freeze_snr(self):
         """
@@ -795,6 +791,204 @@ def cond_mean(
     return m
 
 
+@cond_mean_metric
+def mia(
+    X: np.ndarray,
+    y: np.ndarray,
+    num_classes=None,
+    bins: int = 16,
+    value_range=None,
+    bias_correction: str = "miller-madow",
+    parallel: bool = False,
+):
+    """
+    Compute the mutual information between labels and samples (MIA), from
+    joint histograms of labels and quantized samples.
+
+    Unlike the SNR or the correlation, the mutual information also captures
+    non-linear leakage.
+
+    :param X: training data. An array of shape (n_samples, n_features).
+    :param y: Target values. An array of shape (n_samples,)
+        or (n_samples, n_targets).
+    :param num_classes: number of classes (otherwise inferred from the maximum value of labels).
+    :param bins: number of quantization bins of the samples.
+    :param value_range: ``(low, high)`` quantization range (the range of ``X``
+        by default).
+    :param bias_correction: ``"miller-madow"`` or ``"none"``.
+    :param parallel: process targets and samples in parallel.
+    :returns: an array of shape (n_features,) or (n_targets, n_features) of
+        mutual information estimates, in bits.
+
+    For large datasets, a :py:class:`MiaHistogram` accumulator can be fed
+    incrementally (and accumulators can be merged).
+    """
+    accum = _joint_histogram(
+        MiaHistogram, X, y, num_classes, bins, value_range, parallel
+    )
+    return accum.freeze_mi(bias_correction=bias_correction)
+
+
+def chi2_test(
+    X: np.ndarray,
+    y: np.ndarray,
+    num_classes=None,
+    bins: int = 16,
+    value_range=None,
+    parallel: bool = False,
+):
+    """
+    Chi-squared test of independence between labels and quantized samples.
+
+    This test detects any difference between the distributions of the
+    classes (not only differences of means, as the Welch t-test), and
+    supports multiple classes.
+
+    Parameters are the same as :py:func:`mia`.
+
+    :returns: a tuple ``(statistics, pvalues)`` of arrays of shape
+        (n_features,) or (n_targets, n_features).
+
+    For large datasets, a :py:class:`DistributionTest` accumulator can be fed
+    incrementally (and accumulators can be merged).
+    """
+    freeze = DistributionTest.freeze_chi2_test
+    return _histogram_test(X, y, num_classes, bins, value_range, parallel, freeze)
+
+
+def ks_test(
+    X: np.ndarray,
+    y: np.ndarray,
+    num_classes=None,
+    bins: int = 256,
+    value_range=None,
+    parallel: bool = False,
+):
+    """
+    Two-sample Kolmogorov-Smirnov test between classes (e.g., fixed versus
+    random traces).
+
+    With more than two classes, each class is compared with the traces of
+    all other classes, and the largest statistic and the smallest p-value
+    (with a Bonferroni correction) are returned.
+
+    The statistic is computed on samples quantized in ``bins`` bins, it is
+    exact for integer samples when each bin holds a single value (e.g., 8-bit
+    samples with ``value_range=(-128, 128)``). P-values are asymptotic.
+
+    :param X: training data. An array of shape (n_samples, n_features).
+    :param y: Target values. An array of shape (n_samples,)
+        or (n_samples, n_targets).
+    :param num_classes: number of classes (otherwise inferred from the maximum value of labels).
+    :param bins: number of quantization bins of the samples.
+    :param value_range: ``(low, high)`` quantization range (the range of ``X``
+        by default).
+    :param parallel: process targets and samples in parallel.
+    :returns: a tuple ``(statistics, pvalues)`` of arrays of shape
+        (n_features,) or (n_targets, n_features).
+    """
+    freeze = DistributionTest.freeze_ks_test
+    return _histogram_test(X, y, num_classes, bins, value_range, parallel, freeze)
+
+
+def _joint_histogram(cls, X, y, num_classes, bins, value_range, parallel):
+    if num_classes is None:
+        num_classes = np.max(y) + 1
+    if value_range is None:
+        value_range = (float(np.min(X)), float(np.max(X)))
+        if value_range[1] <= value_range[0]:
+            value_range = (value_range[0], value_range[0] + 1.0)
+        else:
+            # Keep the maximum value in the last bin.
+            width = (value_range[1] - value_range[0]) / bins
+            value_range = (value_range[0], value_range[1] + 1e-6 * width)
+    accum = cls(y.shape[1], X.shape[1], num_classes, range=value_range, bins=bins)
+    accum.process_block(X, y.astype(np.uint16), parallel=parallel)
+    return accum
+
+
+def _histogram_test(X, y, num_classes, bins, value_range, parallel, freeze):
+    check_X_y(X, y, multi_output=True)
+    if y.ndim == 1:
+        stats, pvalues = _histogram_test(
+            X, y[:, np.newaxis], num_classes, bins, value_range, parallel, freeze
+        )
+        return stats[0], pvalues[0]
+    assert y.ndim == 2, f"argument 'y' must be 2-dimensional, actual shape={y.shape}"
+    accum = _joint_histogram(
+        DistributionTest, X, y, num_classes, bins, value_range, parallel
+    )
+    return freeze(accum)
+
+
+def select_pois(
+    metrics,
+    k: Optional[int] = None,
+    threshold=None,
+    min_distance: int = 0,
+    window: int = 0,
+    absolute: bool = False,
+    parallel: bool = False,
+):
+    """
+    Select points of interest from leakage metrics.
+
+    Points are selected per target by decreasing metric value. Selection
+    strategies can be combined.
+
+    :param metrics: metric values, an array of shape ``(n_targets, n_samples)``
+        (e.g., from :py:meth:`CondMeanVar.freeze_snr`) or ``(n_samples,)``.
+    :param k: maximum number of points per target.
+    :param threshold: minimum metric value of selected points. Either a single
+        value, or one value per target.
+    :param min_distance: non-maximum suppression: a point closer than
+        ``min_distance`` samples to a point with a higher metric is not selected.
+    :param window: if non-zero, add the ``window`` samples on each side of
+        selected points (overlapping windows are merged).
+    :param absolute: rank points according to the absolute value of the metric
+        (e.g., for t-tests).
+    :param parallel: process targets in parallel.
+    :return: a list with, for each target, the sorted indices of the selected
+        samples (a single array if ``metrics`` is 1-D). NaN values are never
+        selected.
+
+    .. code-block:: python
+
+        snr = cond_mean_var(X, y, num_classes=256).freeze_snr()
+        pois = select_pois(snr, k=10, min_distance=5)
+        X_target_0 = X[:, pois[0]]
+    """
+    metrics = np.asarray(metrics)
+    if metrics.dtype not in (np.float32, np.float64):
+        metrics = metrics.astype(np.float64)
+    pois = _select_pois(
+        np.atleast_2d(metrics),
+        k=k,
+        threshold=threshold,
+        min_distance=min_distance,
+        window=window,
+        absolute=absolute,
+        parallel=parallel,
+    )
+    if metrics.ndim == 1:
+        return pois[0]
+    return pois
+
+
+def poi_windows(pois, half_width: int, n_samples: int) -> list[tuple[int, int]]:
+    """
+    Merge windows around points of interest into disjoint ranges.
+
+    :param pois: sorted indices of points of interest.
+    :param half_width: number of samples added on each side of a point.
+    :param n_samples: number of samples in the traces.
+    :return: a list of ``(start, stop)`` ranges.
+    """
+    return _merge_poi_windows(
+        [int(p) for p in pois], half_width=half_width, n_samples=n_samples
+    )
+
+
 def perceived_information(model, X, y_true, entropy: float) -> float:
     """
     Compute the perceived information of a given model.
@@ -804,11 +998,157 @@ def perceived_information(model, X, y_true, entropy: float) -> float:
     :param y_true: A numpy array of shape ``(n_samples,)`` that contains correct labels associated with data.
     :param entropy: entropy of the labels.
 
+    .. seealso::
+
+        :py:func:`information_from_probas` computes the perceived and
+        hypothetical information with confidence intervals from probabilities.
     """
     pred = model.predict_proba(X)
     return entropy - log_loss(y_true, pred) / np.log(2)
 
 
+@dataclass
+class InformationEstimate:
+    """
+    Information metrics of a model, in bits per trace.
+
+    Intervals are ``(lower, upper)`` bootstrap confidence intervals (NaN when
+    no bootstrap is done).
+    """
+
+    #: Perceived information (or training information, when estimated on the
+    #: training set).
+    perceived: float
+    perceived_interval: tuple[float, float]
+    #: Hypothetical information.
+    hypothetical: float
+    hypothetical_interval: tuple[float, float]
+
+
+def information_from_probas(
+    probas: npt.NDArray,
+    labels: npt.NDArray,
+    prior: Optional[npt.NDArray] = None,
+    min_proba: float = 1e-15,
+    n_bootstrap: int = 0,
+    confidence: float = 0.95,
+    seed: int = 0,
+    parallel: bool = False,
+) -> InformationEstimate:
+    """
+    Compute the perceived (PI) and hypothetical (HI) information of a model
+    from its output probabilities.
+
+    The PI is :math:`H(Y) + E[\\log_2 m(y | x)]` and the HI is
+    :math:`H(Y) + E[\\sum_y m(y | x) \\log_2 m(y | x)]`, expectations being
+    taken per class and weighted by the prior. When ``probas`` are computed on
+    the training set, the PI is the training information (TI).
+
+    :param probas: an array of shape ``(n_samples, n_classes)`` containing
+        the probabilities given by the model (e.g., ``predict_proba``).
+    :param labels: an array of shape ``(n_samples,)`` containing the correct
+        classes.
+    :param prior: prior probabilities of the classes (the label frequencies
+        by default).
+    :param min_proba: probabilities of the correct class are clipped to this
+        value in the PI, since a single zero probability would make it
+        ``-inf``. Use 0 to disable the clipping.
+    :param n_bootstrap: number of bootstrap replicates for confidence intervals.
+    :param confidence: level of the confidence intervals.
+    :param seed: seed of the bootstrap.
+    :param parallel: run bootstrap replicates in parallel.
+    """
+    probas = np.asarray(probas)
+    if probas.dtype not in (np.float32, np.float64):
+        probas = probas.astype(np.float64)
+    r = _information_metrics(
+        probas,
+        np.asarray(labels).astype(np.uint16),
+        prior=None if prior is None else [float(p) for p in prior],
+        min_proba=min_proba,
+        n_bootstrap=n_bootstrap,
+        confidence=confidence,
+        seed=seed,
+        parallel=parallel,
+    )
+    return InformationEstimate(
+        perceived=r[0, 0],
+        perceived_interval=(r[0, 1], r[0, 2]),
+        hypothetical=r[1, 0],
+        hypothetical_interval=(r[1, 1], r[1, 2]),
+    )
+
+
+@dataclass
+class InformationCurve:
+    """
+    Information metrics versus the training set size.
+
+    Metrics are arrays of shape ``(n_sizes, 3)`` containing the estimate and
+    the confidence interval bounds for each training set size.
+    """
+
+    train_sizes: npt.NDArray
+    perceived: npt.NDArray
+    hypothetical: npt.NDArray
+    #: Training information, if training probabilities were given.
+    training: Optional[npt.NDArray] = None
+
+
+def information_learning_curve(
+    train_sizes: Sequence[int],
+    probas: Sequence[npt.NDArray],
+    labels: npt.NDArray,
+    train_probas: Optional[Sequence[npt.NDArray]] = None,
+    train_labels: Optional[Sequence[npt.NDArray]] = None,
+    **kwargs,
+) -> InformationCurve:
+    """
+    Learning curves of the perceived, hypothetical and training information.
+
+    :param train_sizes: size of the training sets.
+    :param probas: for each training set size, probabilities of shape
+        ``(n_samples, n_classes)`` of the model trained on this set, evaluated
+        on the validation set (a 3-D array can also be given).
+    :param labels: labels of the validation set.
+    :param train_probas: for each training set size, probabilities of the
+        model on its own training set (optional).
+    :param train_labels: labels of each training set (required with
+        ``train_probas``).
+    :param kwargs: passed to :py:func:`information_from_probas`.
+    """
+    if len(probas) != len(train_sizes):
+        raise ValueError("one probability array is required per training set size")
+    if train_probas is not None and (
+        train_labels is None
+        or len(train_probas) != len(train_sizes)
+        or len(train_labels) != len(train_sizes)
+    ):
+        raise ValueError(
+            "train_probas and train_labels must be given for each training set size"
+        )
+
+    def as_row(estimate, which):
+        value = getattr(estimate, which)
+        return [value, *getattr(estimate, f"{which}_interval")]
+
+    estimates = [information_from_probas(p, labels, **kwargs) for p in probas]
+    training = None
+    if train_probas is not None:
+        training = np.array(
+            [
+                as_row(information_from_probas(p, y, **kwargs), "perceived")
+                for p, y in zip(train_probas, train_labels)
+            ]
+        )
+    return InformationCurve(
+        train_sizes=np.asarray(train_sizes),
+        perceived=np.array([as_row(e, "perceived") for e in estimates]),
+        hypothetical=np.array([as_row(e, "hypothetical") for e in estimates]),
+        training=training,
+    )
+
+
 def fisher_transformation(rho: npt.NDArray, n_traces: int):
     """
     Fisher's transformation to derive a test statistic from correlation coefficient.
@@ -829,6 +1169,11 @@ def pvalues_from_statistic(s_values: npt.NDArray) -> npt.NDArray:
     Such approximation holds when the t-distribution has a high degree of freedom (which is the case with many SCA traces)
 
     :params s_values: Values of the statistic
+
+    .. seealso::
+
+        :py:func:`chi2_test` and :py:func:`ks_test` return p-values of
+        distribution-level tests directly.
     """
     return 2 * (1 - norm.cdf(np.abs(s_values)))
 
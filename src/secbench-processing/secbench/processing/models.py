@@ -36,10 +36,22 @@ import sys
 import numpy as np
 import numpy.typing as npt
 
+from ._native import secbench_native_processing as _native
 from .helpers import InvalidInputError, UnsignedScalar, check_array
 
 _HW8_TABLE = np.array([bin(x).count("1") for x in range(256)], dtype=np.uint8)
 
+# Types supported by native leakage models (native byte order only).
+_NATIVE_DTYPES = tuple(
+    np.dtype(t) for t in (np.uint8, np.uint16, np.uint32, np.uint64)
+)
+
+
+def _use_native(*arrays) -> bool:
+    return _native is not None and all(
+        isinstance(a, np.ndarray) and a.dtype in _NATIVE_DTYPES for a in arrays
+    )
+
 
 def hamming_weight(x: npt.NDArray[UnsignedScalar] | int):
     """
@@ -68,6 +80,8 @@ def hamming_weight(x: npt.NDArray[UnsignedScalar] | int):
         return hamming_weight_64(int(x))
     elif isinstance(x, np.ndarray):
         check_array(x, dtype=(np.uint8, np.uint16, np.uint32, np.uint64))
+        if _use_native(x):
+            return _native.hamming_weight(x)
         if x.dtype == np.dtype("uint8"):
             return hamming_weight_8(x)
         elif x.dtype == np.dtype("uint16"):
@@ -97,6 +111,8 @@ def hamming_distance(x: np.ndarray, y: np.ndarray):
     array([2, 0, 5], dtype=uint8)
 
     """
+    if _use_native(x, y) and x.dtype == y.dtype and x.shape == y.shape:
+        return _native.hamming_distance(x, y)
     return hamming_weight(x ^ y)
 
 
@@ -178,6 +194,8 @@ def unpackbits(y: np.ndarray, count: int = 0) -> np.ndarray:
         needed to encode the integer elements. The bits are returned in little endian
         order (least significant first).
     """
+    if _use_native(y):
+        return _native.unpackbits(y, count=count)
     output_shape = np.append(y.shape, y.itemsize * 8)
     byteorder = y.dtype.byteorder
     if byteorder == ">":
@@ -216,6 +234,10 @@ def lra_unpackbits(
 
         The same rules apply on the input as for :py:func:`unpackbits`.
     """
+    if _use_native(y):
+        return _native.lra_unpackbits(
+            y, center=center, with_intercept=with_intercept, count=count
+        )
     y_bits = unpackbits(y, count=count)
     if center:
         y_bits = (2.0 * (y_bits - 0.5)).astype(np.int8)
@@ -260,6 +282,10 @@ def lra_unpackbits_2nd_order(
         little endian order (least significant first). The intercept is added in the
         last column.
     """
+    if _use_native(y):
+        return _native.lra_unpackbits_2nd_order(
+            y, with_intercept=with_intercept, count=count, center=center
+        )
     y_bits = unpackbits(y, count=count)
     n_bits = y_bits.shape[-1]
     comb = _gf2n_order2_decomp_indices(n_bits)
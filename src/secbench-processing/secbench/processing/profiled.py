@@ -41,12 +41,16 @@ from sklearn.decomposition import PCA
 from sklearn.preprocessing import LabelEncoder
 from sklearn.utils.validation import check_is_fitted
 
+from ._native import secbench_native_import
 from ._network import ConvLayerSpec, DenseLayerSpec
 from .helpers import key_scores, rank_of
 from .metrics import cond_mean
 
 logger = logging.getLogger()
 
+GaussianTemplates = secbench_native_import("GaussianTemplates")
+ClassScatter = secbench_native_import("ClassScatter")
+
 
 class ProfiledAttack(abc.ABC):
     """
@@ -170,14 +170,28 @@ def downsample(
     return x_s, y_s
 
 
-_moving_sum_dispatch = {
-    "int8": secbench_native_import("moving_sum_i8"),
-    "int16": secbench_native_import("moving_sum_i16"),
-    "float32": secbench_native_import("moving_sum_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+_INPUT_TYPES = (np.int8, np.int16, np.uint8, np.uint16, np.float32, np.float64)
+_OUTPUT_TYPES = (np.float32, np.float64)
+
+_moving_sum = secbench_native_import("moving_sum")
+_fft_filter = secbench_native_import("fft_filter")
+_phase_correlation = secbench_native_import("phase_correlation")
+_rfft_mag = secbench_native_import("rfft_mag")
+_match_euclidean = secbench_native_import("match_euclidean")
+_match_correlation = secbench_native_import("match_correlation")
+_sliding_mean = secbench_native_import("sliding_mean")
+_sliding_var = secbench_native_import("sliding_var")
+_sliding_std = secbench_native_import("sliding_std")
+_sliding_skew = secbench_native_import("sliding_skew")
+_sliding_kurt = secbench_native_import("sliding_kurt")
+_trace_stats = secbench_native_import("trace_stats")
+_outlier_keep_mask = secbench_native_import("outlier_keep_mask")
+
+#: Columns of the output of :py:func:`trace_stats`.
+TRACE_STATS_COLUMNS = ("min", "max", "mean", "std", "saturated")
+
+
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def moving_sum(
     X: ScaArray,
     *,
@@ -186,7 +200,7 @@ def moving_sum(
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
 ):
     """
     Compute a windowed sum of the input array.
@@ -202,27 +216,20 @@ def moving_sum(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    fn = _moving_sum_dispatch[X.dtype.name]
-    return fn(
+    return _moving_sum(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         scale=scale,
+        dtype=dtype,
     )
 
 
-_fft_filter = {
-    "int8": secbench_native_import("fft_filter_i8"),
-    "int16": secbench_native_import("fft_filter_i16"),
-    "float32": secbench_native_import("fft_filter_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def fft_filter(
     X: ScaArray,
     kernel: ScaArray1D,
@@ -230,7 +237,7 @@ def fft_filter(
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
     two_pass=False,
 ):
     """
@@ -242,7 +249,7 @@ def fft_filter(
     parallelism.
 
     :param X: a numpy array of shape ``(n_samples, n_features)`` or ``(n_features,)``.
-    :param kernel: a numpy array of shape ``(n_coeffs,)`` and dtype ``np.float32``.
+    :param kernel: a numpy array of shape ``(n_coeffs,)``, converted to the output type.
         The kernel must be smaller that the number of features in the input.
     :param two_pass: if ``True``, performs left filtering pass then right
         filtering pass. This provides functionality similar to
@@ -255,32 +262,26 @@ def fft_filter(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    check_array(kernel, dtype=np.float32, ndim=1)
+    kernel = np.ascontiguousarray(kernel, dtype=dtype)
+    check_array(kernel, ndim=1)
     if kernel.shape[0] > X.shape[1]:
         raise InvalidInputError(
             f"kernel has shape {kernel.shape}, which is larger than input traces (shape: {X.shape}), filter cannot be applied"
         )
-    fn = _fft_filter[X.dtype.name]
-    return fn(
+    return _fft_filter(
         output,
         X,
         kernel,
         parallel=parallel,
         chunk_size=chunk_size,
         two_pass=two_pass,
+        dtype=dtype,
     )
 
 
-_phase_correlation = {
-    "int8": secbench_native_import("phase_correlation_i8"),
-    "int16": secbench_native_import("phase_correlation_i16"),
-    "float32": secbench_native_import("phase_correlation_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def phase_correlation(
     X: ScaArray,
     kernel: ScaArray1D,
@@ -288,13 +289,13 @@ def phase_correlation(
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
 ):
     """
     Compute phase correlation between an input signal and a kernel.
 
     :param X: a numpy array of shape `(n_samples, n_features)` or `(n_features,)`.
-    :param kernel: a numpy array of shape `(n_coeffs,)` and dtype `np.float32`.
+    :param kernel: a numpy array of shape `(n_coeffs,)`, converted to the output type.
         The kernel must be smaller that the number of features in the input.
 
     :param output: if given, compute the result in this array. Otherwise,
@@ -304,38 +305,32 @@ def phase_correlation(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    check_array(kernel, dtype=np.float32, ndim=1)
+    kernel = np.ascontiguousarray(kernel, dtype=dtype)
+    check_array(kernel, ndim=1)
     if kernel.shape[0] > X.shape[1]:
         raise InvalidInputError(
             f"kernel has shape {kernel.shape}, which is larger than input traces (shape: {X.shape}), filter cannot be applied"
         )
-    fn = _phase_correlation[X.dtype.name]
-    return fn(
+    return _phase_correlation(
         output,
         X,
         kernel,
         parallel=parallel,
         chunk_size=chunk_size,
+        dtype=dtype,
     )
 
 
-_rfft_mag = {
-    "int8": secbench_native_import("rfft_mag_i8"),
-    "int16": secbench_native_import("rfft_mag_i16"),
-    "float32": secbench_native_import("rfft_mag_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def rfft_mag(
     X: ScaArray,
     *,
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
 ):
     """
     Magnitude of the real Fourier transform of the signal.
@@ -349,25 +344,18 @@ def rfft_mag(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    fn = _rfft_mag[X.dtype.name]
-    return fn(
+    return _rfft_mag(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
+        dtype=dtype,
     )
 
 
-_match_euclidean = {
-    "int8": secbench_native_import("match_euclidean_i8"),
-    "int16": secbench_native_import("match_euclidean_i16"),
-    "float32": secbench_native_import("match_euclidean_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def match_euclidean(
     X: ScaArray,
     kernel: ScaArray1D,
@@ -375,7 +363,7 @@ def match_euclidean(
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
 ):
     """
     Match a given kernel using Euclidean distance.
@@ -399,31 +387,25 @@ def match_euclidean(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    check_array(kernel, dtype=np.float32, ndim=1)
+    kernel = np.ascontiguousarray(kernel, dtype=dtype)
+    check_array(kernel, ndim=1)
     if kernel.shape[0] > X.shape[1]:
         raise InvalidInputError(
             f"kernel has shape {kernel.shape}, which is larger than input traces (shape: {X.shape}), filter cannot be applied"
         )
-    fn = _match_euclidean[X.dtype.name]
-    return fn(
+    return _match_euclidean(
         output,
         X,
         kernel,
         parallel=parallel,
         chunk_size=chunk_size,
+        dtype=dtype,
     )
 
 
-_match_correlation = {
-    "int8": secbench_native_import("match_correlation_i8"),
-    "int16": secbench_native_import("match_correlation_i16"),
-    "float32": secbench_native_import("match_correlation_f32"),
-}
-
-
-@transform_2d(input_types=(np.int8, np.int16, np.float32), output_types=(np.float32,))
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def match_correlation(
     X,
     kernel,
@@ -431,7 +413,7 @@ def match_correlation(
     output=None,
     parallel=False,
     chunk_size: int | None = None,
-    dtype=np.float32,
+    dtype=None,
 ):
     """
     Match a given kernel using normalized cross-correlation.
@@ -448,54 +430,25 @@ def match_correlation(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-    check_array(kernel, dtype=np.float32, ndim=1)
+    kernel = np.ascontiguousarray(kernel, dtype=dtype)
+    check_array(kernel, ndim=1)
     if kernel.shape[0] > X.shape[1]:
         raise InvalidInputError(
             f"kernel has shape {kernel.shape}, which is larger than input traces (shape: {X.shape}), filter cannot be applied"
         )
-    fn = _match_correlation[X.dtype.name]
-    return fn(
+    return _match_correlation(
         output,
         X,
         kernel,
         parallel=parallel,
         chunk_size=chunk_size,
+        dtype=dtype,
     )
 
 
-types_tuple = [
-    (np.float32, np.int8),
-    (np.float32, np.int16),
-    (np.float32, np.float32),
-    (np.float64, np.float64),
-]
-
-types_rust_conv = {
-    np.float32.__name__: "f32",
-    np.float64.__name__: "f64",
-    np.int8.__name__: "i8",
-    np.int16.__name__: "i16",
-}
-
-
-def generate_tuple_fn_dict(types_tuple, fn_name: str):
-    return {
-        (j.__name__, k.__name__): secbench_native_import(
-            f"{fn_name}_{types_rust_conv[j.__name__]}_{types_rust_conv[k.__name__]}"
-        )
-        for j, k in types_tuple
-    }
-
-
-_sliding_mean = generate_tuple_fn_dict(types_tuple, "sliding_mean")
-
-
-@transform_2d(
-    input_types=(np.int8, np.int16, np.float32, np.float64),
-    output_types=(np.float32, np.float64),
-)
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def sliding_mean(
     X: ScaArray,
     *,
@@ -520,31 +473,20 @@ def sliding_mean(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-
-    try:
-        fn = _sliding_mean[(dtype.__name__, X.dtype.name)]  # type: ignore
-    except KeyError as err:
-        raise TypeError(f"invalid input type for X: {dtype}") from err
-
-    return fn(
+    return _sliding_mean(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         padding_value=padding_value,
+        dtype=dtype,
     )
 
 
-_sliding_var = generate_tuple_fn_dict(types_tuple, "sliding_var")
-
-
-@transform_2d(
-    input_types=(np.int8, np.int16, np.float32, np.float64),
-    output_types=(np.float32, np.float64),
-)
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def sliding_var(
     X: ScaArray,
     *,
@@ -569,31 +511,20 @@ def sliding_var(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-
-    try:
-        fn = _sliding_var[(dtype.__name__, X.dtype.name)]  # type: ignore
-    except KeyError as err:
-        raise TypeError(f"invalid input type for X: {dtype}") from err
-
-    return fn(
+    return _sliding_var(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         padding_value=padding_value,
+        dtype=dtype,
     )
 
 
-_sliding_std = generate_tuple_fn_dict(types_tuple, "sliding_std")
-
-
-@transform_2d(
-    input_types=(np.int8, np.int16, np.float32, np.float64),
-    output_types=(np.float32, np.float64),
-)
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def sliding_std(
     X: ScaArray,
     *,
@@ -618,31 +549,20 @@ def sliding_std(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-
-    try:
-        fn = _sliding_std[(dtype.__name__, X.dtype.name)]  # type: ignore
-    except KeyError as err:
-        raise TypeError(f"invalid input type for X: {dtype}") from err
-
-    return fn(
+    return _sliding_std(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         padding_value=padding_value,
+        dtype=dtype,
     )
 
 
-_sliding_skew = generate_tuple_fn_dict(types_tuple, "sliding_skew")
-
-
-@transform_2d(
-    input_types=(np.int8, np.int16, np.float32, np.float64),
-    output_types=(np.float32, np.float64),
-)
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def sliding_skew(
     X: ScaArray,
     *,
@@ -667,31 +587,20 @@ def sliding_skew(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-
-    try:
-        fn = _sliding_skew[(dtype.__name__, X.dtype.name)]  # type: ignore
-    except KeyError as err:
-        raise TypeError(f"invalid input type for X: {dtype}") from err
-
-    return fn(
+    return _sliding_skew(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         padding_value=padding_value,
+        dtype=dtype,
     )
 
 
-_sliding_kurt = generate_tuple_fn_dict(types_tuple, "sliding_kurt")
-
-
-@transform_2d(
-    input_types=(np.int8, np.int16, np.float32, np.float64),
-    output_types=(np.float32, np.float64),
-)
+@transform_2d(input_types=_INPUT_TYPES, output_types=_OUTPUT_TYPES)
 def sliding_kurt(
     X: ScaArray,
     *,
@@ -716,19 +625,84 @@ def sliding_kurt(
         environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
         done sequentially.
     :param chunk_size: number of rows of ``X`` processed in parallel.
-    :param dtype: output type (only ``np.float32`` is exposed currently).
+    :param dtype: output type (``np.float32`` or ``np.float64``).
     """
-
-    try:
-        fn = _sliding_kurt[(dtype.__name__, X.dtype.name)]  # type: ignore
-    except KeyError as err:
-        raise TypeError(f"invalid input type for X: {dtype}") from err
-
-    return fn(
+    return _sliding_kurt(
         output,
         X,
         parallel=parallel,
         chunk_size=chunk_size,
         window_size=window_size,
         padding_value=padding_value,
-    )
\ No newline at end of file
+        dtype=dtype,
+    )
+
+
+@transform_2d(
+    input_types=(np.int8, np.int16, np.uint8, np.uint16), output_types=(np.float32,)
+)
+def trace_stats(
+    X: ScaArray,
+    *,
+    output=None,
+    parallel=False,
+    chunk_size: int | None = None,
+    dtype=None,
+):
+    """
+    Compute per-trace statistics of raw ADC data.
+
+    The output columns are given by :py:data:`TRACE_STATS_COLUMNS`: minimum,
+    maximum, mean, standard deviation (same as ``np.std``) and number of
+    samples equal to the minimum or maximum value of the input type
+    (saturated samples). Empty traces have NaN statistics.
+
+    :param X: a numpy array of shape ``(n_samples, n_features)`` or ``(n_features,)``.
+
+    :param output: if given, compute the result in this array. Otherwise,
+        an output array will be allocated.
+    :param parallel: if ``True``, processes groups of ``chunk_size`` rows
+        of ``X`` in parallel. The number of threads is defined by
+        environment variable `RAYON_NUM_THREADS`. Otherwise, processing is
+        done sequentially.
+    :param chunk_size: number of rows of ``X`` processed in parallel.
+    :param dtype: output type (only ``np.float32`` is supported).
+    :returns: an array of shape ``(n_samples, 5)`` or ``(5,)``.
+    """
+    return _trace_stats(
+        output,
+        X,
+        parallel=parallel,
+        chunk_size=chunk_size,
+        dtype=dtype,
+    )
+
+
+def outlier_keep_mask(
+    stats: np.ndarray, *, mad_threshold: float = 5.0, max_saturated: int | None = 0
+) -> np.ndarray:
+    """
+    Compute a mask of traces to keep from the output of :py:func:`trace_stats`.
+
+    A trace is rejected if its minimum, maximum, mean or standard deviation
+    is NaN or deviates from the median over all traces by more than
+    ``mad_threshold`` times the median absolute deviation (scaled by 1.4826,
+    so that the threshold is expressed in standard deviations for Gaussian
+    data), or if it has more than ``max_saturated`` saturated samples.
+
+    :param stats: an array of shape ``(n_traces, 5)``.
+    :param mad_threshold: rejection threshold.
+    :param max_saturated: maximum number of saturated samples allowed per
+        trace (no limit if ``None``).
+    :returns: a boolean array of shape ``(n_traces,)``.
+
+    .. code-block:: python
+
+        mask = outlier_keep_mask(trace_stats(X, parallel=True))
+        X, y = X[mask], y[mask]
+    """
+    stats = np.ascontiguousarray(stats, dtype=np.float32)
+    check_array(stats, ndim=2, array_name="stats")
+    return _outlier_keep_mask(
+        stats, mad_threshold=mad_threshold, max_saturated=max_saturated
+    )
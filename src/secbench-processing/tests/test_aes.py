@@ -32,6 +32,7 @@
 import numpy as np
 import pytest
 
+from secbench.processing import secbench_native_installed
 from secbench.processing.crypto.aes import (
     AES,
     AesOps,
@@ -39,12 +40,23 @@ from secbench.processing.crypto.aes import (
     aes_inv_expand_key,
     aes_inv_expand_key_step,
     aes_nist_key,
+    aes_decrypt,
+    aes_encrypt,
+    aes_intermediates,
+    aes_inv_sbox,
+    aes_labels,
+    aes_sbox,
+    aes_simulate_masked,
     biased_hd_plaintexts,
     biased_state_plaintexts,
     generate_plaintexts,
     generate_round_states,
 )
 
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
 
 def test_sub_bytes():
     pts = np.random.randint(0, 256, size=(200, 16), dtype=np.uint8)
@@ -349,4 +361,183 @@ def test_hd_bias_state(tmp_path):
                             if sign == "negative":
                                 assert np.all(states2[:, b] == 0)
                             assert np.all((states1 ^ states2)[labels == 1, b] == 255)
-                            assert np.all((states1 ^ states2)[labels == 0, b] == 0)
\ No newline at end of file
+                            assert np.all((states1 ^ states2)[labels == 0, b] == 0)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("order", [0, 1, 2])
+def test_aes_simulate_masked(order):
+    key = aes_nist_key()
+    pts = np.random.randint(0, 256, size=(50, 16), dtype=np.uint8)
+    traces = aes_simulate_masked(pts, key, order=order, model="identity", noise=0.0)
+    n_shares = order + 1
+    # Initial AddRoundKey, SubBytes, ShiftRows, MixColumns, AddRoundKey.
+    assert traces.shape == (50, 5 * 16 * n_shares)
+
+    shares = traces.reshape(50, 5, n_shares, 16).astype(np.uint8)
+    sbox_out = np.bitwise_xor.reduce(shares[:, 1], axis=1)
+    np.testing.assert_equal(sbox_out, AesOps.sub_bytes(pts ^ key))
+
+    # Reproducible, including in parallel and across batches.
+    a = aes_simulate_masked(pts, key, order=order, seed=3, max_delay=4)
+    b = aes_simulate_masked(pts, key, order=order, seed=3, max_delay=4, parallel=True)
+    c = aes_simulate_masked(pts[10:], key, order=order, seed=3, max_delay=4, offset=10)
+    np.testing.assert_equal(a, b)
+    np.testing.assert_equal(a[10:], c)
+
+    with pytest.raises(ValueError):
+        aes_simulate_masked(pts, key, model="unknown")
+    for noise in [-1.0, np.nan, np.inf]:
+        with pytest.raises(ValueError, match="noise"):
+            aes_simulate_masked(pts, key, noise=noise)
+
+
+# FIPS-197, Appendix C: (key, ciphertext) for AES-128, AES-192 and AES-256.
+FIPS197_PLAINTEXT = "00112233445566778899aabbccddeeff"
+FIPS197_VECTORS = [
+    ("000102030405060708090a0b0c0d0e0f", "69c4e0d86a7b0430d8cdb78070b4c55a"),
+    (
+        "000102030405060708090a0b0c0d0e0f1011121314151617",
+        "dda97ca4864cdfe06eaf70a0ec0d7191",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "8ea2b7ca516745bfeafc49904b496089",
+    ),
+]
+
+
+def _hex_row(s):
+    return np.frombuffer(bytes.fromhex(s), dtype=np.uint8)[np.newaxis, :]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("key, ciphertext", FIPS197_VECTORS)
+def test_native_aes_fips197(key, ciphertext):
+    key, pt, ct = _hex_row(key), _hex_row(FIPS197_PLAINTEXT), _hex_row(ciphertext)
+    np.testing.assert_equal(aes_encrypt(pt, key), ct)
+    np.testing.assert_equal(aes_decrypt(ct, key), pt)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("parallel", [False, True])
+def test_native_aes_batch(parallel):
+    pts = np.random.randint(0, 256, size=(100, 16), dtype=np.uint8)
+    keys = np.random.randint(0, 256, size=(100, 16), dtype=np.uint8)
+    cts = aes_encrypt(pts, keys, parallel=parallel)
+    for i in range(0, 100, 17):
+        np.testing.assert_equal(cts[i], AES(keys[i]).encrypt(pts[i : i + 1])[0])
+    np.testing.assert_equal(aes_decrypt(cts, keys, parallel=parallel), pts)
+
+    # A single key is shared by all blocks.
+    key = aes_nist_key()
+    np.testing.assert_equal(
+        aes_encrypt(pts, key[np.newaxis, :]), AES(key).encrypt(pts)
+    )
+    with pytest.raises(ValueError):
+        aes_encrypt(pts, keys[:, :15])
+
+
+@secbench_ffi_testcase
+def test_native_aes_intermediates():
+    key = aes_nist_key()
+    keys = key[np.newaxis, :]
+    pts = np.random.randint(0, 256, size=(50, 16), dtype=np.uint8)
+    aes = AES(key)
+    ops = {
+        "sub_bytes": AesOps.sub_bytes,
+        "shift_rows": AesOps.shift_rows,
+        "mix_columns": AesOps.mix_columns,
+        "add_round_key": AesOps.add_round_key,
+    }
+    np.testing.assert_equal(
+        aes_intermediates(pts, keys, target="add_round_key", round=0), pts ^ key
+    )
+    for r in range(1, 11):
+        for name, op in ops.items():
+            if r == 10 and name == "mix_columns":
+                continue
+            expected = aes.encrypt(pts, stop_round=r, stop_after=op)
+            actual = aes_intermediates(pts, keys, target=name, round=r, parallel=True)
+            np.testing.assert_equal(actual, expected, err_msg=f"{name}, round {r}")
+
+    # Distance between the last round input and the ciphertext.
+    last_round_in = aes.encrypt(pts, stop_round=9)
+    np.testing.assert_equal(
+        aes_intermediates(pts, keys, target="last_round_hd"),
+        last_round_in ^ aes.encrypt(pts),
+    )
+    with pytest.raises(ValueError):
+        aes_intermediates(pts, keys, target="mix_columns", round=10)
+    with pytest.raises(ValueError):
+        aes_intermediates(pts, keys, target="sub_bytes")
+
+
+def _hw(x):
+    return np.unpackbits(x.astype(np.uint8)[..., np.newaxis], axis=-1).sum(axis=-1)
+
+
+def _labels_model(data, selection, k):
+    x = data ^ np.uint8(k)
+    if selection == "sbox_out":
+        return aes_sbox(x)
+    if selection == "sbox_out_hw":
+        return _hw(aes_sbox(x))
+    if selection == "sbox_out_hd_in":
+        return _hw(x ^ aes_sbox(x))
+    if selection == "inv_sbox_out":
+        return aes_inv_sbox(x)
+    assert selection == "inv_sbox_out_hd"
+    # The state register holding the last round S-box input of byte i is
+    # overwritten by the ciphertext byte that ShiftRows moves to position i.
+    return _hw(aes_inv_sbox(x) ^ AesOps.shift_rows(data))
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize(
+    "selection",
+    ["sbox_out", "sbox_out_hw", "sbox_out_hd_in", "inv_sbox_out", "inv_sbox_out_hd"],
+)
+def test_native_aes_labels(selection):
+    data = np.random.randint(0, 256, size=(30, 16), dtype=np.uint8)
+    labels = aes_labels(data, selection=selection)
+    assert labels.shape == (30, 16 * 256)
+    assert labels.dtype == np.uint16
+    labels = labels.reshape(30, 16, 256)
+    for k in [0, 1, 0x53, 0xA7, 255]:
+        np.testing.assert_equal(labels[:, :, k], _labels_model(data, selection, k))
+
+    sub = aes_labels(data, selection=selection, bytes=[5, 2], guesses=[7, 9])
+    np.testing.assert_equal(sub, labels[:, [5, 5, 2, 2], [7, 9, 7, 9]])
+    np.testing.assert_equal(
+        aes_labels(data, selection=selection, parallel=True).reshape(30, 16, 256),
+        labels,
+    )
+
+
+@secbench_ffi_testcase
+def test_native_aes_labels_last_round():
+    key = aes_nist_key()
+    pts = np.random.randint(0, 256, size=(40, 16), dtype=np.uint8)
+    aes = AES(key)
+    cts = aes.encrypt(pts)
+    k10 = aes_expand_key(key)[10]
+    # Hamming distance of a register overwritten by the ciphertext in the last round.
+    hd = _hw(aes.encrypt(pts, stop_round=9) ^ cts)
+
+    labels = aes_labels(cts, selection="inv_sbox_out_hd").reshape(40, 16, 256)
+    for i in range(16):
+        # Byte i of the ciphertext comes from the register moved there by ShiftRows.
+        src = AesOps.shift_rows(np.arange(16, dtype=np.uint8)[np.newaxis, :])[0, i]
+        np.testing.assert_equal(labels[:, i, k10[i]], hd[:, src], err_msg=f"byte {i}")
+    np.testing.assert_equal(
+        labels[:, np.arange(16), k10], AesOps.shift_rows(hd.astype(np.uint8))
+    )
+
+    with pytest.raises(ValueError):
+        aes_labels(cts[:, :8], selection="inv_sbox_out_hd")
+    with pytest.raises(ValueError):
+        aes_labels(cts, selection="unknown")
+    with pytest.raises(ValueError):
+        aes_labels(cts, selection="sbox_out", bytes=[16])
+
@@ -0,0 +1,233 @@
+# Copyright CEA (Commissariat à l'énergie atomique et aux
+# énergies alternatives) (2017-2025)
+#
+# This software is governed by the CeCILL  license under French law and
+# abiding by the rules of distribution of free software.  You can  use,
+# modify and/ or redistribute the software under the terms of the CeCILL
+# license as circulated by CEA, CNRS and INRIA at the following URL
+# "http://www.cecill.info".
+#
+# As a counterpart to the access to the source code and  rights to copy,
+# modify and redistribute granted by the license, users are provided only
+# with a limited warranty  and the software's author,  the holder of the
+# economic rights,  and the successive licensors  have only  limited
+# liability.
+#
+# In this respect, the user's attention is drawn to the risks associated
+# with loading,  using,  modifying and/or developing or reproducing the
+# software by the user in light of its specific status of free software,
+# that may mean  that it is complicated to manipulate,  and  that  also
+# therefore means  that it is reserved for developers  and  experienced
+# professionals having in-depth computer knowledge. Users are therefore
+# encouraged to load and test the software's suitability as regards their
+# requirements in conditions enabling the security of their systems and/or
+# data to be ensured and,  more generally, to use and operate it in the
+# same conditions as regards security.
+#
+# The fact that you are presently reading this means that you have had
+# knowledge of the CeCILL license and that you accept its terms.
+
+import copy
+
+import itertools
+
+import numpy as np
+import pytest
+
+from secbench.processing import secbench_native_installed
+from secbench.processing.attacks import (
+    accumulate_key_scores,
+    enumerate_keys,
+    guessing_entropy,
+    key_rank_bounds,
+)
+from secbench.processing.crypto.aes import (
+    aes_encrypt,
+    aes_labels,
+    aes_nist_key,
+    aes_sbox,
+)
+from secbench.processing.helpers import key_scores, rank_of
+
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
+
+def _log_probas(n_traces, n_classes, dtype=np.float64):
+    p = np.random.uniform(0.01, 1, size=(n_traces, n_classes))
+    return np.log(p / p.sum(axis=1, keepdims=True)).astype(dtype)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.float32, np.float64])
+@pytest.mark.parametrize("parallel", [False, True])
+def test_accumulate_key_scores_table(dtype, parallel):
+    lp = _log_probas(200, 16, dtype)
+    labels = np.random.randint(0, 16, size=(40, 200), dtype=np.uint16)
+    scores = accumulate_key_scores(
+        lp, labels=labels, checkpoints=[0, 10, 200], parallel=parallel
+    )
+    assert scores.shape == (3, 40)
+    np.testing.assert_equal(scores[0], 0)
+    expected = key_scores(lp[:10], lambda h: labels[h, :10], range(40))
+    np.testing.assert_allclose(scores[1], expected, rtol=1e-5)
+    expected = key_scores(lp, lambda h: labels[h], range(40))
+    np.testing.assert_allclose(scores[2], expected, rtol=1e-5)
+
+
+@secbench_ffi_testcase
+def test_accumulate_key_scores_aes():
+    pts = np.random.randint(0, 256, size=(100, 16), dtype=np.uint8)
+    lp = _log_probas(100, 256)
+    scores = accumulate_key_scores(lp, data=pts, selection="sbox_out", byte=3)
+    expected = key_scores(lp, lambda k: aes_sbox(pts[:, 3] ^ k), range(256))
+    assert scores.shape == (1, 256)
+    np.testing.assert_allclose(scores[0], expected)
+
+    # Non-diagonal byte, whose last round register is another ciphertext byte.
+    cts = aes_encrypt(pts, aes_nist_key()[np.newaxis, :])
+    labels = aes_labels(cts, selection="inv_sbox_out_hd", bytes=[1])
+    scores = accumulate_key_scores(
+        lp[:, :9], data=cts, selection="inv_sbox_out_hd", byte=1
+    )
+    expected = accumulate_key_scores(lp[:, :9], labels=np.ascontiguousarray(labels.T))
+    np.testing.assert_allclose(scores, expected)
+
+    with pytest.raises(ValueError):
+        # Not enough classes for the S-box output.
+        accumulate_key_scores(lp[:, :9], data=pts, selection="sbox_out")
+    with pytest.raises(ValueError):
+        accumulate_key_scores(lp, data=pts, selection="sbox_out", checkpoints=[101])
+    with pytest.raises(ValueError):
+        accumulate_key_scores(lp)
+
+
+@secbench_ffi_testcase
+def test_guessing_entropy():
+    n_traces, n_hyp, key = 50, 32, 5
+    scores = np.random.normal(size=(n_traces, n_hyp))
+    scores[:, key] += 0.5
+    ge, sr, pct = guessing_entropy(
+        scores, key, n_experiments=200, percentiles=[0, 50, 100], seed=1
+    )
+    assert ge.shape == sr.shape == (n_traces,)
+    assert pct.shape == (3, n_traces)
+    assert np.all((ge >= 0) & (ge < n_hyp))
+    assert np.all((sr >= 0) & (sr <= 1))
+    assert np.all(pct[0] <= pct[1]) and np.all(pct[1] <= pct[2])
+    # The attack converges.
+    assert ge[-1] < ge[0]
+    # All traces are used at the last checkpoint, whatever the ordering.
+    assert ge[-1] == rank_of(scores.sum(axis=0), key, randomize=False)
+
+    par = guessing_entropy(
+        scores, key, n_experiments=200, percentiles=[0, 50, 100], seed=1, parallel=True
+    )
+    np.testing.assert_equal(par[0], ge)
+    np.testing.assert_equal(par[2], pct)
+
+
+@secbench_ffi_testcase
+def test_guessing_entropy_ties():
+    # With constant scores, the rank is uniform (as rank_of(randomize=True)).
+    scores = np.zeros((4, 16), dtype=np.float32)
+    ge, sr, _ = guessing_entropy(scores, 0, checkpoints=[4], n_experiments=4000)
+    assert abs(ge[0] - 7.5) < 0.5
+    assert abs(sr[0] - 1 / 16) < 0.02
+
+
+@secbench_ffi_testcase
+def test_guessing_entropy_nan():
+    scores = np.zeros((4, 16))
+    scores[2, 3] = np.nan
+    with pytest.raises(ValueError, match="NaN"):
+        guessing_entropy(scores, 0)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("bins", [1, 10, 1000])
+def test_key_rank_bounds_exact(bins):
+    tables = [np.log(np.random.uniform(size=n)) for n in [16, 8, 32]]
+    key = [3, 5, 7]
+    key_score = sum(t[k] for t, k in zip(tables, key))
+    better = sum(sum(c) > key_score for c in itertools.product(*tables))
+    lower, upper = key_rank_bounds(tables, key, bins=bins)
+    assert 2**lower <= better + 1 <= 2**upper
+
+
+@secbench_ffi_testcase
+def test_key_rank_bounds_aes():
+    scores = np.log(np.random.dirichlet(np.ones(256), size=16))
+    key = np.random.randint(0, 256, size=16)
+    lower, upper = key_rank_bounds(scores, key)
+    assert 0 <= lower <= upper <= 128 + 1e-9
+
+    # Best key: rank 1.
+    best = np.argmax(scores, axis=1)
+    lower, upper = key_rank_bounds(scores, best, bins=1 << 16)
+    assert lower == 0.0
+
+    # A 16-bit subkey combining the first two bytes.
+    merged = (scores[0][:, None] + scores[1][None, :]).flatten()
+    tables = [merged] + list(scores[2:])
+    key_16 = [key[0] * 256 + key[1]] + list(key[2:])
+    lower_16, upper_16 = key_rank_bounds(tables, key_16)
+    assert 0 <= lower_16 <= upper_16 <= 128 + 1e-9
+
+    with pytest.raises(ValueError):
+        key_rank_bounds(scores, key[:15])
+
+
+def _key_scores_around(key, n_candidates=4):
+    # The correct byte is among the n_candidates most likely values.
+    scores = np.full((len(key), 256), -50.0)
+    for i, k in enumerate(key):
+        candidates = (k + np.arange(n_candidates)) % 256
+        scores[i, candidates] = np.log(np.random.dirichlet(np.ones(n_candidates)))
+    return scores
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("parallel", [False, True])
+def test_enumerate_keys_aes(parallel):
+    key = aes_nist_key()
+    pt = np.arange(16, dtype=np.uint8)
+    ct = aes_encrypt(pt[None, :], key[None, :])[0]
+    scores = _key_scores_around(key[:4], n_candidates=4)
+    # Fix the last 12 bytes.
+    scores = np.vstack([scores, _key_scores_around(key[4:], n_candidates=1)])
+    found, n_tested = enumerate_keys(
+        scores, plaintext=pt, ciphertext=ct, batch_size=16, parallel=parallel
+    )
+    np.testing.assert_equal(found, key)
+    assert 1 <= n_tested <= 4**4
+
+    found, n_tested = enumerate_keys(
+        scores, plaintext=pt, ciphertext=ct ^ 1, budget=100, batch_size=16
+    )
+    assert found is None
+    assert n_tested == 100
+
+
+@secbench_ffi_testcase
+def test_enumerate_keys_callback():
+    scores = [np.log(np.random.dirichlet(np.ones(n))) for n in [16, 8, 32]]
+    secret = np.array([3, 5, 7])
+    seen = []
+
+    def key_test(keys):
+        seen.append(keys.copy())
+        match = np.flatnonzero(np.all(keys == secret, axis=1))
+        return int(match[0]) if len(match) else None
+
+    found, n_tested = enumerate_keys(scores, key_test=key_test, batch_size=10)
+    np.testing.assert_equal(found, secret)
+    seen = np.vstack(seen)
+    assert n_tested == np.flatnonzero(np.all(seen == secret, axis=1))[0] + 1
+    assert len({tuple(k) for k in seen}) == len(seen)
+
+    # Without match, all keys are enumerated.
+    found, n_tested = enumerate_keys(scores, key_test=lambda keys: None)
+    assert found is None
+    assert n_tested == 16 * 8 * 32
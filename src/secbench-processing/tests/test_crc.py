@@ -0,0 +1,161 @@
+###
+# Copyright CEA (Commissariat à l'énergie atomique et aux
+# énergies alternatives) (2017-2025)
+#
+# This software is governed by the CeCILL  license under French law and
+# abiding by the rules of distribution of free software.  You can  use,
+# modify and/ or redistribute the software under the terms of the CeCILL
+# license as circulated by CEA, CNRS and INRIA at the following URL
+# "http://www.cecill.info".
+#
+# As a counterpart to the access to the source code and  rights to copy,
+# modify and redistribute granted by the license, users are provided only
+# with a limited warranty  and the software's author,  the holder of the
+# economic rights,  and the successive licensors  have only  limited
+# liability.
+#
+# In this respect, the user's attention is drawn to the risks associated
+# with loading,  using,  modifying and/or developing or reproducing the
+# software by the user in light of its specific status of free software,
+# that may mean  that it is complicated to manipulate,  and  that  also
+# therefore means  that it is reserved for developers  and  experienced
+# professionals having in-depth computer knowledge. Users are therefore
+# encouraged to load and test the software's suitability as regards their
+# requirements in conditions enabling the security of their systems and/or
+# data to be ensured and,  more generally, to use and operate it in the
+# same conditions as regards security.
+#
+# The fact that you are presently reading this means that you have had
+# knowledge of the CeCILL license and that you accept its terms.
+###
+
+import binascii
+import zlib
+
+import numpy as np
+import pytest
+
+from secbench.processing import secbench_native_installed
+from secbench.processing.crypto.crc import Crc
+from secbench.processing.crypto.crc8 import crc8
+
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
+CHECK_MESSAGE = np.frombuffer(b"123456789", dtype=np.uint8)[np.newaxis, :]
+
+# (parameters, check value) from the catalogue of parametrised CRC algorithms.
+CRC_CATALOGUE = [
+    (dict(width=8, poly=0x4D, init=0xFF, refin=True, refout=True, xorout=0xFF), 0xD8),
+    (dict(width=12, poly=0x80F), 0xF5B),
+    (dict(width=16, poly=0x8005, refin=True, refout=True), 0xBB3D),
+    (dict(width=16, poly=0x1021, init=0xFFFF), 0x29B1),
+    (dict(width=16, poly=0x1021), 0x31C3),
+    (
+        dict(
+            width=32,
+            poly=0x04C11DB7,
+            init=0xFFFFFFFF,
+            refin=True,
+            refout=True,
+            xorout=0xFFFFFFFF,
+        ),
+        0xCBF43926,
+    ),
+    (
+        dict(
+            width=32,
+            poly=0x1EDC6F41,
+            init=0xFFFFFFFF,
+            refin=True,
+            refout=True,
+            xorout=0xFFFFFFFF,
+        ),
+        0xE3069283,
+    ),
+    (
+        dict(
+            width=64,
+            poly=0x42F0E1EBA9EA3693,
+            init=2**64 - 1,
+            refin=True,
+            refout=True,
+            xorout=2**64 - 1,
+        ),
+        0x995DC9BBDF1939FA,
+    ),
+]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("params, check", CRC_CATALOGUE)
+def test_crc_check_values(params, check):
+    crc = Crc(params.pop("width"), params.pop("poly"), **params)
+    assert crc.checksum(CHECK_MESSAGE)[0] == check
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize(
+    "name, check",
+    [
+        ("crc8", 0xD8),
+        ("crc16", 0xBB3D),
+        ("crc16_ccitt_false", 0x29B1),
+        ("crc16_xmodem", 0x31C3),
+        ("crc32", 0xCBF43926),
+        ("crc32c", 0xE3069283),
+    ],
+)
+def test_crc_from_name(name, check):
+    assert Crc.from_name(name).checksum(CHECK_MESSAGE)[0] == check
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("parallel", [False, True])
+def test_crc_reference_implementations(parallel):
+    messages = np.random.randint(0, 256, size=(50, 37), dtype=np.uint8)
+
+    crc32 = Crc.from_name("crc32")
+    checksums = crc32.checksum(messages, parallel=parallel)
+    assert checksums.dtype == np.uint64
+    for m, c in zip(messages, checksums):
+        assert c == zlib.crc32(m.tobytes())
+    # Registers of a reflected CRC are stored before xorout.
+    registers = crc32.registers(messages, parallel=parallel)
+    assert registers.shape == messages.shape
+    for m, r in zip(messages[:5], registers[:5]):
+        prefixes = [zlib.crc32(m[: i + 1].tobytes()) for i in range(m.shape[0])]
+        np.testing.assert_equal(r ^ 0xFFFFFFFF, prefixes)
+
+    xmodem = Crc.from_name("crc16_xmodem")
+    registers = xmodem.registers(messages, parallel=parallel)
+    np.testing.assert_equal(registers[:, -1], xmodem.checksum(messages))
+    for m, r in zip(messages[:5], registers[:5]):
+        prefixes = [binascii.crc_hqx(m[: i + 1].tobytes(), 0) for i in range(37)]
+        np.testing.assert_equal(r, prefixes)
+
+    koop = Crc.from_name("crc8")
+    for m, c in zip(messages, koop.checksum(messages)):
+        assert c == crc8(m)
+
+
+@secbench_ffi_testcase
+def test_crc_invalid_parameters():
+    with pytest.raises(ValueError, match="init"):
+        Crc(16, 0x1021, init=0x1FFFF)
+    with pytest.raises(ValueError, match="xorout"):
+        Crc(8, 0x07, xorout=0x100)
+    with pytest.raises(ValueError, match="poly"):
+        Crc(8, 0x107)
+    with pytest.raises(ValueError, match="init"):
+        Crc(16, 0x1021, init=-1)
+    with pytest.raises(ValueError, match="xorout"):
+        Crc(64, 0x1B, xorout=2**64)
+    for width in [0, 65]:
+        with pytest.raises(ValueError):
+            Crc(width, 0x1)
+    with pytest.raises(ValueError):
+        Crc.from_name("crc7")
+    # The largest values are accepted.
+    Crc(16, 0xFFFF, init=0xFFFF, xorout=0xFFFF)
@@ -33,13 +33,16 @@ from scipy import signal
 from scipy.fft import rfftn
 
 from secbench.processing import secbench_native_installed
+from secbench.processing._native import secbench_native_import
 from secbench.processing.helpers import check_array
 from secbench.processing.metrics import CondMeanVar
 from secbench.processing.signal import (
     fft_filter,
     moving_sum,
+    outlier_keep_mask,
     phase_correlation,
     rfft_mag,
+    trace_stats,
 )
 
 secbench_native_testcase = pytest.mark.skipif(
@@ -70,6 +73,29 @@ def test_rfft_mag(parallel):
         assert np.allclose(dst, ref, rtol=1e-4)
 
 
+@secbench_native_testcase
+def test_unsupported_dtype():
+    DtypeException = secbench_native_import("DtypeException")
+    native_rfft_mag = secbench_native_import("rfft_mag")
+    X = np.zeros((4, 64), dtype=np.int64)
+
+    # Call the binding directly, the Python wrapper checks input types itself.
+    with pytest.raises(DtypeException, match="int64") as exc:
+        native_rfft_mag(None, X, parallel=False, chunk_size=None)
+    assert exc.type is DtypeException
+    with pytest.raises(DtypeException, match="int32"):
+        native_rfft_mag(
+            None, X.astype(np.float32), parallel=False, chunk_size=None, dtype=np.int32
+        )
+    # Still a TypeError for callers that do not know about DtypeException.
+    with pytest.raises(TypeError):
+        native_rfft_mag(None, X, parallel=False, chunk_size=None)
+
+    acc = CondMeanVar(1, 64, 2)
+    with pytest.raises(DtypeException):
+        acc.process_block(X, np.zeros((4, 1), dtype=np.uint16))
+
+
 def ref_phase_correlation(xs, kernel):
     """
     Pure numpy-based implementation of phase correlation.
@@ -177,7 +203,9 @@ def test_fft_filter_two_pass(input_type, parallel):
 
 
 @secbench_native_testcase
-@pytest.mark.parametrize("input_type", (np.int8, np.int16, np.float32))
+@pytest.mark.parametrize(
+    "input_type", (np.int8, np.int16, np.uint8, np.uint16, np.float32, np.float64)
+)
 @pytest.mark.parametrize("parallel", (False, True))
 def test_moving_sum(input_type, parallel):
     x = np.arange(32, dtype=input_type)
@@ -186,6 +214,70 @@ def test_moving_sum(input_type, parallel):
     np.testing.assert_equal(y[:7], np.array([3, 6, 9, 12, 15, 18, 21]))
 
 
+def ref_trace_stats(X):
+    info = np.iinfo(X.dtype)
+    return np.stack(
+        [
+            X.min(axis=1),
+            X.max(axis=1),
+            X.mean(axis=1),
+            X.std(axis=1),
+            np.sum((X == info.min) | (X == info.max), axis=1),
+        ],
+        axis=1,
+    )
+
+
+def ref_outlier_keep_mask(stats, mad_threshold, max_saturated):
+    keep = np.ones(stats.shape[0], dtype=bool)
+    if max_saturated is not None:
+        keep &= stats[:, 4] <= max_saturated
+    for col in stats[:, :4].T:
+        med = np.median(col)
+        dev = np.abs(col - med)
+        mad = 1.4826 * np.median(dev)
+        if mad > 0:
+            keep &= dev <= mad_threshold * mad
+    return keep
+
+
+@secbench_native_testcase
+@pytest.mark.parametrize("input_type", (np.int8, np.int16, np.uint8, np.uint16))
+@pytest.mark.parametrize("parallel", (False, True))
+def test_trace_stats(input_type, parallel):
+    info = np.iinfo(input_type)
+    step = (int(info.max) - int(info.min)) // 8
+    low = int(info.min) + 3 * step
+    X = np.random.randint(low, low + 2 * step, size=(200, 301)).astype(input_type)
+    # Clipped traces.
+    X[3, 10:20] = info.max
+    X[4, :] = info.min
+    # Glitch with a large offset.
+    X[50] += 2 * step
+
+    stats = trace_stats(X, parallel=parallel)
+    assert stats.shape == (200, 5)
+    assert stats.dtype == np.float32
+    np.testing.assert_allclose(stats, ref_trace_stats(X), rtol=1e-5, atol=1e-3)
+    np.testing.assert_equal(trace_stats(X[7]), stats[7])
+
+    mask = outlier_keep_mask(stats, mad_threshold=5.0, max_saturated=0)
+    np.testing.assert_equal(mask, ref_outlier_keep_mask(stats, 5.0, 0))
+    assert not mask[3] and not mask[4] and not mask[50]
+    assert np.sum(mask) == 197
+    mask = outlier_keep_mask(stats, mad_threshold=5.0, max_saturated=None)
+    np.testing.assert_equal(mask, ref_outlier_keep_mask(stats, 5.0, None))
+    assert not mask[50]
+
+
+@secbench_native_testcase
+def test_trace_stats_empty():
+    stats = trace_stats(np.zeros((3, 0), dtype=np.int8))
+    assert np.all(np.isnan(stats[:, :4]))
+    np.testing.assert_equal(stats[:, 4], 0)
+    assert not np.any(outlier_keep_mask(stats))
+
+
 def cond_mean_var_model(data, labels, num_classes):
     m = np.zeros_like(data, shape=(num_classes, data.shape[1]))
     v = np.zeros_like(data, shape=(num_classes, data.shape[1]))
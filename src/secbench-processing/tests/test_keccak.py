@@ -29,10 +29,25 @@
 # knowledge of the CeCILL license and that you accept its terms.
 ###
 
+import hashlib
+
 import numpy as np
 import pytest
 
-from secbench.processing.crypto.keccak import Keccak_f
+from secbench.processing import secbench_native_installed
+from secbench.processing.crypto.keccak import (
+    RC,
+    RHO_OFFSETS,
+    Keccak_f,
+    keccak_f1600,
+    keccak_f1600_intermediates,
+    sha3,
+    shake,
+)
+
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
 
 
 def test_init():
@@ -108,4 +123,92 @@ def test_permutation():
         [0xf2, 0x9d, 0xe2, 0xed, 0xdc, 0x20, 0x55, 0x5c, 0x25, 0xa3, 0xcc, 0x82, 0x3a, 0xec, 0xce, 0xa0, 0x9c, 0x5d, 0xb0, 0xc2, 0xbe, 0xf5, 0xd6, 0x2f, 0x3f]],
         dtype=np.uint8)
     # fmt: on
-    assert np.all(result == expected)
\ No newline at end of file
+    assert np.all(result == expected)
+
+
+@secbench_ffi_testcase
+def test_native_keccak_f1600():
+    # Keccak-f[1600] of the all-zero state, from the Keccak team known answers.
+    out = keccak_f1600(np.zeros((1, 25), dtype=np.uint64))
+    assert out[0, 0] == 0xF1258F7940E1DDE7
+    assert out[0, 24] == 0xEAF1FF7B5CECA249
+
+    states = np.random.randint(0, 2**63, size=(20, 25), dtype=np.uint64)
+    np.testing.assert_equal(
+        keccak_f1600(states, parallel=True), keccak_f1600(states)
+    )
+
+
+def _rho_reference(states):
+    mask = (1 << 64) - 1
+    out = np.empty_like(states)
+    for i, r in enumerate(RHO_OFFSETS.astype(int)):
+        for n in range(states.shape[0]):
+            a = int(states[n, i])
+            out[n, i] = ((a << r) | (a >> (64 - r))) & mask
+    return out
+
+
+@secbench_ffi_testcase
+def test_native_keccak_f1600_intermediates():
+    states = np.random.randint(0, 2**63, size=(8, 25), dtype=np.uint64)
+    inter = keccak_f1600_intermediates(states)
+    assert inter.shape == (8, 24, 5, 25)
+    np.testing.assert_equal(inter[:, -1, -1], keccak_f1600(states))
+
+    model = Keccak_f(1600)
+    steps = [model.theta, _rho_reference, model.pi, model.chi]
+    prev = states
+    for r in range(24):
+        for j, step in enumerate(steps):
+            np.testing.assert_equal(
+                inter[:, r, j], step(prev.astype(np.uint64)), err_msg=f"{r}, {j}"
+            )
+            prev = inter[:, r, j]
+        expected = prev.copy()
+        expected[:, 0] ^= RC[r]
+        np.testing.assert_equal(inter[:, r, 4], expected)
+        prev = inter[:, r, 4]
+
+    sub = keccak_f1600_intermediates(states, rounds=[3, 0], steps=["chi", "theta"])
+    assert sub.shape == (8, 2, 2, 25)
+    np.testing.assert_equal(sub[:, 0, 0], inter[:, 3, 3])
+    np.testing.assert_equal(sub[:, 1, 1], inter[:, 0, 0])
+    with pytest.raises(ValueError):
+        keccak_f1600_intermediates(states, rounds=[24])
+    with pytest.raises(ValueError):
+        keccak_f1600_intermediates(states, steps=["unknown"])
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("bits", [224, 256, 384, 512])
+@pytest.mark.parametrize("length", [0, 1, 71, 72, 135, 136, 137, 300])
+def test_native_sha3(bits, length):
+    messages = np.random.randint(0, 256, size=(5, length), dtype=np.uint8)
+    digests = sha3(messages, bits=bits, parallel=True)
+    assert digests.shape == (5, bits // 8)
+    for m, d in zip(messages, digests):
+        expected = hashlib.new(f"sha3_{bits}", m.tobytes()).digest()
+        assert d.tobytes() == expected
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("security", [128, 256])
+@pytest.mark.parametrize("output_len", [1, 32, 168, 500])
+def test_native_shake(security, output_len):
+    messages = np.random.randint(0, 256, size=(5, 170), dtype=np.uint8)
+    outputs = shake(messages, output_len=output_len, security=security)
+    assert outputs.shape == (5, output_len)
+    for m, o in zip(messages, outputs):
+        expected = hashlib.new(f"shake_{security}", m.tobytes()).digest(output_len)
+        assert o.tobytes() == expected
+
+
+@secbench_ffi_testcase
+def test_native_sha3_errors():
+    messages = np.zeros((2, 4), dtype=np.uint8)
+    with pytest.raises(ValueError):
+        sha3(messages, bits=128)
+    with pytest.raises(ValueError):
+        shake(messages, output_len=16, security=64)
+
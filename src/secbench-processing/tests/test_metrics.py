@@ -203,4 +203,298 @@ def test_lra_colinear():
     train = np.random.normal(size=(5000, 10))
     cls = np.random.randint(0, 7, size=5000, dtype=np.uint8)
     with pytest.raises(ValueError, match="unable to find"):
-        metrics.LRA(model=lra_unpackbits)(train, cls)
\ No newline at end of file
+        metrics.LRA(model=lra_unpackbits)(train, cls)
+
+
+@secbench_ffi_testcase
+def test_select_pois():
+    snr = np.array(
+        [
+            [0.1, 0.5, 0.9, 0.8, 0.2, np.nan, 0.3, 0.7, 0.1, 0.0],
+            [0.0, 0.0, 0.1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.4],
+        ]
+    )
+    pois = metrics.select_pois(snr, k=3)
+    assert len(pois) == 2
+    np.testing.assert_equal(pois[0], [2, 3, 7])
+    np.testing.assert_equal(pois[1], [0, 2, 9])
+
+    pois = metrics.select_pois(snr, threshold=0.35)
+    np.testing.assert_equal(pois[0], [1, 2, 3, 7])
+    np.testing.assert_equal(pois[1], [9])
+    pois = metrics.select_pois(snr, threshold=[0.75, 0.05], parallel=True)
+    np.testing.assert_equal(pois[0], [2, 3])
+    np.testing.assert_equal(pois[1], [2, 9])
+    with pytest.raises(ValueError):
+        metrics.select_pois(snr, threshold=[0.1, 0.2, 0.3])
+
+    pois = metrics.select_pois(snr[0], k=3, min_distance=2)
+    np.testing.assert_equal(pois, [2, 4, 7])
+    pois = metrics.select_pois(-snr[0].astype(np.float32), k=1, absolute=True)
+    np.testing.assert_equal(pois, [2])
+
+    pois = metrics.select_pois(snr, k=2, min_distance=2, window=1)
+    np.testing.assert_equal(pois[0], [1, 2, 3, 6, 7, 8])
+    np.testing.assert_equal(pois[1], [1, 2, 3, 8, 9])
+    assert metrics.poi_windows(pois[0], half_width=1, n_samples=10) == [(0, 10)]
+    assert metrics.poi_windows([2, 7], half_width=1, n_samples=10) == [(1, 4), (6, 9)]
+
+
+@secbench_ffi_testcase
+def test_select_pois_snr():
+    n, samples = 5000, 40
+    y = np.random.randint(0, 9, size=(n, 2)).astype(np.uint16)
+    X = np.random.normal(size=(n, samples)).astype(np.float32)
+    X[:, 5] += y[:, 0]
+    X[:, 6] += 0.5 * y[:, 0]
+    X[:, 30] += y[:, 1]
+    snr = metrics.cond_mean_var(X, y, num_classes=9).freeze_snr()
+    pois = metrics.select_pois(snr, k=1)
+    np.testing.assert_equal(pois[0], [5])
+    np.testing.assert_equal(pois[1], [30])
+    pois = metrics.select_pois(snr, threshold=0.5)
+    np.testing.assert_equal(pois[0], [5, 6])
+
+
+def random_probas(n, classes, rng):
+    p = rng.random((n, classes)) ** 4
+    return p / p.sum(axis=1, keepdims=True)
+
+
+@secbench_ffi_testcase
+def test_information_from_probas():
+    rng = np.random.default_rng(0)
+    labels = rng.integers(0, 8, size=2000)
+
+    perfect = metrics.information_from_probas(np.eye(8)[labels], labels)
+    freqs = np.bincount(labels) / len(labels)
+    entropy = -np.sum(freqs * np.log2(freqs))
+    assert perfect.perceived == pytest.approx(entropy)
+    assert perfect.hypothetical == pytest.approx(entropy)
+    assert np.isnan(perfect.perceived_interval[0])
+
+    uniform = metrics.information_from_probas(
+        np.full((2000, 8), 1 / 8, dtype=np.float32), labels, prior=np.ones(8)
+    )
+    assert uniform.perceived == pytest.approx(0.0, abs=1e-6)
+    assert uniform.hypothetical == pytest.approx(0.0, abs=1e-6)
+
+    probas = random_probas(2000, 8, rng)
+    r = metrics.information_from_probas(probas, labels)
+    pi = entropy + np.mean(np.log2(probas[np.arange(2000), labels]))
+    hi = entropy + np.mean(np.sum(probas * np.log2(probas), axis=1))
+    assert r.perceived == pytest.approx(pi)
+    assert r.hypothetical == pytest.approx(hi)
+
+    # Classes are weighted according to the prior.
+    prior = np.arange(1, 9) / 36
+    r = metrics.information_from_probas(probas, labels, prior=prior)
+    log_p = np.log2(probas[np.arange(2000), labels])
+    pi = -np.sum(prior * np.log2(prior)) + sum(
+        prior[c] * np.mean(log_p[labels == c]) for c in range(8)
+    )
+    assert r.perceived == pytest.approx(pi)
+
+    with pytest.raises(ValueError):
+        metrics.information_from_probas(probas, labels, prior=np.ones(3))
+    with pytest.raises(ValueError):
+        metrics.information_from_probas(probas[:, :4], labels)
+
+
+@secbench_ffi_testcase
+def test_information_zero_proba():
+    rng = np.random.default_rng(4)
+    labels = rng.integers(0, 4, size=100)
+    probas = random_probas(100, 4, rng)
+    probas[0] = 0.0
+    probas[0, (labels[0] + 1) % 4] = 1.0
+    freqs = np.bincount(labels) / len(labels)
+    entropy = -np.sum(freqs * np.log2(freqs))
+
+    r = metrics.information_from_probas(probas, labels, min_proba=1e-6)
+    log_p = np.log2(np.maximum(probas[np.arange(100), labels], 1e-6))
+    assert np.isfinite(r.perceived)
+    assert r.perceived == pytest.approx(entropy + np.mean(log_p))
+    assert np.isfinite(metrics.information_from_probas(probas, labels).perceived)
+
+    r = metrics.information_from_probas(probas, labels, min_proba=0.0)
+    assert r.perceived == -np.inf
+    assert np.isfinite(r.hypothetical)
+    with pytest.raises(ValueError):
+        metrics.information_from_probas(probas, labels, min_proba=1.0)
+
+
+@secbench_ffi_testcase
+def test_information_bootstrap():
+    rng = np.random.default_rng(1)
+    labels = rng.integers(0, 4, size=1000)
+    probas = random_probas(1000, 4, rng)
+    r = metrics.information_from_probas(probas, labels, n_bootstrap=200, seed=3)
+    for value, (lo, hi) in [
+        (r.perceived, r.perceived_interval),
+        (r.hypothetical, r.hypothetical_interval),
+    ]:
+        assert lo < value < hi
+    r_par = metrics.information_from_probas(
+        probas, labels, n_bootstrap=200, seed=3, parallel=True
+    )
+    assert r_par == r
+    r_small = metrics.information_from_probas(
+        probas, labels, n_bootstrap=200, seed=3, confidence=0.5
+    )
+    assert r.perceived_interval[0] < r_small.perceived_interval[0]
+
+
+@secbench_ffi_testcase
+def test_information_learning_curve():
+    rng = np.random.default_rng(2)
+    labels = rng.integers(0, 4, size=500)
+    sizes = [100, 200, 400]
+    probas = [random_probas(500, 4, rng) for _ in sizes]
+    train_labels = [rng.integers(0, 4, size=s) for s in sizes]
+    train_probas = [np.eye(4)[y] * 0.9 + 0.025 for y in train_labels]
+    curve = metrics.information_learning_curve(
+        sizes,
+        probas,
+        labels,
+        train_probas=train_probas,
+        train_labels=train_labels,
+        n_bootstrap=10,
+    )
+    assert curve.perceived.shape == (3, 3)
+    assert curve.hypothetical.shape == (3, 3)
+    assert curve.training.shape == (3, 3)
+    expected = metrics.information_from_probas(probas[1], labels, n_bootstrap=10)
+    assert curve.perceived[1, 0] == expected.perceived
+    assert curve.perceived[1, 1] == expected.perceived_interval[0]
+    assert np.all(curve.training[:, 0] > curve.perceived[:, 0])
+
+    curve = metrics.information_learning_curve(sizes, np.stack(probas), labels)
+    assert curve.training is None
+    with pytest.raises(ValueError):
+        metrics.information_learning_curve(sizes[:2], probas, labels)
+
+
+@secbench_ffi_testcase
+def test_mia():
+    n = 20000
+    y = np.random.randint(0, 4, size=(n, 2))
+    X = np.random.normal(scale=0.2, size=(n, 6))
+    X[:, 1] += y[:, 0]
+    # Non-linear leakage, invisible to the correlation and the SNR of the
+    # Hamming weight.
+    X[:, 4] += np.array([0, 1, 1, 0])[y[:, 1]]
+
+    mi = metrics.mia(X, y, bins=8)
+    assert mi.shape == (2, 6)
+    assert mi[0, 1] > 1.5
+    assert mi[1, 4] > 0.8
+    assert np.all(np.delete(mi[0], 1) < 0.01)
+    assert np.all(np.delete(mi[1], 4) < 0.01)
+    plugin = metrics.mia(X, y, bins=8, bias_correction="none", parallel=True)
+    assert mi[0, 0] < plugin[0, 0]
+    assert metrics.mia(X, y[:, 0], num_classes=4).shape == (6,)
+    with pytest.raises(ValueError):
+        metrics.mia(X, y, bias_correction="unknown")
+
+
+@secbench_ffi_testcase
+def test_mia_histogram_merge():
+    y = np.random.randint(0, 9, size=(3000, 1)).astype(np.uint16)
+    X = np.random.randint(-128, 128, size=(3000, 20)).astype(np.int8)
+    X[:, 3] = y[:, 0] * 10
+
+    def make():
+        return metrics.MiaHistogram(1, 20, 9, range=(-128, 128), bins=32)
+
+    acc = make()
+    acc.process_block(X, y)
+    a, b = make(), make()
+    a.process_block(X[:1000], y[:1000])
+    b.process_block(X[1000:], y[1000:], parallel=True, chunk_size=3)
+    a.merge(b)
+    np.testing.assert_equal(a.histograms(), acc.histograms())
+    np.testing.assert_equal(a.freeze_samples_per_class(), acc.freeze_samples_per_class())
+    np.testing.assert_equal(a.freeze_mi(), acc.freeze_mi())
+    assert acc.histograms().shape == (1, 9, 20, 32)
+    assert np.argmax(acc.freeze_mi()[0]) == 3
+
+    with pytest.raises(ValueError):
+        a.merge(metrics.MiaHistogram(1, 20, 9, range=(0, 1), bins=32))
+    with pytest.raises(ValueError):
+        acc.process_block(X, y + 9)
+
+
+@secbench_ffi_testcase
+def test_ks_test():
+    n = 4000
+    y = np.random.randint(0, 2, size=n)
+    X = np.random.normal(size=(n, 5))
+    # Same mean, different variance: invisible to the t-test.
+    X[:, 2] *= 1 + 2 * y
+
+    t = metrics.welch_t_test(X, y)
+    assert abs(t[2]) < 5
+    d, p = metrics.ks_test(X, y)
+    assert d.shape == p.shape == (5,)
+    assert p[2] < 1e-10
+    assert np.all(np.delete(p, 2) > 1e-5)
+    assert np.all((0 <= d) & (d <= 1))
+
+    d2, p2 = metrics.ks_test(X, np.stack([y, 1 - y], axis=1), parallel=True)
+    assert d2.shape == (2, 5)
+    np.testing.assert_allclose(d2[0], d)
+    np.testing.assert_allclose(d2[1], d)
+    # Empty classes are ignored.
+    d3, p3 = metrics.ks_test(X, y * 2)
+    np.testing.assert_allclose(d3, d)
+    np.testing.assert_allclose(p3, p)
+    with pytest.raises(ValueError):
+        metrics.ks_test(X, y, num_classes=1)
+
+
+@secbench_ffi_testcase
+def test_ks_test_multi_class():
+    n = 6000
+    y = np.random.randint(0, 3, size=n)
+    X = np.random.normal(size=(n, 4))
+    # Only class 2 has a different distribution.
+    X[:, 1] *= 1 + 2 * (y == 2)
+
+    d, p = metrics.ks_test(X, y)
+    assert d.shape == p.shape == (4,)
+    assert p[1] < 1e-10
+    assert np.all(np.delete(p, 1) > 1e-5)
+
+    acc = metrics.DistributionTest(1, 4, 3, range=(-8, 8), bins=256)
+    acc.process_block(X[:3000], y[:3000, np.newaxis].astype(np.uint16))
+    other = metrics.DistributionTest(1, 4, 3, range=(-8, 8), bins=256)
+    other.process_block(X[3000:], y[3000:, np.newaxis].astype(np.uint16), parallel=True)
+    acc.merge(other)
+    d_acc, p_acc = acc.freeze_ks_test()
+    assert d_acc.shape == (1, 4)
+    assert p_acc[0, 1] < 1e-10
+    stat, p_chi2 = acc.freeze_chi2_test()
+    assert p_chi2[0, 1] < 1e-10
+    assert acc.freeze_samples_per_class().sum() == n
+
+
+@secbench_ffi_testcase
+def test_chi2_test():
+    n = 8000
+    y = np.random.randint(0, 4, size=(n, 2))
+    X = np.random.normal(size=(n, 6))
+    X[:, 1] *= 1 + y[:, 0]
+    X[:, 4] += y[:, 1]
+
+    stat, p = metrics.chi2_test(X, y, bins=8)
+    assert stat.shape == p.shape == (2, 6)
+    assert p[0, 1] < 1e-10
+    assert p[1, 4] < 1e-10
+    assert np.all(np.delete(p[0], 1) > 1e-5)
+    assert np.all(np.delete(p[1], 4) > 1e-5)
+    assert np.all(stat >= 0)
+
+    stat_0, p_0 = metrics.chi2_test(X, y[:, 0], num_classes=4, bins=8)
+    np.testing.assert_allclose(stat_0, stat[0])
+    np.testing.assert_allclose(p_0, p[0])
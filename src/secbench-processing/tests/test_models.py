@@ -33,14 +33,20 @@ import random
 import numpy as np
 import pytest
 
-from secbench.processing import InvalidInputError
+from secbench.processing import InvalidInputError, secbench_native_installed
+from secbench.processing import models
 from secbench.processing.models import (
+    hamming_distance,
     hamming_weight,
     lra_unpackbits,
     lra_unpackbits_2nd_order,
     unpackbits,
 )
 
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
 
 def test_hamming_weight():
     x = np.array([0xAAAAAAAA, 0x0], dtype=np.uint32)
@@ -136,4 +142,49 @@ def test_lra_unpackbits_example():
     )
 
     bs = lra_unpackbits_2nd_order(ys)
-    assert bs.shape == (3, 37)
\ No newline at end of file
+    assert bs.shape == (3, 37)
+
+
+def _random_unsigned(dtype, shape):
+    rng = np.random.default_rng()
+    return rng.integers(0, np.iinfo(dtype).max, size=shape, dtype=dtype, endpoint=True)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.uint8, np.uint16, np.uint32, np.uint64])
+@pytest.mark.parametrize("shape", [7, (3, 5), (2, 3, 5)])
+def test_native_models(monkeypatch, dtype, shape):
+    x = _random_unsigned(dtype, shape)
+    y = _random_unsigned(dtype, shape)
+    # Extreme values and a non-contiguous view.
+    x.flat[0], y.flat[0] = 0, np.iinfo(dtype).max
+    x_strided = np.swapaxes(x, 0, -1)
+
+    def run(x_strided):
+        results = [
+            hamming_weight(x),
+            hamming_weight(x_strided),
+            hamming_distance(x, y),
+            unpackbits(x_strided),
+        ]
+        for count in [0, 3]:
+            results.append(unpackbits(x, count=count))
+            for center in [False, True]:
+                for with_intercept in [False, True]:
+                    kwargs = dict(
+                        center=center, with_intercept=with_intercept, count=count
+                    )
+                    results.append(lra_unpackbits(x, **kwargs))
+                    if count or dtype in (np.uint8, np.uint16):
+                        results.append(lra_unpackbits_2nd_order(x, **kwargs))
+        return results
+
+    native = run(x_strided)
+    monkeypatch.setattr(models, "_native", None)
+    # The numpy implementation of unpackbits needs a contiguous last axis.
+    reference = run(np.ascontiguousarray(x_strided))
+    assert len(native) == len(reference)
+    for i, (actual, expected) in enumerate(zip(native, reference)):
+        assert actual.shape == expected.shape, f"output {i}"
+        np.testing.assert_equal(actual, expected, err_msg=f"output {i}")
+
@@ -27,6 +27,9 @@
 # The fact that you are presently reading this means that you have had
 # knowledge of the CeCILL license and that you accept its terms.
 
+import copy
+import pickle
+
 import numpy as np
 import pytest
 
@@ -48,4 +51,100 @@ def test_pcg32(state, inc):
     rng_2 = Pcg32(state, inc)
     dst = np.zeros(10, dtype=np.uint64)
     rng_2.fill(dst)
-    np.testing.assert_equal(samples, dst)
\ No newline at end of file
+    np.testing.assert_equal(samples, dst)
+
+
+@secbench_ffi_testcase
+def test_pcg32_reference():
+    # Outputs of pcg32_random_r after pcg32_srandom_r(&rng, 42, 54) in the
+    # reference C implementation.
+    rng = Pcg32(42, 54)
+    samples = [rng.generate() for _ in range(6)]
+    assert samples == [
+        0xA15C02B7,
+        0x7B47F409,
+        0xBA1D3330,
+        0x83D2F293,
+        0xBFA4784B,
+        0xCBED606E,
+    ]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.uint8, np.uint16, np.uint32, np.uint64])
+def test_pcg32_fill_int(dtype):
+    rng = Pcg32(42, 54)
+    outputs = np.array([rng.generate() for _ in range(8)], dtype=np.uint64)
+
+    dst = np.zeros(8, dtype=dtype)
+    Pcg32(42, 54).fill(dst)
+    np.testing.assert_equal(dst, outputs.astype(dtype))
+
+    packed = np.zeros(6, dtype=dtype)
+    rng = Pcg32(42, 54)
+    rng.fill(packed, packed=True)
+    n_outputs = -(-packed.nbytes // 4) if dtype in (np.uint8, np.uint16) else 6
+    words = outputs[:n_outputs].astype(np.uint32)
+    if dtype in (np.uint8, np.uint16):
+        np.testing.assert_equal(packed, words.view(dtype)[:6])
+    else:
+        np.testing.assert_equal(packed, words.astype(dtype))
+    assert rng.generate() == outputs[n_outputs]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.float32, np.float64])
+def test_pcg32_fill_float(dtype):
+    dst = np.zeros(10_000, dtype=dtype)
+    Pcg32(42, 54).fill(dst)
+    assert np.all((dst >= 0) & (dst < 1))
+    assert abs(dst.mean() - 0.5) < 0.02
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.uint8, np.uint32, np.float32])
+@pytest.mark.parametrize("packed", [False, True])
+def test_pcg32_fill_parallel(dtype, packed):
+    seq = Pcg32(0xDEADBEEF, 7)
+    par = Pcg32(0xDEADBEEF, 7)
+    a = np.zeros((37, 4099), dtype=dtype)
+    b = np.zeros((37, 4099), dtype=dtype)
+    seq.fill(a, packed=packed)
+    par.fill(b, packed=packed, parallel=True)
+    np.testing.assert_equal(a, b)
+    assert seq.generate() == par.generate()
+
+
+@secbench_ffi_testcase
+def test_pcg32_state():
+    rng = Pcg32(42, 54)
+    rng.generate()
+    state = rng.get_state()
+    expected = [rng.generate() for _ in range(4)]
+
+    restored = Pcg32(0, 0)
+    restored.set_state(state)
+    assert [restored.generate() for _ in range(4)] == expected
+
+    with pytest.raises(ValueError):
+        restored.set_state((state[0], state[1] ^ 1))
+
+
+@secbench_ffi_testcase
+def test_pcg32_pickle_copy():
+    rng = Pcg32(0xDEADBEEF, 7)
+    rng.generate()
+    for other in [pickle.loads(pickle.dumps(rng)), copy.copy(rng), copy.deepcopy(rng)]:
+        assert other.get_state() == rng.get_state()
+        assert other.generate() == rng.generate()
+
+
+@secbench_ffi_testcase
+def test_pcg32_advance():
+    rng = Pcg32(42, 54)
+    outputs = [rng.generate() for _ in range(6)]
+    rng = Pcg32(42, 54)
+    rng.advance(4)
+    assert rng.generate() == outputs[4]
+    rng.advance(-5)
+    assert rng.generate() == outputs[0]
@@ -0,0 +1,151 @@
+# Copyright CEA (Commissariat à l'énergie atomique et aux
+# énergies alternatives) (2017-2025)
+#
+# This software is governed by the CeCILL  license under French law and
+# abiding by the rules of distribution of free software.  You can  use,
+# modify and/ or redistribute the software under the terms of the CeCILL
+# license as circulated by CEA, CNRS and INRIA at the following URL
+# "http://www.cecill.info".
+#
+# As a counterpart to the access to the source code and  rights to copy,
+# modify and redistribute granted by the license, users are provided only
+# with a limited warranty  and the software's author,  the holder of the
+# economic rights,  and the successive licensors  have only  limited
+# liability.
+#
+# In this respect, the user's attention is drawn to the risks associated
+# with loading,  using,  modifying and/or developing or reproducing the
+# software by the user in light of its specific status of free software,
+# that may mean  that it is complicated to manipulate,  and  that  also
+# therefore means  that it is reserved for developers  and  experienced
+# professionals having in-depth computer knowledge. Users are therefore
+# encouraged to load and test the software's suitability as regards their
+# requirements in conditions enabling the security of their systems and/or
+# data to be ensured and,  more generally, to use and operate it in the
+# same conditions as regards security.
+#
+# The fact that you are presently reading this means that you have had
+# knowledge of the CeCILL license and that you accept its terms.
+
+import copy
+import pickle
+
+import numpy as np
+import pytest
+
+from secbench.processing import secbench_native_installed
+from secbench.processing.crypto import Pcg32, Prng
+
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
+
+@secbench_ffi_testcase
+def test_xorshift32():
+    rng = Prng.xorshift32(1)
+    assert rng.name == "xorshift32"
+    assert [rng.generate() for _ in range(3)] == [0x00042021, 0x04080601, 0x9DCCA8C5]
+
+
+@secbench_ffi_testcase
+def test_xoshiro128starstar():
+    rng = Prng.xoshiro128starstar([1, 2, 3, 4])
+    assert [rng.generate() for _ in range(6)] == [
+        11520,
+        0,
+        5927040,
+        70819200,
+        2031721883,
+        1637235492,
+    ]
+
+
+@secbench_ffi_testcase
+def test_galois_lfsr():
+    rng = Prng.galois_lfsr(0xB400, 0xACE1)
+    assert rng.generate() == 0x3B62C4E1
+
+
+@secbench_ffi_testcase
+def test_chacha20():
+    # RFC 8439, appendix A.1, test vector #1.
+    rng = Prng.chacha20(bytes(32))
+    keystream = np.zeros(8, dtype=np.uint8)
+    rng.fill(keystream, packed=True)
+    np.testing.assert_equal(
+        keystream, [0x76, 0xB8, 0xE0, 0xAD, 0xA0, 0xF1, 0x3D, 0x90]
+    )
+    with pytest.raises(ValueError):
+        Prng.chacha20(bytes(16))
+
+
+@secbench_ffi_testcase
+def test_prng_pcg32():
+    rng, ref = Prng.pcg32(42, 54), Pcg32(42, 54)
+    assert [rng.generate() for _ in range(4)] == [ref.generate() for _ in range(4)]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.uint8, np.uint16, np.uint32, np.float64])
+def test_prng_fill(dtype):
+    rng = Prng.xoshiro128starstar([5, 6, 7, 8])
+    other = copy.copy(rng)
+    dst = np.zeros(16, dtype=dtype)
+    rng.fill(dst)
+    outputs = np.array([other.generate() for _ in range(16)], dtype=np.uint32)
+    if dtype == np.float64:
+        np.testing.assert_equal(dst, outputs / 2.0**32)
+    else:
+        np.testing.assert_equal(dst, outputs.astype(dtype))
+
+
+def _all_prngs():
+    return [
+        Prng.pcg32(42, 54),
+        Prng.xorshift32(1),
+        Prng.xoshiro128starstar([1, 2, 3, 4]),
+        Prng.galois_lfsr(0xB400, 0xACE1),
+        Prng.chacha20(bytes(range(32)), bytes(12), 1),
+    ]
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("index", range(5))
+def test_prng_state(index):
+    rng = _all_prngs()[index]
+    # Stop in the middle of a ChaCha20 block.
+    for _ in range(21):
+        rng.generate()
+    state = rng.get_state()
+    assert state[0] == rng.name
+    expected = [rng.generate() for _ in range(40)]
+
+    rng.set_state(state)
+    assert [rng.generate() for _ in range(40)] == expected
+    restored = Prng.from_state(state)
+    assert restored.name == rng.name
+    assert [restored.generate() for _ in range(40)] == expected
+
+    rng.set_state(state)
+    restored = pickle.loads(pickle.dumps(rng))
+    assert [restored.generate() for _ in range(40)] == expected
+    restored = copy.deepcopy(rng)
+    assert [restored.generate() for _ in range(40)] == expected
+
+
+@secbench_ffi_testcase
+def test_prng_invalid_state():
+    with pytest.raises(ValueError):
+        Prng.from_state(("xorshift32", [1, 2]))
+    with pytest.raises(ValueError):
+        Prng.from_state(("xorshift32", [2**32]))
+    with pytest.raises(ValueError):
+        Prng.from_state(("pcg32", [1, 2]))
+    with pytest.raises(ValueError):
+        Prng.from_state(("unknown", [1]))
+    rng = Prng.xorshift32(1)
+    with pytest.raises(ValueError):
+        rng.set_state(("chacha20", [0] * 11))
+    # A failed restore leaves the generator unchanged.
+    assert rng.get_state() == ("xorshift32", [1])
@@ -0,0 +1,216 @@
+###
+# Copyright CEA (Commissariat à l'énergie atomique et aux
+# énergies alternatives) (2017-2025)
+#
+# This software is governed by the CeCILL  license under French law and
+# abiding by the rules of distribution of free software.  You can  use,
+# modify and/ or redistribute the software under the terms of the CeCILL
+# license as circulated by CEA, CNRS and INRIA at the following URL
+# "http://www.cecill.info".
+#
+# As a counterpart to the access to the source code and  rights to copy,
+# modify and redistribute granted by the license, users are provided only
+# with a limited warranty  and the software's author,  the holder of the
+# economic rights,  and the successive licensors  have only  limited
+# liability.
+#
+# In this respect, the user's attention is drawn to the risks associated
+# with loading,  using,  modifying and/or developing or reproducing the
+# software by the user in light of its specific status of free software,
+# that may mean  that it is complicated to manipulate,  and  that  also
+# therefore means  that it is reserved for developers  and  experienced
+# professionals having in-depth computer knowledge. Users are therefore
+# encouraged to load and test the software's suitability as regards their
+# requirements in conditions enabling the security of their systems and/or
+# data to be ensured and,  more generally, to use and operate it in the
+# same conditions as regards security.
+#
+# The fact that you are presently reading this means that you have had
+# knowledge of the CeCILL license and that you accept its terms.
+###
+
+import copy
+
+import numpy as np
+import pytest
+
+from secbench.processing import secbench_native_installed
+from secbench.processing.attacks import accumulate_key_scores
+from secbench.processing.crypto.aes import aes_sbox
+from secbench.processing.models import hamming_weight
+from secbench.processing.profiled import ClassScatter, GaussianTemplates
+
+secbench_ffi_testcase = pytest.mark.skipif(
+    not secbench_native_installed(), reason="secbench_ffi package is not installed"
+)
+
+
+def make_dataset(n, n_samples=6, noise=0.5, seed=0):
+    rng = np.random.default_rng(seed)
+    y = rng.integers(0, 9, size=n).astype(np.uint16)
+    X = rng.normal(scale=noise, size=(n, n_samples))
+    X[:, 1] += y
+    X[:, 4] -= 0.5 * y
+    return X, y
+
+
+@secbench_ffi_testcase
+def test_gaussian_templates_moments():
+    X, y = make_dataset(5000)
+    pois = [1, 2, 4]
+    tpl = GaussianTemplates(pois, 9)
+    tpl.process_block(X, y)
+
+    assert tpl.pois == pois
+    assert tpl.num_classes == 9
+    np.testing.assert_equal(tpl.samples_per_class(), np.bincount(y, minlength=9))
+
+    Xp = X[:, pois]
+    means = np.array([Xp[y == c].mean(axis=0) for c in range(9)])
+    np.testing.assert_allclose(tpl.means(), means, atol=1e-10)
+
+    centered = Xp - means[y]
+    cov = centered.T @ centered / (len(y) - 9)
+    np.testing.assert_allclose(tpl.covariance(), cov, atol=1e-10)
+
+
+@secbench_ffi_testcase
+@pytest.mark.parametrize("dtype", [np.int8, np.int16, np.float32])
+def test_gaussian_templates_merge(dtype):
+    X, y = make_dataset(4000, noise=3)
+    X = X.astype(dtype)
+    ref = GaussianTemplates([0, 1, 4], 9)
+    ref.process_block(X, y)
+
+    a = GaussianTemplates([0, 1, 4], 9)
+    a.process_block(X[:1500], y[:1500])
+    b = copy.copy(a)
+    b.process_block(X[1500:], y[1500:], parallel=True, chunk_size=100)
+    a.process_block(X[1500:], y[1500:])
+    np.testing.assert_allclose(a.covariance(), b.covariance(), atol=1e-9)
+
+    c = GaussianTemplates([0, 1, 4], 9)
+    c.process_block(X[1500:], y[1500:])
+    m = GaussianTemplates([0, 1, 4], 9)
+    m.process_block(X[:1500], y[:1500])
+    m.merge(c)
+    np.testing.assert_allclose(m.means(), ref.means(), atol=1e-9)
+    np.testing.assert_allclose(m.covariance(), ref.covariance(), atol=1e-9)
+
+
+@secbench_ffi_testcase
+def test_gaussian_templates_errors():
+    X, y = make_dataset(100)
+    tpl = GaussianTemplates([0, 10], 9)
+    with pytest.raises(ValueError):
+        tpl.process_block(X, y)
+    tpl = GaussianTemplates([0, 1], 4)
+    with pytest.raises(ValueError):
+        tpl.process_block(X, y)
+    tpl = GaussianTemplates([0, 1], 9)
+    with pytest.raises(ValueError):
+        tpl.log_likelihood(X)
+    X[10, 1] = np.nan
+    tpl.process_block(X, y)
+    with pytest.raises(ValueError, match="not finite"):
+        tpl.log_likelihood(X)
+
+
+@secbench_ffi_testcase
+def test_gaussian_templates_classify():
+    X, y = make_dataset(20_000, noise=0.2)
+    tpl = GaussianTemplates([1, 4], 9)
+    tpl.process_block(X[:10_000], y[:10_000])
+
+    ll = tpl.log_likelihood(X[10_000:])
+    assert ll.shape == (10_000, 9)
+    assert np.mean(np.argmax(ll, axis=1) == y[10_000:]) > 0.95
+
+    lp = tpl.log_likelihood(X[10_000:], normalize=True, parallel=True)
+    np.testing.assert_allclose(np.exp(lp).sum(axis=1), 1.0, rtol=1e-9)
+    # Normalization only shifts each row by a constant.
+    shift = lp - ll
+    np.testing.assert_allclose(shift, np.repeat(shift[:, :1], 9, axis=1), atol=1e-6)
+
+    # Templates are updated when more traces are accumulated.
+    tpl.process_block(X[10_000:], y[10_000:])
+    ref = GaussianTemplates([1, 4], 9)
+    ref.process_block(X, y)
+    np.testing.assert_allclose(tpl.log_likelihood(X[:10]), ref.log_likelihood(X[:10]))
+
+
+@secbench_ffi_testcase
+def test_gaussian_templates_aes_attack():
+    rng = np.random.default_rng(1)
+    key = 0x2B
+
+    def leak(pts):
+        hw = hamming_weight(aes_sbox(pts ^ key)).astype(np.float64)
+        X = rng.normal(scale=1.0, size=(len(pts), 4))
+        X[:, 2] += hw
+        return X, hw.astype(np.uint16)
+
+    pts = rng.integers(0, 256, size=20_000, dtype=np.uint8)
+    X, y = leak(pts)
+    tpl = GaussianTemplates([2], 9)
+    tpl.process_block(X, y)
+
+    pts = rng.integers(0, 256, size=200, dtype=np.uint8)
+    X, _ = leak(pts)
+    log_probas = tpl.log_likelihood(X, normalize=True)
+    guesses = np.arange(256, dtype=np.uint8)
+    labels = hamming_weight(aes_sbox(guesses[:, None] ^ pts[None, :]))
+    scores = accumulate_key_scores(log_probas, labels=labels.astype(np.uint16))
+    assert np.argmax(scores[-1]) == key
+
+
+@secbench_ffi_testcase
+def test_class_scatter():
+    X, y = make_dataset(5000)
+    pois = list(range(1, 5))
+    acc = ClassScatter(range(1, 5), 9)
+    acc.process_block(X[:2000], y[:2000])
+    other = ClassScatter(pois, 9)
+    other.process_block(X[2000:], y[2000:], parallel=True, chunk_size=500)
+    acc.merge(other)
+    assert acc.pois == pois
+
+    Xp = X[:, pois]
+    means = np.array([Xp[y == c].mean(axis=0) for c in range(9)])
+    np.testing.assert_allclose(acc.means(), means, atol=1e-10)
+    centered = Xp - means[y]
+    sw = centered.T @ centered
+    np.testing.assert_allclose(acc.within_scatter(), sw, atol=1e-8)
+    counts = np.bincount(y, minlength=9)
+    delta = means - Xp.mean(axis=0)
+    sb = (delta * counts[:, None]).T @ delta
+    np.testing.assert_allclose(acc.between_scatter(), sb, atol=1e-8)
+
+    values, components = acc.lda()
+    assert components.shape == (4, 4)
+    w = components[0]
+    np.testing.assert_allclose(sb @ w, values[0] * (sw @ w), rtol=1e-8)
+    # Leakage is along samples 1 and 4 only.
+    assert abs(w[1]) < 0.1 and abs(w[2]) < 0.1
+
+    values, components = acc.class_pca(2)
+    assert components.shape == (2, 4)
+    cov = np.cov(means, rowvar=False)
+    np.testing.assert_allclose(cov @ components.T, components.T * values, atol=1e-8)
+
+    with pytest.raises(ValueError):
+        acc.merge(ClassScatter([0, 1], 9))
+    with pytest.raises(ValueError):
+        ClassScatter(pois, 9).lda()
+
+
+@secbench_ffi_testcase
+def test_class_scatter_not_finite():
+    X, y = make_dataset(500)
+    X[10, 2] = np.nan
+    acc = ClassScatter(range(1, 5), 9)
+    acc.process_block(X, y)
+    with pytest.raises(ValueError):
+        acc.lda()
+    with pytest.raises(ValueError):
+        acc.class_pca()